@@ -4,7 +4,7 @@ import { useEffect, useRef, useState, useCallback } from 'react';
 import { loadSimulation, type SimulationUniverse } from '@/app/_lib/wasm/loadSimulation';
 import { Renderer } from '@/app/_lib/renderer';
 import { InputHandler } from '@/app/_lib/input';
-import { ghostStamp, GHOST_SPECIES } from '@/app/_lib/stamps';
+import { GHOST_SPECIES, GHOST_STAMP } from '@/app/_lib/stamps';
 import { useTheme } from '@/app/_lib/useTheme';
 
 const GRID_WIDTH = 256;
@@ -20,8 +20,19 @@ const ELEMENTS = [
   { label: 'Fire', species: 4, color: 'var(--el-fire)', rawColor: '#e85d2a', shortcut: 'F' },
   { label: 'Ghost', species: 5, color: '#f0f0f7', rawColor: '#f0f0f7', shortcut: 'G' },
   { label: 'Smoke', species: 6, color: '#7a7a7a', rawColor: '#7a7a7a', shortcut: 'K' },
+  { label: 'Steam', species: 7, color: '#ccd6e6', rawColor: '#ccd6e6', shortcut: 'M' },
+  { label: 'Lava', species: 8, color: '#e64c14', rawColor: '#e64c14', shortcut: 'L' },
+  { label: 'Stone', species: 9, color: '#66615c', rawColor: '#66615c', shortcut: 'N' },
+  { label: 'Acid', species: 10, color: '#7ae63d', rawColor: '#7ae63d', shortcut: 'A' },
+  { label: 'Glass', species: 11, color: '#b2d9e0', rawColor: '#b2d9e0', shortcut: 'B' },
+  { label: 'Ice', species: 12, color: '#ade0f0', rawColor: '#ade0f0', shortcut: 'I' },
+  { label: 'Wood', species: 13, color: '#7a5229', rawColor: '#7a5229', shortcut: 'D' },
+  { label: 'Oil', species: 14, color: '#4c3d26', rawColor: '#4c3d26', shortcut: 'O' },
+  { label: 'Gunpowder', species: 15, color: '#595452', rawColor: '#595452', shortcut: 'P' },
 ] as const;
 
+/** `PaintMode::FillEmpty`: only paint empty cells; the eraser clears all. */
+const PAINT_FILL_EMPTY = 0;
 const BRUSH_SIZES = [0, 1, 2, 4, 6, 10] as const;
 
 /** Refs bundle passed to the frame loop to avoid per-ref closures. */
@@ -51,14 +62,23 @@ function runFrame(refs: SimRefs) {
   try {
     const commands = input.flush();
     for (const cmd of commands) {
-      if (cmd.species === GHOST_SPECIES) {
-        const group = universe.alloc_ghost_group();
-        const stampCmds = ghostStamp(cmd.x, cmd.y, GHOST_SPECIES, GRID_WIDTH, GRID_HEIGHT);
-        for (const sc of stampCmds) {
-          universe.set_ghost(sc.x, sc.y, group, sc.rb);
+      if ('action' in cmd) {
+        switch (cmd.action) {
+          case 'beginStroke': universe.begin_stroke(); break;
+          case 'endStroke': universe.end_stroke(); break;
+          case 'undo': universe.undo(); break;
+          case 'redo': universe.redo(); break;
+        }
+      } else if (cmd.species === GHOST_SPECIES) {
+        try {
+          universe.place_stamp(GHOST_STAMP, cmd.x1, cmd.y1);
+        } catch {
+          // Not enough empty room here; the ghost is simply not placed.
         }
       } else {
-        universe.set_cell(cmd.x, cmd.y, cmd.species);
+        universe.paint_line(
+          cmd.x0, cmd.y0, cmd.x1, cmd.y1, cmd.radius, cmd.species, PAINT_FILL_EMPTY, 0,
+        );
       }
     }
 
@@ -173,6 +193,12 @@ export default function SimulationCanvas() {
     const handler = (e: KeyboardEvent) => {
       if (e.target instanceof HTMLInputElement) return;
       const key = e.key.toUpperCase();
+      if ((e.ctrlKey || e.metaKey) && (key === 'Z' || key === 'Y')) {
+        e.preventDefault();
+        if (key === 'Y' || e.shiftKey) inputRef.current?.redo();
+        else inputRef.current?.undo();
+        return;
+      }
       for (const el of ELEMENTS) {
         if (el.shortcut === key) { setSelectedSpecies(el.species); return; }
       }
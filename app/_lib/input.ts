@@ -1,10 +1,26 @@
-/** Draw command produced by the input handler. */
+/**
+ * Draw command produced by the input handler: one brush stroke segment
+ * from (x0, y0) to (x1, y1). A click is a segment with equal endpoints.
+ */
 export interface DrawCommand {
-  x: number;
-  y: number;
+  x0: number;
+  y0: number;
+  x1: number;
+  y1: number;
+  radius: number;
   species: number;
 }
 
+/**
+ * History command produced by the input handler. Strokes group the draw
+ * commands of one pointer press so they are undone together.
+ */
+export interface HistoryCommand {
+  action: "beginStroke" | "endStroke" | "undo" | "redo";
+}
+
+export type InputCommand = DrawCommand | HistoryCommand;
+
 // ── Pure helper functions (exported for testing) ──────────
 
 /**
@@ -82,8 +98,8 @@ export function brushCells(
 
 /**
  * Handles mouse/touch input on the simulation canvas.
- * Converts pointer events to grid-space draw commands with
- * Bresenham interpolation and configurable brush radius.
+ * Converts pointer events to grid-space stroke segments; the simulation
+ * paints each segment with a round brush in a single call.
  */
 export class InputHandler {
   private canvas: HTMLCanvasElement;
@@ -94,7 +110,7 @@ export class InputHandler {
   private stampMode: boolean = false;
   private isDrawing: boolean = false;
   private lastPos: { x: number; y: number } | null = null;
-  private commands: DrawCommand[] = [];
+  private commands: InputCommand[] = [];
 
   // Bound listeners for cleanup
   private onPointerDown: (e: PointerEvent) => void;
@@ -127,8 +143,18 @@ export class InputHandler {
     this.stampMode = stamp;
   }
 
-  /** Returns pending draw commands since last call, then clears the buffer. */
-  flush(): DrawCommand[] {
+  /** Queue an undo of the latest stroke, in order with pending draws. */
+  undo(): void {
+    this.commands.push({ action: "undo" });
+  }
+
+  /** Queue a redo of the latest undone stroke. */
+  redo(): void {
+    this.commands.push({ action: "redo" });
+  }
+
+  /** Returns pending commands since last call, then clears the buffer. */
+  flush(): InputCommand[] {
     const out = this.commands;
     this.commands = [];
     return out;
@@ -143,25 +169,25 @@ export class InputHandler {
   // ── Private ──────────────────────────────────────────────
 
   private handlePointerDown(e: PointerEvent): void {
+    if (this.isDrawing) this.commands.push({ action: "endStroke" });
     this.isDrawing = true;
+    this.commands.push({ action: "beginStroke" });
     const pos = this.screenToGrid(e);
     this.lastPos = pos;
-    this.paintBrush(pos.x, pos.y);
+    this.paintSegment(pos.x, pos.y, pos.x, pos.y);
   }
 
   private handlePointerMove(e: PointerEvent): void {
       if (!this.isDrawing) return;
       if (this.stampMode) return; // stamp = one placement per mousedown only
       const pos = this.screenToGrid(e);
-      if (this.lastPos) {
-        this.interpolateAndPaint(this.lastPos.x, this.lastPos.y, pos.x, pos.y);
-      } else {
-        this.paintBrush(pos.x, pos.y);
-      }
+      const from = this.lastPos ?? pos;
+      this.paintSegment(from.x, from.y, pos.x, pos.y);
       this.lastPos = pos;
     }
 
   private handlePointerUp(): void {
+    if (this.isDrawing) this.commands.push({ action: "endStroke" });
     this.isDrawing = false;
     this.lastPos = null;
   }
@@ -180,28 +206,14 @@ export class InputHandler {
   }
 
   /**
-   * Bresenham line interpolation between two grid points.
-   * Paints a brush circle at every point along the line.
-   */
-  private interpolateAndPaint(x0: number, y0: number, x1: number, y1: number): void {
-    const points = interpolateLine(x0, y0, x1, y1);
-    for (const p of points) {
-      this.paintBrush(p.x, p.y);
-    }
-  }
-
-  /**
-   * Paint a filled circle of cells around (cx, cy) with the current brush radius.
+   * Queue a brush stroke from (x0, y0) to (x1, y1) with the current radius.
    */
-  private paintBrush(cx: number, cy: number): void {
+  private paintSegment(x0: number, y0: number, x1: number, y1: number): void {
     if (this.stampMode) {
-      // Stamp mode: emit only the center point; the consumer expands it
-      this.commands.push({ x: cx, y: cy, species: this.selectedSpecies });
+      // Stamp mode: emit only the end point; the consumer expands it
+      this.commands.push({ x0: x1, y0: y1, x1, y1, radius: 0, species: this.selectedSpecies });
       return;
     }
-    const cells = brushCells(cx, cy, this.brushRadius, this.gridWidth, this.gridHeight);
-    for (const c of cells) {
-      this.commands.push({ x: c.x, y: c.y, species: this.selectedSpecies });
-    }
+    this.commands.push({ x0, y0, x1, y1, radius: this.brushRadius, species: this.selectedSpecies });
   }
 }
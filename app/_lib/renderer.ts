@@ -152,6 +152,15 @@ fn fs(in: VertexOutput) -> @location(0) vec4<f32> {
             let gray = mix(dark_gray, light_gray, t_mode);
             color = vec4<f32>(gray, gray, gray * mix(1.08, 0.98, t_mode), 1.0);
         }
+        case 7u: { color = vec4<f32>(0.8, 0.84, 0.9, 1.0); }     // Steam: pale blue-white
+        case 8u: { color = vec4<f32>(0.9, 0.3, 0.08, 1.0); }     // Lava: orange-red
+        case 9u: { color = vec4<f32>(0.4, 0.38, 0.36, 1.0); }    // Stone: dark gray
+        case 10u: { color = vec4<f32>(0.48, 0.9, 0.24, 1.0); }   // Acid: green
+        case 11u: { color = vec4<f32>(0.7, 0.85, 0.88, 1.0); }   // Glass: pale cyan
+        case 12u: { color = vec4<f32>(0.68, 0.88, 0.94, 1.0); }  // Ice: icy blue
+        case 13u: { color = vec4<f32>(0.48, 0.32, 0.16, 1.0); }  // Wood: brown
+        case 14u: { color = vec4<f32>(0.3, 0.24, 0.15, 1.0); }   // Oil: dark brown
+        case 15u: { color = vec4<f32>(0.35, 0.33, 0.32, 1.0); }  // Gunpowder: charcoal
         default: { color = vec4<f32>(1.0, 0.0, 1.0, 1.0); }
     }
     return color;
@@ -9,8 +9,26 @@ export interface SimulationExports {
 export interface SimulationUniverse {
   tick(): void;
   set_cell(x: number, y: number, species: number): void;
+  paint_circle(cx: number, cy: number, r: number, species: number, mode: number, target: number): void;
+  paint_line(
+    x0: number, y0: number, x1: number, y1: number,
+    radius: number, species: number, mode: number, target: number,
+  ): void;
+  paint_rect(x0: number, y0: number, x1: number, y1: number, species: number, mode: number, target: number): void;
+  flood_fill(x: number, y: number, species: number, connectivity: number): number;
+  replace_species(from: number, to: number, x0: number, y0: number, x1: number, y1: number): void;
+  copy_region(x0: number, y0: number, x1: number, y1: number): SimulationPattern;
+  cut_region(x0: number, y0: number, x1: number, y1: number): SimulationPattern;
+  paste_pattern(x: number, y: number, pattern: SimulationPattern, mode: number): void;
+  begin_stroke(): void;
+  end_stroke(): void;
+  undo(): boolean;
+  redo(): boolean;
+  can_undo(): boolean;
+  can_redo(): boolean;
   alloc_ghost_group(): number;
   set_ghost(x: number, y: number, group: number, rb: number): void;
+  place_stamp(name: string, x: number, y: number): void;
   set_cursor(x: number, y: number): void;
   clear_cursor(): void;
   species_ptr(): number;
@@ -20,6 +38,18 @@ export interface SimulationUniverse {
   free(): void;
 }
 
+export interface SimulationPattern {
+  width(): number;
+  height(): number;
+  mask_empty(): void;
+  rotate_cw(): void;
+  rotate_ccw(): void;
+  rotate_180(): void;
+  flip_horizontal(): void;
+  flip_vertical(): void;
+  free(): void;
+}
+
 let wasmInstance: SimulationExports | null = null;
 let wasmLoading: Promise<SimulationExports> | null = null;
 
@@ -0,0 +1,211 @@
+//! `sandsim`: run the simulation headless from the command line.
+//!
+//! Loads a world from a snapshot (`Grid::to_snapshot`), a compact encoding
+//! (`Grid::to_compact`) or a palette PNG (`Grid::from_image`), runs it for
+//! a number of ticks, and reports per-tick timings and the final
+//! `Grid::state_hash`. Optionally writes the final snapshot, PNG frames
+//! along the way and a per-tick CSV of timings.
+//!
+//! ```text
+//! sandsim world.png --ticks 600 --frames out/ --frame-every 60 --output end.snds
+//! sandsim world.snds --ticks 1000 --check-hash 9f3c1a0b5e7d2468
+//! ```
+
+mod stats;
+
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use simulation::image::{Shading, UnknownColors};
+use simulation::Grid;
+use stats::TickStats;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+const SNAPSHOT_MAGIC: &[u8] = b"SNDS";
+const COMPACT_MAGIC: &[u8] = b"SNDC";
+const PNG_MAGIC: &[u8] = b"\x89PNG";
+
+#[derive(Debug, Parser)]
+#[command(version, about = "Run the falling sand simulation headless")]
+struct Args {
+    /// World to load: a snapshot, compact encoding or palette PNG.
+    input: PathBuf,
+
+    /// Number of ticks to run.
+    #[arg(short, long, default_value_t = 100)]
+    ticks: u64,
+
+    /// Run at this many ticks per second instead of as fast as possible.
+    #[arg(long, value_name = "HZ", value_parser = parse_rate)]
+    rate: Option<Duration>,
+
+    /// Write the final state here as a snapshot.
+    #[arg(short, long, value_name = "FILE")]
+    output: Option<PathBuf>,
+
+    /// Write PNG frames into this directory.
+    #[arg(long, value_name = "DIR")]
+    frames: Option<PathBuf>,
+
+    /// Write a frame every this many ticks.
+    #[arg(long, value_name = "N", default_value_t = 10, value_parser = clap::value_parser!(u64).range(1..))]
+    frame_every: u64,
+
+    /// Write per-tick timings as CSV (`tick,micros,active_chunks`).
+    #[arg(long, value_name = "FILE")]
+    stats: Option<PathBuf>,
+
+    /// Snap PNG colours outside the palette to the nearest species
+    /// instead of failing.
+    #[arg(long)]
+    nearest_colors: bool,
+
+    /// Fail unless the final state hash equals this (hex).
+    #[arg(long, value_name = "HASH", value_parser = parse_hash)]
+    check_hash: Option<u64>,
+}
+
+/// Parse a tick rate in Hz into the period between ticks.
+fn parse_rate(s: &str) -> Result<Duration, String> {
+    s.parse::<f64>()
+        .ok()
+        .filter(|hz| hz.is_finite() && *hz > 0.0)
+        .and_then(|hz| Duration::try_from_secs_f64(1.0 / hz).ok())
+        .ok_or_else(|| format!("`{s}` is not a usable tick rate"))
+}
+
+fn parse_hash(s: &str) -> Result<u64, String> {
+    let digits = s.strip_prefix("0x").unwrap_or(s);
+    u64::from_str_radix(digits, 16).map_err(|err| format!("`{s}` is not a hex hash: {err}"))
+}
+
+/// Decode a world in any supported format, chosen by its leading bytes.
+fn load_grid(bytes: &[u8], unknown: UnknownColors) -> Result<Grid> {
+    if bytes.starts_with(SNAPSHOT_MAGIC) {
+        Ok(Grid::from_snapshot(bytes)?)
+    } else if bytes.starts_with(COMPACT_MAGIC) {
+        Ok(Grid::from_compact(bytes)?)
+    } else if bytes.starts_with(PNG_MAGIC) {
+        Ok(Grid::from_image(bytes, unknown)?)
+    } else {
+        bail!("not a snapshot, compact encoding or PNG")
+    }
+}
+
+fn write_frame(grid: &Grid, dir: &Path) -> Result<()> {
+    let path = dir.join(format!("frame_{:06}.png", grid.tick_count));
+    fs::write(&path, grid.to_image(Shading::Game))
+        .with_context(|| format!("writing {}", path.display()))
+}
+
+fn run(args: &Args) -> Result<()> {
+    let bytes =
+        fs::read(&args.input).with_context(|| format!("reading {}", args.input.display()))?;
+    let unknown = if args.nearest_colors {
+        UnknownColors::Nearest
+    } else {
+        UnknownColors::Reject
+    };
+    let mut grid =
+        load_grid(&bytes, unknown).with_context(|| format!("loading {}", args.input.display()))?;
+    if let Some(dir) = &args.frames {
+        fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+    }
+
+    let mut stats = TickStats::default();
+    let mut csv = String::from("tick,micros,active_chunks\n");
+    let mut deadline = Instant::now();
+    for _ in 0..args.ticks {
+        let tick_start = Instant::now();
+        grid.tick();
+        let elapsed = tick_start.elapsed();
+        stats.record(elapsed);
+        if args.stats.is_some() {
+            let _ = writeln!(
+                csv,
+                "{},{},{}",
+                grid.tick_count,
+                elapsed.as_micros(),
+                grid.active_chunk_count()
+            );
+        }
+        if let Some(dir) = &args.frames {
+            if grid.tick_count % args.frame_every == 0 {
+                write_frame(&grid, dir)?;
+            }
+        }
+        if let Some(period) = args.rate {
+            // Sleep to absolute deadlines so slow ticks don't accumulate drift.
+            deadline += period;
+            std::thread::sleep(deadline.saturating_duration_since(Instant::now()));
+        }
+    }
+
+    if let Some(path) = &args.stats {
+        fs::write(path, csv).with_context(|| format!("writing {}", path.display()))?;
+    }
+    if let Some(path) = &args.output {
+        fs::write(path, grid.to_snapshot())
+            .with_context(|| format!("writing {}", path.display()))?;
+    }
+    if let Some(summary) = stats.summary() {
+        eprintln!("{summary}");
+    }
+
+    let hash = grid.state_hash();
+    println!("{hash:016x}");
+    if let Some(expected) = args.check_hash {
+        if hash != expected {
+            bail!(
+                "state hash mismatch after tick {}: expected {expected:016x}, got {hash:016x}",
+                grid.tick_count
+            );
+        }
+    }
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    run(&Args::parse())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use simulation::cell::{Cell, Species};
+
+    #[test]
+    fn loads_every_input_format_by_magic() {
+        let mut grid = Grid::with_seed(8, 6, 3);
+        grid.set(2, 1, Cell::new(Species::Sand));
+        let reject = UnknownColors::Reject;
+
+        let loaded = load_grid(&grid.to_snapshot(), reject).unwrap();
+        assert_eq!(loaded.state_hash(), grid.state_hash());
+        let loaded = load_grid(&grid.to_compact(true), reject).unwrap();
+        assert_eq!(loaded.state_hash(), grid.state_hash());
+        let loaded = load_grid(&grid.to_image(Shading::Flat), reject).unwrap();
+        assert_eq!(loaded.get(2, 1).species, Species::Sand);
+
+        assert!(load_grid(b"GIF89a", reject).is_err());
+    }
+
+    #[test]
+    fn parses_hashes_and_rates() {
+        assert_eq!(parse_hash("0x00ff"), Ok(255));
+        assert_eq!(parse_hash("DEADbeef"), Ok(0xdead_beef));
+        assert!(parse_hash("xyz").is_err());
+        assert_eq!(parse_rate("20"), Ok(Duration::from_millis(50)));
+        assert!(parse_rate("0").is_err());
+        assert!(parse_rate("inf").is_err());
+        assert!(parse_rate("1e-300").is_err(), "period overflows a Duration");
+    }
+
+    #[test]
+    fn cli_definition_is_valid() {
+        use clap::CommandFactory;
+        Args::command().debug_assert();
+    }
+}
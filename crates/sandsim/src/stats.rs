@@ -0,0 +1,83 @@
+//! Per-tick timing collection and summary.
+
+use std::fmt;
+use std::time::Duration;
+
+/// Wall-clock duration of every tick in a run.
+#[derive(Debug, Default)]
+pub struct TickStats {
+    samples: Vec<Duration>,
+}
+
+impl TickStats {
+    pub fn record(&mut self, elapsed: Duration) {
+        self.samples.push(elapsed);
+    }
+
+    /// Aggregate figures over all recorded ticks, or `None` before the first.
+    #[must_use]
+    pub fn summary(&self) -> Option<Summary> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let mut sorted = self.samples.clone();
+        sorted.sort_unstable();
+        let total: Duration = sorted.iter().sum();
+        let percentile = |p: usize| sorted[(sorted.len() - 1) * p / 100];
+        Some(Summary {
+            ticks: sorted.len(),
+            total,
+            mean: total / sorted.len() as u32,
+            p50: percentile(50),
+            p99: percentile(99),
+            max: sorted[sorted.len() - 1],
+        })
+    }
+}
+
+/// Timing figures for a run.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Summary {
+    pub ticks: usize,
+    pub total: Duration,
+    pub mean: Duration,
+    pub p50: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let micros = |d: Duration| d.as_secs_f64() * 1e6;
+        write!(
+            f,
+            "{} ticks in {:.3} s: mean {:.1} µs, p50 {:.1} µs, p99 {:.1} µs, max {:.1} µs",
+            self.ticks,
+            self.total.as_secs_f64(),
+            micros(self.mean),
+            micros(self.p50),
+            micros(self.p99),
+            micros(self.max),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary_orders_samples() {
+        let mut stats = TickStats::default();
+        assert_eq!(stats.summary(), None);
+        for micros in [30, 10, 20, 40, 100] {
+            stats.record(Duration::from_micros(micros));
+        }
+        let summary = stats.summary().unwrap();
+        assert_eq!(summary.ticks, 5);
+        assert_eq!(summary.total, Duration::from_micros(200));
+        assert_eq!(summary.mean, Duration::from_micros(40));
+        assert_eq!(summary.p50, Duration::from_micros(30));
+        assert_eq!(summary.max, Duration::from_micros(100));
+    }
+}
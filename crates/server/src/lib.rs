@@ -0,0 +1,10 @@
+//! Authoritative multiplayer server for the falling sand simulation.
+//!
+//! Each room simulates its own `Grid` at a fixed tick rate; clients only
+//! send draws and cursor positions and receive the world back (see
+//! [`protocol`]). Connections arrive over any [`transport::Transport`].
+
+pub mod protocol;
+pub mod room;
+pub mod session;
+pub mod transport;
@@ -0,0 +1,113 @@
+//! `server`: host multiplayer rooms.
+//!
+//! Listens for clients on plain TCP, WebSocket or both; the two carry the
+//! same binary protocol. Clients join a room by name, which is created on
+//! first use, and every room simulates its world here at `--tick-rate`.
+//!
+//! ```text
+//! server --ws 0.0.0.0:9001 --tcp 127.0.0.1:9000 --width 320 --height 240
+//! ```
+
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use server::room::{Lobby, RoomConfig};
+use server::session;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::net::TcpListener;
+
+#[derive(Debug, Parser)]
+#[command(version, about = "Host falling sand multiplayer rooms")]
+struct Args {
+    /// Accept plain TCP clients on this address.
+    #[arg(long, value_name = "ADDR")]
+    tcp: Option<SocketAddr>,
+
+    /// Accept WebSocket clients on this address.
+    #[arg(long, value_name = "ADDR")]
+    ws: Option<SocketAddr>,
+
+    /// World width in cells.
+    #[arg(long, default_value_t = 256, value_parser = clap::value_parser!(u16).range(1..))]
+    width: u16,
+
+    /// World height in cells.
+    #[arg(long, default_value_t = 256, value_parser = clap::value_parser!(u16).range(1..))]
+    height: u16,
+
+    /// Simulation ticks per second.
+    #[arg(long, value_name = "HZ", default_value_t = 20, value_parser = clap::value_parser!(u8).range(1..))]
+    tick_rate: u8,
+
+    /// Send the world to clients every this many ticks.
+    #[arg(long, value_name = "N", default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    state_every: u32,
+
+    /// Players allowed in one room.
+    #[arg(long, default_value_t = 8)]
+    max_players: usize,
+
+    /// Rooms allowed at once.
+    #[arg(long, default_value_t = 16)]
+    max_rooms: usize,
+
+    /// Shut a room down after it has been empty this many seconds.
+    #[arg(long, value_name = "SECS", default_value_t = 60)]
+    idle_timeout: u64,
+}
+
+async fn bind(addr: Option<SocketAddr>) -> Result<Option<TcpListener>> {
+    let Some(addr) = addr else {
+        return Ok(None);
+    };
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("binding {addr}"))?;
+    Ok(Some(listener))
+}
+
+async fn run(args: Args) -> Result<()> {
+    if args.tcp.is_none() && args.ws.is_none() {
+        bail!("nothing to listen on; pass --tcp and/or --ws");
+    }
+    let config = RoomConfig {
+        width: args.width,
+        height: args.height,
+        tick_rate: args.tick_rate,
+        state_every: args.state_every,
+        max_players: args.max_players,
+        idle_timeout: Duration::from_secs(args.idle_timeout),
+    };
+    let lobby = Lobby::new(config, args.max_rooms);
+
+    let mut servers = tokio::task::JoinSet::new();
+    if let Some(listener) = bind(args.tcp).await? {
+        eprintln!("tcp: listening on {}", listener.local_addr()?);
+        servers.spawn(session::serve_tcp(listener, lobby.clone()));
+    }
+    if let Some(listener) = bind(args.ws).await? {
+        eprintln!("ws: listening on {}", listener.local_addr()?);
+        servers.spawn(session::serve_ws(listener, lobby));
+    }
+    // The accept loops only return on error.
+    if let Some(result) = servers.join_next().await {
+        result?.context("accepting connections")?;
+    }
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    run(Args::parse()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cli_definition_is_valid() {
+        use clap::CommandFactory;
+        Args::command().debug_assert();
+    }
+}
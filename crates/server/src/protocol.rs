@@ -0,0 +1,507 @@
+//! Binary wire protocol, the same over every transport.
+//!
+//! Every message is a 1-byte type, a little-endian `u32` payload length
+//! and the payload. `docs/ARCH.md` sketches a 2-byte length; full states
+//! outgrow that, so lengths are 4 bytes. Over WebSocket each binary
+//! message carries exactly one protocol message; over TCP messages are
+//! sent back to back and the header delimits them. Multi-byte integers
+//! are little-endian and strings are a `u8` length followed by UTF-8.
+//!
+//! | Type   | Direction | Message     |
+//! |--------|-----------|-------------|
+//! | `0x01` | C → S     | [`ClientMessage::Join`]   |
+//! | `0x03` | C → S     | [`ClientMessage::Draw`]   |
+//! | `0x05` | C → S     | [`ClientMessage::Cursor`] |
+//! | `0x10` | S → C     | [`ServerMessage::Welcome`]   |
+//! | `0x11` | S → C     | [`ServerMessage::FullState`] |
+//! | `0x1F` | S → C     | [`ServerMessage::Rejected`]  |
+//! | `0x21` | S → C     | [`ServerMessage::Cursors`]   |
+
+use std::fmt;
+
+/// Bytes before every payload: type and length.
+pub const HEADER_LEN: usize = 5;
+
+/// Largest server message payload accepted, so a bad length can't make a
+/// client allocate without bound. Full states are the big ones.
+pub const MAX_PAYLOAD: usize = 16 << 20;
+
+/// Largest client message payload accepted. The biggest client message, a
+/// join with two full-length strings, is just over 512 bytes; the server
+/// reads at most this much from a connection before it has joined.
+pub const MAX_CLIENT_PAYLOAD: usize = 1024;
+
+const JOIN: u8 = 0x01;
+const DRAW: u8 = 0x03;
+const CURSOR: u8 = 0x05;
+const WELCOME: u8 = 0x10;
+const FULL_STATE: u8 = 0x11;
+const REJECTED: u8 = 0x1F;
+const CURSORS: u8 = 0x21;
+
+/// A player's brush stroke at one point.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Draw {
+    pub x: u16,
+    pub y: u16,
+    pub radius: u8,
+    /// Species id to paint; Ghost places the ghost stamp.
+    pub material: u8,
+}
+
+/// Messages from a client.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ClientMessage {
+    /// First message of a session: enter `room` as `name`. The room is
+    /// created if it doesn't exist yet.
+    Join {
+        room: String,
+        name: String,
+    },
+    Draw(Draw),
+    /// Where the player is pointing, shared with the rest of the room.
+    Cursor {
+        x: u16,
+        y: u16,
+    },
+}
+
+/// Messages from the server.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ServerMessage {
+    /// Reply to a join.
+    Welcome {
+        player_id: u32,
+        width: u16,
+        height: u16,
+        tick_rate: u8,
+    },
+    /// The whole world after tick `tick`, as `Grid::to_compact(true)`.
+    FullState { tick: u64, world: Vec<u8> },
+    /// The join was refused; the server closes the session.
+    Rejected { reason: String },
+    /// Every player's last cursor position as `(player id, x, y)`, the
+    /// recipient's own included; one frame goes to the whole room, so
+    /// clients skip the `player_id` they were welcomed with.
+    Cursors(Vec<(u32, u16, u16)>),
+}
+
+/// Bytes could not be decoded as a protocol message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ProtocolError {
+    /// The message ended early.
+    Truncated,
+    /// The message continues past its last field.
+    TrailingBytes,
+    /// The message type is not one this side understands.
+    UnknownType(u8),
+    /// The header announces a payload of `len` bytes, over the `limit`
+    /// for its direction ([`MAX_PAYLOAD`] or [`MAX_CLIENT_PAYLOAD`]).
+    Oversized { len: usize, limit: usize },
+    /// A string is not valid UTF-8.
+    BadText,
+}
+
+impl fmt::Display for ProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Truncated => f.write_str("message is truncated"),
+            Self::TrailingBytes => f.write_str("message has trailing bytes"),
+            Self::UnknownType(ty) => write!(f, "unknown message type {ty:#04x}"),
+            Self::Oversized { len, limit } => {
+                write!(f, "payload of {len} bytes exceeds the {limit} byte limit")
+            }
+            Self::BadText => f.write_str("string is not valid UTF-8"),
+        }
+    }
+}
+
+impl std::error::Error for ProtocolError {}
+
+/// Payload length announced by a message header.
+///
+/// # Errors
+///
+/// Returns a [`ProtocolError`] if the length exceeds `limit`.
+pub fn payload_len(header: &[u8; HEADER_LEN], limit: usize) -> Result<usize, ProtocolError> {
+    let len = u32::from_le_bytes([header[1], header[2], header[3], header[4]]) as usize;
+    if len > limit {
+        return Err(ProtocolError::Oversized { len, limit });
+    }
+    Ok(len)
+}
+
+/// Builds one message: header first, length patched in by `finish`.
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn new(ty: u8) -> Self {
+        Self(vec![ty, 0, 0, 0, 0])
+    }
+
+    fn u8(&mut self, v: u8) {
+        self.0.push(v);
+    }
+
+    fn u16(&mut self, v: u16) {
+        self.0.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn u32(&mut self, v: u32) {
+        self.0.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn u64(&mut self, v: u64) {
+        self.0.extend_from_slice(&v.to_le_bytes());
+    }
+
+    /// Strings longer than 255 bytes are cut at a character boundary.
+    fn str(&mut self, s: &str) {
+        let mut end = s.len().min(usize::from(u8::MAX));
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+        self.u8(end as u8);
+        self.0.extend_from_slice(&s.as_bytes()[..end]);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        let len = (self.0.len() - HEADER_LEN) as u32;
+        self.0[1..HEADER_LEN].copy_from_slice(&len.to_le_bytes());
+        self.0
+    }
+}
+
+/// Reads one message's payload.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    /// Split a whole message into its type and a reader over its payload
+    /// of at most `limit` bytes.
+    fn open(bytes: &'a [u8], limit: usize) -> Result<(u8, Self), ProtocolError> {
+        let header: &[u8; HEADER_LEN] = bytes
+            .get(..HEADER_LEN)
+            .and_then(|h| h.try_into().ok())
+            .ok_or(ProtocolError::Truncated)?;
+        let len = payload_len(header, limit)?;
+        let payload = &bytes[HEADER_LEN..];
+        if payload.len() < len {
+            return Err(ProtocolError::Truncated);
+        }
+        if payload.len() > len {
+            return Err(ProtocolError::TrailingBytes);
+        }
+        Ok((header[0], Self(payload)))
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], ProtocolError> {
+        if self.0.len() < n {
+            return Err(ProtocolError::Truncated);
+        }
+        let (head, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8, ProtocolError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, ProtocolError> {
+        Ok(u16::from_le_bytes(
+            self.take(2)?.try_into().unwrap_or_default(),
+        ))
+    }
+
+    fn u32(&mut self) -> Result<u32, ProtocolError> {
+        Ok(u32::from_le_bytes(
+            self.take(4)?.try_into().unwrap_or_default(),
+        ))
+    }
+
+    fn u64(&mut self) -> Result<u64, ProtocolError> {
+        Ok(u64::from_le_bytes(
+            self.take(8)?.try_into().unwrap_or_default(),
+        ))
+    }
+
+    fn str(&mut self) -> Result<String, ProtocolError> {
+        let len = usize::from(self.u8()?);
+        let bytes = self.take(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| ProtocolError::BadText)
+    }
+
+    fn rest(&mut self) -> &'a [u8] {
+        std::mem::take(&mut self.0)
+    }
+
+    fn end(&self) -> Result<(), ProtocolError> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(ProtocolError::TrailingBytes)
+        }
+    }
+}
+
+impl ClientMessage {
+    /// Encode as one whole message, header included.
+    #[must_use]
+    pub fn encode(&self) -> Vec<u8> {
+        match self {
+            Self::Join { room, name } => {
+                let mut w = Writer::new(JOIN);
+                w.str(room);
+                w.str(name);
+                w.finish()
+            }
+            Self::Draw(draw) => {
+                let mut w = Writer::new(DRAW);
+                w.u16(draw.x);
+                w.u16(draw.y);
+                w.u8(draw.radius);
+                w.u8(draw.material);
+                w.finish()
+            }
+            Self::Cursor { x, y } => {
+                let mut w = Writer::new(CURSOR);
+                w.u16(*x);
+                w.u16(*y);
+                w.finish()
+            }
+        }
+    }
+
+    /// Decode one whole message, header included.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ProtocolError`] if the bytes are not exactly one
+    /// well-formed client message, or its payload is over
+    /// [`MAX_CLIENT_PAYLOAD`].
+    pub fn decode(bytes: &[u8]) -> Result<Self, ProtocolError> {
+        let (ty, mut r) = Reader::open(bytes, MAX_CLIENT_PAYLOAD)?;
+        let message = match ty {
+            JOIN => Self::Join {
+                room: r.str()?,
+                name: r.str()?,
+            },
+            DRAW => Self::Draw(Draw {
+                x: r.u16()?,
+                y: r.u16()?,
+                radius: r.u8()?,
+                material: r.u8()?,
+            }),
+            CURSOR => Self::Cursor {
+                x: r.u16()?,
+                y: r.u16()?,
+            },
+            other => return Err(ProtocolError::UnknownType(other)),
+        };
+        r.end()?;
+        Ok(message)
+    }
+}
+
+impl ServerMessage {
+    /// Encode as one whole message, header included.
+    #[must_use]
+    pub fn encode(&self) -> Vec<u8> {
+        match self {
+            Self::Welcome {
+                player_id,
+                width,
+                height,
+                tick_rate,
+            } => {
+                let mut w = Writer::new(WELCOME);
+                w.u32(*player_id);
+                w.u16(*width);
+                w.u16(*height);
+                w.u8(*tick_rate);
+                w.finish()
+            }
+            Self::FullState { tick, world } => {
+                let mut w = Writer::new(FULL_STATE);
+                w.u64(*tick);
+                w.0.extend_from_slice(world);
+                w.finish()
+            }
+            Self::Rejected { reason } => {
+                let mut w = Writer::new(REJECTED);
+                w.str(reason);
+                w.finish()
+            }
+            Self::Cursors(cursors) => {
+                let mut w = Writer::new(CURSORS);
+                let count = cursors.len().min(usize::from(u16::MAX));
+                w.u16(count as u16);
+                for &(player, x, y) in &cursors[..count] {
+                    w.u32(player);
+                    w.u16(x);
+                    w.u16(y);
+                }
+                w.finish()
+            }
+        }
+    }
+
+    /// Decode one whole message, header included.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ProtocolError`] if the bytes are not exactly one
+    /// well-formed server message.
+    pub fn decode(bytes: &[u8]) -> Result<Self, ProtocolError> {
+        let (ty, mut r) = Reader::open(bytes, MAX_PAYLOAD)?;
+        let message = match ty {
+            WELCOME => Self::Welcome {
+                player_id: r.u32()?,
+                width: r.u16()?,
+                height: r.u16()?,
+                tick_rate: r.u8()?,
+            },
+            FULL_STATE => Self::FullState {
+                tick: r.u64()?,
+                world: r.rest().to_vec(),
+            },
+            REJECTED => Self::Rejected { reason: r.str()? },
+            CURSORS => {
+                let count = r.u16()?;
+                let cursors = (0..count)
+                    .map(|_| Ok((r.u32()?, r.u16()?, r.u16()?)))
+                    .collect::<Result<_, _>>()?;
+                Self::Cursors(cursors)
+            }
+            other => return Err(ProtocolError::UnknownType(other)),
+        };
+        r.end()?;
+        Ok(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_message_round_trips() {
+        let client = [
+            ClientMessage::Join {
+                room: "lobby".into(),
+                name: "Ada ✓".into(),
+            },
+            ClientMessage::Draw(Draw {
+                x: 300,
+                y: 7,
+                radius: 3,
+                material: 1,
+            }),
+            ClientMessage::Cursor { x: 0, y: u16::MAX },
+        ];
+        for message in client {
+            assert_eq!(ClientMessage::decode(&message.encode()).unwrap(), message);
+        }
+        let server = [
+            ServerMessage::Welcome {
+                player_id: 9,
+                width: 256,
+                height: 128,
+                tick_rate: 20,
+            },
+            ServerMessage::FullState {
+                tick: 1 << 40,
+                world: vec![1, 2, 3],
+            },
+            ServerMessage::Rejected {
+                reason: "room is full".into(),
+            },
+            ServerMessage::Cursors(vec![(1, 2, 3), (4, 5, 6)]),
+        ];
+        for message in server {
+            assert_eq!(ServerMessage::decode(&message.encode()).unwrap(), message);
+        }
+    }
+
+    #[test]
+    fn header_matches_the_layout() {
+        let bytes = ClientMessage::Draw(Draw {
+            x: 1,
+            y: 2,
+            radius: 3,
+            material: 4,
+        })
+        .encode();
+        assert_eq!(bytes, [DRAW, 6, 0, 0, 0, 1, 0, 2, 0, 3, 4]);
+        let header: [u8; HEADER_LEN] = bytes[..HEADER_LEN].try_into().unwrap();
+        assert_eq!(payload_len(&header, MAX_CLIENT_PAYLOAD).unwrap(), 6);
+    }
+
+    #[test]
+    fn long_strings_are_cut_on_a_character_boundary() {
+        let name = "é".repeat(200);
+        let ClientMessage::Join { name: cut, .. } = ClientMessage::decode(
+            &ClientMessage::Join {
+                room: String::new(),
+                name,
+            }
+            .encode(),
+        )
+        .unwrap() else {
+            panic!("not a join");
+        };
+        assert_eq!(cut, "é".repeat(127));
+    }
+
+    #[test]
+    fn malformed_messages_are_rejected() {
+        let bytes = ClientMessage::Cursor { x: 1, y: 2 }.encode();
+        assert_eq!(
+            ClientMessage::decode(&bytes[..bytes.len() - 1]),
+            Err(ProtocolError::Truncated)
+        );
+        assert_eq!(
+            ClientMessage::decode(&bytes[..3]),
+            Err(ProtocolError::Truncated)
+        );
+        let mut long = bytes.clone();
+        long.push(0);
+        assert_eq!(
+            ClientMessage::decode(&long),
+            Err(ProtocolError::TrailingBytes)
+        );
+        assert_eq!(
+            ClientMessage::decode(&[0x7E, 0, 0, 0, 0]),
+            Err(ProtocolError::UnknownType(0x7E))
+        );
+        assert_eq!(
+            ServerMessage::decode(&bytes),
+            Err(ProtocolError::UnknownType(bytes[0])),
+            "client types aren't server types"
+        );
+        assert_eq!(
+            payload_len(&[DRAW, 0xFF, 0xFF, 0xFF, 0xFF], MAX_PAYLOAD),
+            Err(ProtocolError::Oversized {
+                len: 0xFFFF_FFFF,
+                limit: MAX_PAYLOAD
+            })
+        );
+        let mut huge_join = vec![JOIN];
+        huge_join.extend_from_slice(&2000u32.to_le_bytes());
+        huge_join.resize(HEADER_LEN + 2000, 0);
+        assert_eq!(
+            ClientMessage::decode(&huge_join),
+            Err(ProtocolError::Oversized {
+                len: 2000,
+                limit: MAX_CLIENT_PAYLOAD
+            }),
+            "clients get a much smaller allowance"
+        );
+        let bad_utf8 = [JOIN, 3, 0, 0, 0, 1, 0xFF, 0];
+        assert_eq!(
+            ClientMessage::decode(&bad_utf8),
+            Err(ProtocolError::BadText)
+        );
+    }
+}
@@ -0,0 +1,561 @@
+//! Rooms: independent worlds, each simulated at a fixed tick rate.
+//!
+//! A [`Room`] owns the authoritative `Grid`. Sessions talk to it only
+//! through [`RoomEvent`]s on its inbox, and it answers through each
+//! player's outbox, so one task owns the world and nothing is shared.
+//! Draws are applied in arrival order as they come in, between ticks
+//! (last write wins), and every `state_every` ticks the whole world is
+//! broadcast as a [`ServerMessage::FullState`]. An empty room stops
+//! ticking, and once it has been empty for the configured idle timeout it
+//! shuts down and its world is discarded.
+//!
+//! Broadcasting the whole deflated world is a deliberate first step: it
+//! keeps clients stateless between frames and a settled world compresses
+//! to little. `docs/ARCH.md` calls for dirty-chunk deltas instead; those
+//! would compare `Grid::chunk_hashes` against the previous broadcast and
+//! need a delta message in the protocol, and are left for later.
+//!
+//! The [`Lobby`] maps room names to running rooms, creating them on the
+//! first join and forgetting them when they shut down, which frees their
+//! slot for another room.
+
+use crate::protocol::{Draw, ServerMessage};
+use simulation::cell::Species;
+use simulation::paint::Brush;
+use simulation::stamp::GHOST;
+use simulation::Grid;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::{interval, Instant, MissedTickBehavior};
+
+pub type PlayerId = u32;
+
+/// Encoded messages on their way to one player.
+pub type Outbox = mpsc::Sender<Arc<[u8]>>;
+
+/// Messages a player's outbox holds before the room starts skipping
+/// state broadcasts for them. A later state supersedes a dropped one.
+pub const OUTBOX_FRAMES: usize = 16;
+
+/// Events a room's inbox holds before sessions wait to send more.
+const INBOX_EVENTS: usize = 1024;
+
+/// Draws a player may make per tick; the rest are dropped.
+pub const MAX_DRAWS_PER_TICK: u32 = 64;
+
+/// Largest brush radius a draw may use; larger ones are clamped.
+pub const MAX_RADIUS: u8 = 16;
+
+/// Settings shared by every room on a server.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RoomConfig {
+    pub width: u16,
+    pub height: u16,
+    /// Simulation ticks per second.
+    pub tick_rate: u8,
+    /// Broadcast the world every this many ticks.
+    pub state_every: u32,
+    pub max_players: usize,
+    /// How long a room may stay empty before it shuts down.
+    pub idle_timeout: Duration,
+}
+
+/// Something a session tells its room.
+#[derive(Debug)]
+pub enum RoomEvent {
+    Join { player: PlayerId, outbox: Outbox },
+    Leave { player: PlayerId },
+    Draw { player: PlayerId, draw: Draw },
+    Cursor { player: PlayerId, x: u16, y: u16 },
+}
+
+#[derive(Debug)]
+struct Player {
+    id: PlayerId,
+    outbox: Outbox,
+    cursor: Option<(u16, u16)>,
+    /// Draws made since the last tick.
+    draws: u32,
+}
+
+/// One world and the players in it; see the [module docs](self).
+#[derive(Debug)]
+pub struct Room {
+    name: String,
+    config: RoomConfig,
+    grid: Grid,
+    players: Vec<Player>,
+}
+
+impl Room {
+    #[must_use]
+    pub fn new(name: &str, config: RoomConfig) -> Self {
+        let grid = Grid::new(usize::from(config.width), usize::from(config.height));
+        Self {
+            name: name.to_owned(),
+            config,
+            grid,
+            players: Vec::new(),
+        }
+    }
+
+    /// The authoritative world.
+    #[must_use]
+    pub fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    #[must_use]
+    pub fn player_count(&self) -> usize {
+        self.players.len()
+    }
+
+    pub fn handle(&mut self, event: RoomEvent) {
+        match event {
+            RoomEvent::Join { player, outbox } => self.join(player, outbox),
+            RoomEvent::Leave { player } => self.players.retain(|p| p.id != player),
+            RoomEvent::Draw { player, draw } => self.draw(player, draw),
+            RoomEvent::Cursor { player, x, y } => {
+                if let Some(p) = self.players.iter_mut().find(|p| p.id == player) {
+                    p.cursor = Some((x, y));
+                }
+            }
+        }
+    }
+
+    /// Advance one tick and broadcast when due. Does nothing while the
+    /// room is empty.
+    pub fn step(&mut self) {
+        if self.players.is_empty() {
+            return;
+        }
+        for player in &mut self.players {
+            player.draws = 0;
+        }
+        // Players' cursors differ, so ghosts look straight ahead, as they
+        // do in a client `Universe` with no cursor.
+        self.grid.tick_with_cursor(None);
+        if self
+            .grid
+            .tick_count
+            .is_multiple_of(u64::from(self.config.state_every.max(1)))
+        {
+            self.broadcast(&self.state_frame());
+            let cursors: Vec<_> = self
+                .players
+                .iter()
+                .filter_map(|p| p.cursor.map(|(x, y)| (p.id, x, y)))
+                .collect();
+            if !cursors.is_empty() {
+                self.broadcast(&ServerMessage::Cursors(cursors).encode().into());
+            }
+        }
+    }
+
+    fn join(&mut self, player: PlayerId, outbox: Outbox) {
+        if self.players.len() >= self.config.max_players {
+            let reason = format!("room {} is full", self.name);
+            let _ = outbox.try_send(ServerMessage::Rejected { reason }.encode().into());
+            return;
+        }
+        let welcome = ServerMessage::Welcome {
+            player_id: player,
+            width: self.config.width,
+            height: self.config.height,
+            tick_rate: self.config.tick_rate,
+        };
+        let _ = outbox.try_send(welcome.encode().into());
+        let _ = outbox.try_send(self.state_frame());
+        self.players.push(Player {
+            id: player,
+            outbox,
+            cursor: None,
+            draws: 0,
+        });
+    }
+
+    fn draw(&mut self, player: PlayerId, draw: Draw) {
+        let Some(p) = self.players.iter_mut().find(|p| p.id == player) else {
+            return;
+        };
+        if p.draws >= MAX_DRAWS_PER_TICK {
+            return;
+        }
+        p.draws += 1;
+        let Some(species) = Species::from_id(draw.material) else {
+            return;
+        };
+        let (x, y) = (i32::from(draw.x), i32::from(draw.y));
+        if species == Species::Ghost {
+            // No room for a ghost here is not an error worth reporting.
+            let _ = self.grid.place_stamp(GHOST.name, x, y);
+        } else {
+            self.grid.paint_circle(
+                x,
+                y,
+                u32::from(draw.radius.min(MAX_RADIUS)),
+                Brush::new(species),
+            );
+        }
+    }
+
+    fn state_frame(&self) -> Arc<[u8]> {
+        let state = ServerMessage::FullState {
+            tick: self.grid.tick_count,
+            world: self.grid.to_compact(true),
+        };
+        state.encode().into()
+    }
+
+    /// Queue `frame` for every player, skipping any whose outbox is full.
+    fn broadcast(&self, frame: &Arc<[u8]>) {
+        for player in &self.players {
+            let _ = player.outbox.try_send(Arc::clone(frame));
+        }
+    }
+}
+
+/// Run `room`, handling events from `inbox` between fixed-rate ticks,
+/// until every sender to the inbox is gone or the room has been empty for
+/// its `idle_timeout`. Returns the inbox, which may still hold events.
+pub async fn run(
+    mut room: Room,
+    mut inbox: mpsc::Receiver<RoomEvent>,
+) -> mpsc::Receiver<RoomEvent> {
+    let mut ticks = interval(Duration::from_secs(1) / u32::from(room.config.tick_rate.max(1)));
+    // A stalled room resumes at the normal rate instead of racing to catch up.
+    ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut empty_since = None;
+    loop {
+        tokio::select! {
+            _ = ticks.tick() => {
+                room.step();
+                if room.players.is_empty() {
+                    let since = *empty_since.get_or_insert_with(Instant::now);
+                    if since.elapsed() >= room.config.idle_timeout {
+                        break;
+                    }
+                } else {
+                    empty_since = None;
+                }
+            }
+            event = inbox.recv() => match event {
+                Some(event) => room.handle(event),
+                None => break,
+            },
+        }
+    }
+    inbox
+}
+
+/// Running rooms by name; cheap to clone and share between sessions.
+#[derive(Clone, Debug)]
+pub struct Lobby {
+    rooms: Arc<Mutex<HashMap<String, mpsc::Sender<RoomEvent>>>>,
+    next_player: Arc<AtomicU32>,
+    config: RoomConfig,
+    max_rooms: usize,
+}
+
+impl Lobby {
+    #[must_use]
+    pub fn new(config: RoomConfig, max_rooms: usize) -> Self {
+        Self {
+            rooms: Arc::default(),
+            next_player: Arc::new(AtomicU32::new(1)),
+            config,
+            max_rooms,
+        }
+    }
+
+    /// A fresh player ID, unique across all rooms.
+    #[must_use]
+    pub fn next_player_id(&self) -> PlayerId {
+        self.next_player.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// The inbox of room `name`, starting the room if needed. Returns
+    /// `None` if the room doesn't exist and the server already runs
+    /// `max_rooms`. Must be called within a Tokio runtime.
+    #[must_use]
+    pub fn room(&self, name: &str) -> Option<mpsc::Sender<RoomEvent>> {
+        let mut rooms = self
+            .rooms
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if let Some(inbox) = rooms.get(name) {
+            return Some(inbox.clone());
+        }
+        if rooms.len() >= self.max_rooms {
+            return None;
+        }
+        let (inbox, events) = mpsc::channel(INBOX_EVENTS);
+        let lobby = self.clone();
+        let name = name.to_owned();
+        rooms.insert(name.clone(), inbox.clone());
+        tokio::spawn(async move {
+            let events = run(Room::new(&name, lobby.config), events).await;
+            lobby.close(&name, events);
+        });
+        Some(inbox)
+    }
+
+    /// Forget the shut-down room `name` and turn away anyone whose join
+    /// reached its inbox too late. Later sends to the room fail, and by
+    /// then `room` starts a fresh one.
+    fn close(&self, name: &str, mut events: mpsc::Receiver<RoomEvent>) {
+        self.rooms
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .remove(name);
+        events.close();
+        while let Ok(event) = events.try_recv() {
+            if let RoomEvent::Join { outbox, .. } = event {
+                let reason = format!("room {name} has closed; join again");
+                let _ = outbox.try_send(ServerMessage::Rejected { reason }.encode().into());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use simulation::Universe;
+
+    const CONFIG: RoomConfig = RoomConfig {
+        width: 16,
+        height: 12,
+        tick_rate: 20,
+        state_every: 2,
+        max_players: 2,
+        idle_timeout: Duration::from_millis(100),
+    };
+    const SAND: u8 = Species::Sand as u8;
+
+    fn join(room: &mut Room, player: PlayerId) -> mpsc::Receiver<Arc<[u8]>> {
+        let (outbox, frames) = mpsc::channel(OUTBOX_FRAMES);
+        room.handle(RoomEvent::Join { player, outbox });
+        frames
+    }
+
+    fn received(frames: &mut mpsc::Receiver<Arc<[u8]>>) -> Vec<ServerMessage> {
+        std::iter::from_fn(|| frames.try_recv().ok())
+            .map(|f| ServerMessage::decode(&f).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn joining_sends_welcome_and_the_world() {
+        let mut room = Room::new("test", CONFIG);
+        let mut frames = join(&mut room, 7);
+        let messages = received(&mut frames);
+        assert_eq!(
+            messages[0],
+            ServerMessage::Welcome {
+                player_id: 7,
+                width: 16,
+                height: 12,
+                tick_rate: 20
+            }
+        );
+        let ServerMessage::FullState { tick: 0, world } = &messages[1] else {
+            panic!("expected the world, got {:?}", messages[1]);
+        };
+        assert_eq!(
+            Grid::from_compact(world).unwrap().state_hash(),
+            room.grid().state_hash()
+        );
+    }
+
+    #[test]
+    fn draws_are_applied_and_broadcast() {
+        let mut room = Room::new("test", CONFIG);
+        let mut a = join(&mut room, 1);
+        let mut b = join(&mut room, 2);
+        received(&mut a);
+        received(&mut b);
+
+        room.handle(RoomEvent::Draw {
+            player: 1,
+            draw: Draw {
+                x: 8,
+                y: 2,
+                radius: 1,
+                material: SAND,
+            },
+        });
+        room.handle(RoomEvent::Cursor {
+            player: 2,
+            x: 3,
+            y: 4,
+        });
+        room.step();
+        assert!(
+            received(&mut b).is_empty(),
+            "state goes out every other tick"
+        );
+        room.step();
+        let messages = received(&mut b);
+        let ServerMessage::FullState { tick: 2, world } = &messages[0] else {
+            panic!("expected the world, got {messages:?}");
+        };
+        let world = Grid::from_compact(world).unwrap();
+        assert_eq!(
+            world
+                .cells
+                .iter()
+                .filter(|c| c.species == Species::Sand)
+                .count(),
+            5
+        );
+        assert_eq!(messages[1], ServerMessage::Cursors(vec![(2, 3, 4)]));
+        assert_eq!(received(&mut a).len(), 2, "everyone gets the broadcast");
+    }
+
+    #[test]
+    fn ticks_match_a_client_universe() {
+        let config = RoomConfig {
+            width: 40,
+            height: 40,
+            ..CONFIG
+        };
+        let mut room = Room::new("test", config);
+        let _frames = join(&mut room, 1);
+        let mut universe = Universe::new(40, 40);
+        room.handle(RoomEvent::Draw {
+            player: 1,
+            draw: Draw {
+                x: 20,
+                y: 20,
+                radius: 0,
+                material: Species::Ghost as u8,
+            },
+        });
+        universe.place_stamp(GHOST.name, 20, 20).unwrap();
+        for _ in 0..10 {
+            room.step();
+            universe.tick();
+            assert_eq!(room.grid().state_hash(), universe.state_hash());
+        }
+    }
+
+    #[test]
+    fn draws_are_limited_and_validated() {
+        let mut room = Room::new("test", CONFIG);
+        let _frames = join(&mut room, 1);
+        for i in 0..=MAX_DRAWS_PER_TICK {
+            let x = (i % 16) as u16;
+            let y = (i / 16) as u16;
+            room.handle(RoomEvent::Draw {
+                player: 1,
+                draw: Draw {
+                    x,
+                    y,
+                    radius: 0,
+                    material: SAND,
+                },
+            });
+        }
+        room.handle(RoomEvent::Draw {
+            player: 9,
+            draw: Draw {
+                x: 0,
+                y: 11,
+                radius: 0,
+                material: SAND,
+            },
+        });
+        let sand = room
+            .grid()
+            .cells
+            .iter()
+            .filter(|c| c.species == Species::Sand)
+            .count();
+        assert_eq!(
+            sand as u32, MAX_DRAWS_PER_TICK,
+            "the extra draw and the stranger's are dropped"
+        );
+
+        room.step();
+        room.handle(RoomEvent::Draw {
+            player: 1,
+            draw: Draw {
+                x: 0,
+                y: 11,
+                radius: 0,
+                material: 200,
+            },
+        });
+        room.handle(RoomEvent::Draw {
+            player: 1,
+            draw: Draw {
+                x: 0,
+                y: 11,
+                radius: 0,
+                material: 3,
+            },
+        });
+        assert_eq!(
+            room.grid().get(0, 11).species,
+            Species::Wall,
+            "budget resets each tick"
+        );
+    }
+
+    #[test]
+    fn full_rooms_reject_and_empty_rooms_rest() {
+        let mut room = Room::new("test", CONFIG);
+        let _a = join(&mut room, 1);
+        let _b = join(&mut room, 2);
+        let mut c = join(&mut room, 3);
+        assert_eq!(
+            received(&mut c),
+            [ServerMessage::Rejected {
+                reason: "room test is full".into()
+            }]
+        );
+        assert_eq!(room.player_count(), 2);
+
+        room.handle(RoomEvent::Leave { player: 1 });
+        room.handle(RoomEvent::Leave { player: 2 });
+        room.step();
+        assert_eq!(room.grid().tick_count, 0);
+    }
+
+    #[tokio::test]
+    async fn lobby_shares_rooms_by_name_up_to_the_limit() {
+        let lobby = Lobby::new(CONFIG, 1);
+        let first = lobby.room("a").unwrap();
+        assert!(first.same_channel(&lobby.room("a").unwrap()));
+        assert!(lobby.room("b").is_none());
+        assert_ne!(lobby.next_player_id(), lobby.next_player_id());
+    }
+
+    #[tokio::test]
+    async fn idle_rooms_shut_down_and_free_their_slot() {
+        let lobby = Lobby::new(CONFIG, 1);
+        let first = lobby.room("a").unwrap();
+        let (outbox, mut frames) = mpsc::channel(OUTBOX_FRAMES);
+        first
+            .send(RoomEvent::Join { player: 1, outbox })
+            .await
+            .unwrap();
+        assert!(matches!(
+            ServerMessage::decode(&frames.recv().await.unwrap()).unwrap(),
+            ServerMessage::Welcome { .. }
+        ));
+        tokio::time::sleep(CONFIG.idle_timeout * 2).await;
+        assert!(!first.is_closed(), "occupied rooms stay open");
+
+        first.send(RoomEvent::Leave { player: 1 }).await.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), first.closed())
+            .await
+            .expect("the empty room shuts down");
+        let second = lobby.room("b").expect("the slot is free again");
+        assert!(!second.same_channel(&first));
+    }
+}
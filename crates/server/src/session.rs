@@ -0,0 +1,309 @@
+//! One client connection, over any [`Transport`].
+//!
+//! The first message must be a join, sent within [`JOIN_TIMEOUT`]. After
+//! that the session forwards the client's draws and cursor moves to its
+//! room and, from a separate task, writes whatever the room queues in the
+//! player's outbox. The session ends when the client disconnects, breaks
+//! the protocol or sends nothing for [`IDLE_TIMEOUT`], or when the room
+//! drops the player (for example because the room is full).
+
+use crate::protocol::{ClientMessage, ServerMessage};
+use crate::room::{Lobby, PlayerId, RoomEvent, OUTBOX_FRAMES};
+use crate::transport::{
+    client_ws_config, FrameReader, FrameWriter, TcpTransport, Transport, WsTransport,
+};
+use anyhow::{bail, Context, Result};
+use std::io;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio::time::timeout;
+
+/// How long a new connection may take to send its join, and before that
+/// to finish its WebSocket handshake.
+pub const JOIN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long a joined client may go without sending anything before its
+/// session ends and it leaves the room. Clients that only watch should
+/// send their cursor now and then to stay.
+pub const IDLE_TIMEOUT: Duration = Duration::from_mins(5);
+
+/// Serve one client until it leaves.
+///
+/// # Errors
+///
+/// Returns an error if the connection fails or the client sends anything
+/// but a well-formed protocol message.
+pub async fn run<T: Transport>(transport: T, lobby: Lobby) -> Result<()> {
+    let (mut reader, mut writer) = transport.split();
+    let Some(frame) = timeout(JOIN_TIMEOUT, reader.read_frame())
+        .await
+        .context("no join before the timeout")??
+    else {
+        return Ok(());
+    };
+    let ClientMessage::Join { room, name } = ClientMessage::decode(&frame)? else {
+        bail!("the first message must be a join");
+    };
+
+    let player = lobby.next_player_id();
+    let (outbox, mut outgoing) = mpsc::channel(OUTBOX_FRAMES);
+    let mut join = RoomEvent::Join { player, outbox };
+    let inbox = loop {
+        let Some(inbox) = lobby.room(&room) else {
+            let reason = "the server has no room for another world".to_owned();
+            writer
+                .write_frame(&ServerMessage::Rejected { reason }.encode())
+                .await?;
+            return Ok(());
+        };
+        match inbox.send(join).await {
+            Ok(()) => break inbox,
+            // The room shut down after the lookup; the lobby has already
+            // forgotten it, so the next lookup starts a fresh one.
+            Err(mpsc::error::SendError(event)) => join = event,
+        }
+    };
+    eprintln!("room {room:?}: {name:?} joined");
+    let mut writing = tokio::spawn(async move {
+        while let Some(frame) = outgoing.recv().await {
+            writer.write_frame(&frame).await?;
+        }
+        Ok::<_, io::Error>(())
+    });
+
+    let result = tokio::select! {
+        result = forward(&mut reader, &inbox, player) => result,
+        // The room dropped the outbox: the player was rejected.
+        written = &mut writing => written.map_or(Ok(()), |w| w.map_err(Into::into)),
+    };
+    let _ = inbox.send(RoomEvent::Leave { player }).await;
+    eprintln!("room {room:?}: {name:?} left");
+    writing.abort();
+    result
+}
+
+/// Pass the client's messages to the room until it disconnects.
+async fn forward(
+    reader: &mut impl FrameReader,
+    inbox: &mpsc::Sender<RoomEvent>,
+    player: PlayerId,
+) -> Result<()> {
+    while let Some(frame) = timeout(IDLE_TIMEOUT, reader.read_frame())
+        .await
+        .context("client went quiet")??
+    {
+        let event = match ClientMessage::decode(&frame)? {
+            ClientMessage::Draw(draw) => RoomEvent::Draw { player, draw },
+            ClientMessage::Cursor { x, y } => RoomEvent::Cursor { player, x, y },
+            ClientMessage::Join { .. } => bail!("already joined a room"),
+        };
+        if inbox.send(event).await.is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Accept plain TCP clients on `listener` forever.
+///
+/// # Errors
+///
+/// Returns an error if accepting a connection fails.
+pub async fn serve_tcp(listener: TcpListener, lobby: Lobby) -> io::Result<()> {
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let lobby = lobby.clone();
+        tokio::spawn(async move {
+            if let Err(err) = run(TcpTransport::new(stream), lobby).await {
+                eprintln!("{peer}: {err:#}");
+            }
+        });
+    }
+}
+
+/// Accept WebSocket clients on `listener` forever.
+///
+/// # Errors
+///
+/// Returns an error if accepting a connection fails.
+pub async fn serve_ws(listener: TcpListener, lobby: Lobby) -> io::Result<()> {
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let lobby = lobby.clone();
+        tokio::spawn(async move {
+            // A client that never finishes the handshake must not hold
+            // its task forever; the join timeout only starts after it.
+            let handshake = timeout(
+                JOIN_TIMEOUT,
+                tokio_tungstenite::accept_async_with_config(stream, Some(client_ws_config())),
+            );
+            let result = match handshake.await {
+                Ok(Ok(stream)) => run(WsTransport::new(stream), lobby).await,
+                Ok(Err(err)) => Err(err).context("WebSocket handshake"),
+                Err(elapsed) => Err(elapsed).context("no WebSocket handshake before the timeout"),
+            };
+            if let Err(err) = result {
+                eprintln!("{peer}: {err:#}");
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::{Draw, MAX_PAYLOAD};
+    use crate::room::RoomConfig;
+    use simulation::cell::Species;
+    use simulation::Grid;
+    use std::net::SocketAddr;
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpStream;
+
+    const CONFIG: RoomConfig = RoomConfig {
+        width: 32,
+        height: 32,
+        tick_rate: 60,
+        state_every: 1,
+        max_players: 1,
+        idle_timeout: Duration::from_secs(30),
+    };
+
+    async fn listen() -> (TcpListener, SocketAddr) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        (listener, addr)
+    }
+
+    fn join(room: &str) -> Vec<u8> {
+        ClientMessage::Join {
+            room: room.into(),
+            name: "tester".into(),
+        }
+        .encode()
+    }
+
+    fn draw_sand() -> Vec<u8> {
+        ClientMessage::Draw(Draw {
+            x: 16,
+            y: 4,
+            radius: 2,
+            material: Species::Sand as u8,
+        })
+        .encode()
+    }
+
+    /// Read states until one shows sand.
+    async fn wait_for_sand(reader: &mut impl FrameReader) {
+        for _ in 0..200 {
+            let frame = reader.read_frame().await.unwrap().expect("server hung up");
+            if let ServerMessage::FullState { world, .. } = ServerMessage::decode(&frame).unwrap() {
+                let world = Grid::from_compact(&world).unwrap();
+                if world.cells.iter().any(|c| c.species == Species::Sand) {
+                    return;
+                }
+            }
+        }
+        panic!("the draw never showed up");
+    }
+
+    #[tokio::test]
+    async fn tcp_client_draws_and_sees_the_result() {
+        let (listener, addr) = listen().await;
+        tokio::spawn(serve_tcp(listener, Lobby::new(CONFIG, 4)));
+
+        let (mut reader, mut writer) = TcpTransport::new(TcpStream::connect(addr).await.unwrap())
+            .with_max_payload(MAX_PAYLOAD)
+            .split();
+        writer.write_frame(&join("tcp")).await.unwrap();
+        let welcome = ServerMessage::decode(&reader.read_frame().await.unwrap().unwrap()).unwrap();
+        assert!(matches!(
+            welcome,
+            ServerMessage::Welcome {
+                width: 32,
+                height: 32,
+                tick_rate: 60,
+                ..
+            }
+        ));
+        writer.write_frame(&draw_sand()).await.unwrap();
+        wait_for_sand(&mut reader).await;
+
+        // The room is full while this client stays.
+        let (mut reader, mut writer) = TcpTransport::new(TcpStream::connect(addr).await.unwrap())
+            .with_max_payload(MAX_PAYLOAD)
+            .split();
+        writer.write_frame(&join("tcp")).await.unwrap();
+        let rejected = ServerMessage::decode(&reader.read_frame().await.unwrap().unwrap()).unwrap();
+        assert!(matches!(rejected, ServerMessage::Rejected { .. }));
+        assert_eq!(
+            reader.read_frame().await.unwrap(),
+            None,
+            "then the session closes"
+        );
+    }
+
+    #[tokio::test]
+    async fn websocket_client_draws_and_sees_the_result() {
+        let (listener, addr) = listen().await;
+        tokio::spawn(serve_ws(listener, Lobby::new(CONFIG, 4)));
+
+        let socket = TcpStream::connect(addr).await.unwrap();
+        let (stream, _) = tokio_tungstenite::client_async(format!("ws://{addr}"), socket)
+            .await
+            .unwrap();
+        let (mut reader, mut writer) = WsTransport::new(stream).split();
+        writer.write_frame(&join("ws")).await.unwrap();
+        writer.write_frame(&draw_sand()).await.unwrap();
+        let welcome = ServerMessage::decode(&reader.read_frame().await.unwrap().unwrap()).unwrap();
+        assert!(matches!(welcome, ServerMessage::Welcome { .. }));
+        wait_for_sand(&mut reader).await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stalled_websocket_handshakes_are_dropped() {
+        let (listener, addr) = listen().await;
+        tokio::spawn(serve_ws(listener, Lobby::new(CONFIG, 4)));
+
+        let mut socket = TcpStream::connect(addr).await.unwrap();
+        let mut buf = [0; 1];
+        let read = timeout(JOIN_TIMEOUT * 2, socket.read(&mut buf)).await;
+        assert!(
+            matches!(read, Ok(Ok(0) | Err(_))),
+            "the server hangs up: {read:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn sessions_must_start_with_a_join() {
+        let (listener, addr) = listen().await;
+        let accepted = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            run(TcpTransport::new(stream), Lobby::new(CONFIG, 4)).await
+        });
+        let (_, mut writer) = TcpTransport::new(TcpStream::connect(addr).await.unwrap()).split();
+        writer.write_frame(&draw_sand()).await.unwrap();
+        let err = accepted.await.unwrap().unwrap_err();
+        assert_eq!(err.to_string(), "the first message must be a join");
+    }
+
+    #[tokio::test]
+    async fn oversized_frames_are_refused_before_reading_them() {
+        let (listener, addr) = listen().await;
+        let accepted = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            run(TcpTransport::new(stream), Lobby::new(CONFIG, 4)).await
+        });
+        let (_, mut writer) = TcpTransport::new(TcpStream::connect(addr).await.unwrap()).split();
+        // A join header announcing a 16 MiB payload that never comes.
+        let mut header = vec![0x01];
+        header.extend_from_slice(&(16u32 << 20).to_le_bytes());
+        writer.write_frame(&header).await.unwrap();
+        let err = accepted.await.unwrap().unwrap_err();
+        assert!(
+            err.to_string().contains("exceeds the 1024 byte limit"),
+            "{err}"
+        );
+    }
+}
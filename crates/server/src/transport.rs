@@ -0,0 +1,182 @@
+//! Connections that carry whole protocol messages.
+//!
+//! Sessions only see a [`Transport`], split into a [`FrameReader`] and a
+//! [`FrameWriter`] so reading and writing run independently. Plain TCP
+//! ([`TcpTransport`]) and WebSocket ([`WsTransport`]) are provided for
+//! local testing and browsers without WebTransport; a WebTransport
+//! implementation only needs to provide the same three traits.
+//!
+//! Transports are the server's end of a connection, so they refuse
+//! incoming messages over [`MAX_CLIENT_PAYLOAD`] before buffering them.
+//! A client reading server messages raises the cap with
+//! [`TcpTransport::with_max_payload`].
+
+use crate::protocol::{self, HEADER_LEN, MAX_CLIENT_PAYLOAD};
+use futures_util::stream::{SplitSink, SplitStream};
+use futures_util::{SinkExt, StreamExt};
+use std::future::Future;
+use std::io;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::protocol::WebSocketConfig;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+
+/// A bidirectional connection to one client.
+pub trait Transport: Send + 'static {
+    type Reader: FrameReader;
+    type Writer: FrameWriter;
+
+    /// Separate the receiving and sending halves.
+    fn split(self) -> (Self::Reader, Self::Writer);
+}
+
+/// The receiving half of a [`Transport`].
+pub trait FrameReader: Send + 'static {
+    /// The next whole message, header included, or `None` once the peer
+    /// has closed the connection.
+    fn read_frame(&mut self) -> impl Future<Output = io::Result<Option<Vec<u8>>>> + Send;
+}
+
+/// The sending half of a [`Transport`].
+pub trait FrameWriter: Send + 'static {
+    /// Send one whole message, header included.
+    fn write_frame(&mut self, frame: &[u8]) -> impl Future<Output = io::Result<()>> + Send;
+}
+
+/// Messages sent back to back over a TCP stream.
+#[derive(Debug)]
+pub struct TcpTransport {
+    stream: TcpStream,
+    max_payload: usize,
+}
+
+impl TcpTransport {
+    #[must_use]
+    pub fn new(stream: TcpStream) -> Self {
+        // Messages are small and latency-sensitive.
+        let _ = stream.set_nodelay(true);
+        Self {
+            stream,
+            max_payload: MAX_CLIENT_PAYLOAD,
+        }
+    }
+
+    /// Accept incoming payloads of up to `max_payload` bytes instead of
+    /// [`MAX_CLIENT_PAYLOAD`].
+    #[must_use]
+    pub fn with_max_payload(mut self, max_payload: usize) -> Self {
+        self.max_payload = max_payload;
+        self
+    }
+}
+
+impl Transport for TcpTransport {
+    type Reader = TcpReader;
+    type Writer = TcpWriter;
+
+    fn split(self) -> (TcpReader, TcpWriter) {
+        let (read, write) = self.stream.into_split();
+        let reader = TcpReader {
+            read,
+            max_payload: self.max_payload,
+        };
+        (reader, TcpWriter(write))
+    }
+}
+
+#[derive(Debug)]
+pub struct TcpReader {
+    read: OwnedReadHalf,
+    max_payload: usize,
+}
+
+impl FrameReader for TcpReader {
+    async fn read_frame(&mut self) -> io::Result<Option<Vec<u8>>> {
+        let mut header = [0; HEADER_LEN];
+        match self.read.read_exact(&mut header).await {
+            Ok(_) => {}
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(err) => return Err(err),
+        }
+        let len = protocol::payload_len(&header, self.max_payload)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        let mut frame = vec![0; HEADER_LEN + len];
+        frame[..HEADER_LEN].copy_from_slice(&header);
+        self.read.read_exact(&mut frame[HEADER_LEN..]).await?;
+        Ok(Some(frame))
+    }
+}
+
+#[derive(Debug)]
+pub struct TcpWriter(OwnedWriteHalf);
+
+impl FrameWriter for TcpWriter {
+    async fn write_frame(&mut self, frame: &[u8]) -> io::Result<()> {
+        self.0.write_all(frame).await
+    }
+}
+
+/// One protocol message per binary WebSocket message.
+#[derive(Debug)]
+pub struct WsTransport(WebSocketStream<TcpStream>);
+
+impl WsTransport {
+    /// Wrap a stream that has completed the WebSocket handshake.
+    #[must_use]
+    pub fn new(stream: WebSocketStream<TcpStream>) -> Self {
+        Self(stream)
+    }
+}
+
+/// WebSocket limits for the server's end of a connection: no message
+/// larger than the largest client message.
+#[must_use]
+pub fn client_ws_config() -> WebSocketConfig {
+    let max = HEADER_LEN + MAX_CLIENT_PAYLOAD;
+    WebSocketConfig {
+        max_message_size: Some(max),
+        max_frame_size: Some(max),
+        ..WebSocketConfig::default()
+    }
+}
+
+impl Transport for WsTransport {
+    type Reader = WsReader;
+    type Writer = WsWriter;
+
+    fn split(self) -> (WsReader, WsWriter) {
+        let (sink, stream) = self.0.split();
+        (WsReader(stream), WsWriter(sink))
+    }
+}
+
+#[derive(Debug)]
+pub struct WsReader(SplitStream<WebSocketStream<TcpStream>>);
+
+impl FrameReader for WsReader {
+    async fn read_frame(&mut self) -> io::Result<Option<Vec<u8>>> {
+        loop {
+            match self.0.next().await {
+                Some(Ok(Message::Binary(frame))) => return Ok(Some(frame)),
+                Some(Ok(Message::Close(_))) | None => return Ok(None),
+                // Pings are answered by tungstenite; text has no meaning here.
+                Some(Ok(_)) => {}
+                Some(Err(err)) => return Err(io::Error::other(err)),
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct WsWriter(SplitSink<WebSocketStream<TcpStream>, Message>);
+
+impl FrameWriter for WsWriter {
+    async fn write_frame(&mut self, frame: &[u8]) -> io::Result<()> {
+        self.0
+            .send(Message::Binary(frame.to_vec()))
+            .await
+            .map_err(io::Error::other)
+    }
+}
@@ -1,4 +1,4 @@
-//! Benchmark: measure tick() cost under various grid conditions.
+//! Benchmark: measure `tick()` cost under various grid conditions.
 //!
 //! Target: a single tick on a 256×256 grid must complete in < 4 ms
 //! to leave headroom for rendering within an 8.3 ms frame budget (120 Hz).
@@ -10,7 +10,7 @@ use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion
 use simulation::cell::{Cell, Species};
 use simulation::{Grid, Universe};
 
-/// Empty grid — baseline cost of scanning 65K cells with nothing to do.
+/// Empty grid — baseline cost of a tick once every chunk has gone to sleep.
 fn bench_tick_empty(c: &mut Criterion) {
     c.bench_function("tick_empty_256x256", |b| {
         let mut grid = Grid::new(256, 256);
@@ -111,7 +111,7 @@ fn bench_tick_mixed_active(c: &mut Criterion) {
     });
 }
 
-/// Full Universe::tick() including buffer sync — what the browser actually calls.
+/// Full `Universe::tick()` including buffer sync — what the browser actually calls.
 /// Re-seeded so we measure active work, not a settled world.
 fn bench_universe_tick(c: &mut Criterion) {
     c.bench_function("universe_tick_mixed_256x256", |b| {
@@ -3,8 +3,8 @@
 use crate::cell::Cell;
 use crate::Grid;
 
-/// Out-of-bounds reads return Wall, writes are no-ops.
-/// Clock is stamped on every `set`.
+/// Out-of-bounds reads and writes follow `Grid::boundaries` (by default
+/// reads return Wall and writes are no-ops). Clock is stamped on every `set`.
 #[derive(Debug)]
 pub struct SandApi<'a> {
     pub grid: &'a mut Grid,
@@ -15,7 +15,12 @@ pub struct SandApi<'a> {
 
 impl<'a> SandApi<'a> {
     pub fn new(grid: &'a mut Grid, x: i32, y: i32, generation: u8) -> Self {
-        Self { grid, x, y, generation }
+        Self {
+            grid,
+            x,
+            y,
+            generation,
+        }
     }
 
     #[must_use]
@@ -23,33 +28,68 @@ impl<'a> SandApi<'a> {
         self.grid.get(self.x + dx, self.y + dy)
     }
 
+    /// Full tick counter of the grid (see `Grid::tick_count`).
+    #[must_use]
+    pub fn tick_count(&self) -> u64 {
+        self.grid.tick_count
+    }
+
     pub fn set(&mut self, dx: i32, dy: i32, cell: Cell) {
         let mut stamped = cell;
         stamped.clock = self.generation;
         self.grid.set(self.x + dx, self.y + dy, stamped);
     }
+
+    /// Uniform random integer in `0..n`, drawn from the grid's RNG.
+    /// Returns 0 when `n <= 0`.
+    pub fn rand_int(&mut self, n: i32) -> i32 {
+        self.grid.rng.below(n.max(0) as u32) as i32
+    }
+
+    /// Random horizontal direction: -1 or 1.
+    pub fn rand_dir(&mut self) -> i32 {
+        if self.grid.rng.next_u32() & 1 == 0 {
+            -1
+        } else {
+            1
+        }
+    }
+
+    /// True with probability `1/n`. Always true when `n <= 1`.
+    pub fn once_in(&mut self, n: i32) -> bool {
+        self.rand_int(n) == 0
+    }
+
+    /// True with probability `percent`/100. 0 and 100+ don't draw from the RNG.
+    pub fn chance(&mut self, percent: u8) -> bool {
+        match percent {
+            0 => false,
+            100.. => true,
+            p => self.rand_int(100) < i32::from(p),
+        }
+    }
+
+    /// Uniform random byte, for seeding `ra`/`rb` registers.
+    pub fn rand_byte(&mut self) -> u8 {
+        self.grid.rng.byte()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::cell::Species;
+    use crate::elements::registry::arb_species;
     use proptest::prelude::*;
 
-    fn arb_species() -> impl Strategy<Value = Species> {
-        prop_oneof![
-            Just(Species::Empty),
-            Just(Species::Sand),
-            Just(Species::Water),
-            Just(Species::Wall),
-            Just(Species::Fire),
-            Just(Species::Ghost),
-        ]
-    }
-
     fn arb_cell() -> impl Strategy<Value = Cell> {
         (arb_species(), any::<u8>(), any::<u8>(), any::<u8>()).prop_map(
-            |(species, ra, rb, clock)| Cell { species, ra, rb, clock },
+            |(species, ra, rb, clock)| Cell {
+                species,
+                ra,
+                rb,
+                clock,
+            },
         )
     }
 
@@ -110,4 +150,25 @@ mod tests {
             prop_assert_eq!(api.grid.cells.as_slice(), before.as_slice());
         }
     }
+
+    proptest! {
+        #[test]
+        fn prop_sandapi_random_helpers_in_range(seed in any::<u64>(), n in 1i32..500) {
+            let mut grid = Grid::with_seed(4, 4, seed);
+            let mut api = SandApi::new(&mut grid, 1, 1, 1);
+            for _ in 0..50 {
+                let v = api.rand_int(n);
+                prop_assert!((0..n).contains(&v));
+                let d = api.rand_dir();
+                prop_assert!(d == -1 || d == 1);
+            }
+            prop_assert!(api.once_in(1));
+            prop_assert_eq!(api.rand_int(0), 0);
+
+            let state = api.grid.rng;
+            prop_assert!(api.chance(100));
+            prop_assert!(!api.chance(0));
+            prop_assert_eq!(api.grid.rng, state);
+        }
+    }
 }
@@ -0,0 +1,331 @@
+//! Plain-text pictures of grids for tests and debugging.
+//!
+//! Each cell is one character, the species' `ElementDef::glyph`: `.`
+//! Empty, `s` Sand, `~` Water, `#` Wall, `f` Fire, `g` Ghost, `m` Smoke,
+//! `v` Steam, `l` Lava, `@` Stone, `a` Acid, `=` Glass, `i` Ice, `w` Wood,
+//! `o` Oil and `p` Gunpowder. Rows are lines, top first.
+//!
+//! ```
+//! use simulation::Grid;
+//!
+//! let mut grid = Grid::from_ascii(".s. / ... / ###").unwrap();
+//! grid.tick();
+//! simulation::ascii::assert_ascii_eq(&grid, "... / .s. / ###");
+//! ```
+//!
+//! Rows are separated by newlines or ` / `. The parser trims whitespace
+//! around each row and skips blank ones, so multi-line pictures can be
+//! indented inside string literals. Only species survive the round trip:
+//! `ra`, `rb` and heat are reinitialized as `Universe::set_cell` would.
+
+use crate::cell::Species;
+use crate::elements::{spawn_cell, ELEMENTS};
+use crate::Grid;
+use std::fmt::{self, Write as _};
+
+/// Differing cells listed by `assert_grid_eq` before it stops counting.
+const MAX_LISTED_DIFFS: usize = 8;
+
+/// Text could not be parsed as a grid picture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AsciiError {
+    /// The text has no rows.
+    Empty,
+    /// A character is not any species' glyph.
+    UnknownGlyph {
+        row: usize,
+        column: usize,
+        glyph: char,
+    },
+    /// A row is wider or narrower than the first.
+    RaggedRow {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for AsciiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Empty => f.write_str("grid picture has no rows"),
+            Self::UnknownGlyph { row, column, glyph } => {
+                write!(
+                    f,
+                    "row {row}, column {column}: {glyph:?} is not a species glyph"
+                )
+            }
+            Self::RaggedRow {
+                row,
+                expected,
+                found,
+            } => {
+                write!(f, "row {row} is {found} cells wide, expected {expected}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AsciiError {}
+
+/// The species drawn as `glyph`.
+fn glyph_species(glyph: char) -> Option<Species> {
+    ELEMENTS
+        .iter()
+        .find(|def| def.glyph == glyph)
+        .map(|def| def.species)
+}
+
+fn glyph(species: Species) -> char {
+    ELEMENTS[species as usize].glyph
+}
+
+impl Grid {
+    /// Build a grid from a text picture; see the [module docs](self).
+    ///
+    /// Cells get the same per-species initialization as
+    /// `Universe::set_cell`, and ghost cells all join one ghost group,
+    /// allocated at the first of them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`AsciiError`] if the picture has no rows, rows of
+    /// different widths, or a character that is not a species glyph.
+    pub fn from_ascii(text: &str) -> Result<Self, AsciiError> {
+        let rows: Vec<&str> = text
+            .lines()
+            .flat_map(|line| line.split(" / "))
+            .map(str::trim)
+            .filter(|row| !row.is_empty())
+            .collect();
+        let width = rows.first().ok_or(AsciiError::Empty)?.chars().count();
+
+        let mut grid = Self::new(width, rows.len());
+        let mut ghost_group = None;
+        for (y, row) in rows.iter().enumerate() {
+            let found = row.chars().count();
+            if found != width {
+                return Err(AsciiError::RaggedRow {
+                    row: y,
+                    expected: width,
+                    found,
+                });
+            }
+            for (x, ch) in row.chars().enumerate() {
+                let species = glyph_species(ch).ok_or(AsciiError::UnknownGlyph {
+                    row: y,
+                    column: x,
+                    glyph: ch,
+                })?;
+                let mut cell = spawn_cell(species, &mut grid.rng);
+                if species == Species::Ghost {
+                    cell.ra = *ghost_group.get_or_insert_with(|| grid.alloc_ghost_group());
+                }
+                grid.cells[y * width + x] = cell;
+            }
+        }
+        grid.wake_all();
+        Ok(grid)
+    }
+
+    /// Render the grid as a text picture, one line per row.
+    #[must_use]
+    pub fn to_ascii(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for Grid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.cells.chunks(self.width.max(1)) {
+            for cell in row {
+                f.write_char(glyph(cell.species))?;
+            }
+            f.write_char('\n')?;
+        }
+        Ok(())
+    }
+}
+
+/// Side-by-side picture of where two grids' species differ, or `None` if
+/// they match.
+#[must_use]
+pub fn diff(actual: &Grid, expected: &Grid) -> Option<String> {
+    if (actual.width, actual.height) != (expected.width, expected.height) {
+        return Some(format!(
+            "grid is {}×{}, expected {}×{}\nactual:\n{actual}expected:\n{expected}",
+            actual.width, actual.height, expected.width, expected.height,
+        ));
+    }
+    let differing: Vec<usize> = (0..actual.cells.len())
+        .filter(|&i| actual.cells[i].species != expected.cells[i].species)
+        .collect();
+    if differing.is_empty() {
+        return None;
+    }
+
+    let width = actual.width;
+    let pad = width.max("actual".len());
+    let mut out = format!("{} cells differ\n", differing.len());
+    let _ = writeln!(out, "  {:<pad$}   expected", "actual");
+    for (y, (a, e)) in actual
+        .cells
+        .chunks(width)
+        .zip(expected.cells.chunks(width))
+        .enumerate()
+    {
+        let marker = if differing.iter().any(|&i| i / width == y) {
+            '>'
+        } else {
+            ' '
+        };
+        let a: String = a.iter().map(|c| glyph(c.species)).collect();
+        let e: String = e.iter().map(|c| glyph(c.species)).collect();
+        let _ = writeln!(out, "{marker} {a:<pad$} | {e}");
+    }
+    for &i in differing.iter().take(MAX_LISTED_DIFFS) {
+        let (x, y) = (i % width, i / width);
+        let _ = writeln!(
+            out,
+            "  ({x}, {y}): {} ≠ {}",
+            actual.cells[i].species, expected.cells[i].species
+        );
+    }
+    if differing.len() > MAX_LISTED_DIFFS {
+        let _ = writeln!(out, "  … and {} more", differing.len() - MAX_LISTED_DIFFS);
+    }
+    Some(out)
+}
+
+/// Assert that two grids hold the same species everywhere.
+///
+/// # Panics
+///
+/// Panics with a side-by-side picture of both grids, rows with
+/// differences marked `>`, if any cell's species differs.
+#[track_caller]
+pub fn assert_grid_eq(actual: &Grid, expected: &Grid) {
+    if let Some(picture) = diff(actual, expected) {
+        panic!("grids differ: {picture}");
+    }
+}
+
+/// Assert that `actual` matches the text picture `expected`.
+///
+/// # Panics
+///
+/// Panics if `expected` doesn't parse, or as [`assert_grid_eq`] does.
+#[track_caller]
+pub fn assert_ascii_eq(actual: &Grid, expected: &str) {
+    match Grid::from_ascii(expected) {
+        Ok(expected) => assert_grid_eq(actual, &expected),
+        Err(err) => panic!("expected picture is invalid: {err}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cell::Cell;
+
+    #[test]
+    fn glyphs_are_unique_and_printable() {
+        for (i, a) in ELEMENTS.iter().enumerate() {
+            assert!(a.glyph.is_ascii_graphic(), "{} glyph {:?}", a.name, a.glyph);
+            assert_ne!(a.glyph, '/', "{} glyph would split rows", a.name);
+            for b in &ELEMENTS[i + 1..] {
+                assert_ne!(a.glyph, b.glyph, "{} and {} share a glyph", a.name, b.name);
+            }
+        }
+    }
+
+    #[test]
+    fn every_species_round_trips() {
+        let picture: String = ELEMENTS.iter().map(|def| def.glyph).collect();
+        let grid = Grid::from_ascii(&picture).unwrap();
+        assert_eq!(grid.to_ascii(), format!("{picture}\n"));
+        for (cell, def) in grid.cells.iter().zip(&ELEMENTS) {
+            assert_eq!(cell.species, def.species);
+        }
+    }
+
+    #[test]
+    fn parses_indented_and_one_line_pictures() {
+        let block = Grid::from_ascii(
+            "
+            .~.
+            #s#
+            ",
+        )
+        .unwrap();
+        let inline = Grid::from_ascii(".~. / #s#").unwrap();
+        assert_eq!((block.width, block.height), (3, 2));
+        assert_eq!(block.get(1, 1).species, Species::Sand);
+        assert_grid_eq(&inline, &block);
+    }
+
+    #[test]
+    fn initializes_cells_like_set_cell() {
+        let grid = Grid::from_ascii("fg").unwrap();
+        assert!(grid.get(0, 0).rb >= 20, "fire needs a lifetime");
+        assert_ne!(grid.get(1, 0).ra, 0, "ghosts need a group");
+
+        let grid = Grid::from_ascii("f.").unwrap();
+        assert_eq!(grid.next_ghost_group, Grid::new(2, 1).next_ghost_group);
+    }
+
+    #[test]
+    fn malformed_pictures_are_rejected() {
+        assert_eq!(Grid::from_ascii("  \n\n").unwrap_err(), AsciiError::Empty);
+        assert_eq!(
+            Grid::from_ascii("..\n...").unwrap_err(),
+            AsciiError::RaggedRow {
+                row: 1,
+                expected: 2,
+                found: 3
+            }
+        );
+        let err = Grid::from_ascii("..\n.Q").unwrap_err();
+        assert_eq!(
+            err,
+            AsciiError::UnknownGlyph {
+                row: 1,
+                column: 1,
+                glyph: 'Q'
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "row 1, column 1: 'Q' is not a species glyph"
+        );
+    }
+
+    #[test]
+    fn diff_marks_the_changed_rows() {
+        let expected = Grid::from_ascii("... / .s. / ###").unwrap();
+        let mut actual = Grid::from_ascii("... / .s. / ###").unwrap();
+        assert_eq!(diff(&actual, &expected), None);
+
+        actual.set(1, 1, Cell::empty());
+        actual.set(1, 0, Cell::new(Species::Water));
+        let picture = diff(&actual, &expected).unwrap();
+        assert!(picture.starts_with("2 cells differ\n"), "{picture}");
+        assert!(picture.contains("> .~.    | ...\n"), "{picture}");
+        assert!(picture.contains("> ...    | .s.\n"), "{picture}");
+        assert!(picture.contains("  ###    | ###\n"), "{picture}");
+        assert!(picture.contains("(1, 1): Empty ≠ Sand"), "{picture}");
+
+        let small = Grid::from_ascii("..").unwrap();
+        assert!(diff(&small, &expected)
+            .unwrap()
+            .starts_with("grid is 2×1, expected 3×3"));
+    }
+
+    #[test]
+    #[should_panic(expected = "grids differ")]
+    fn assert_ascii_eq_panics_on_mismatch() {
+        let grid = Grid::from_ascii(".s.").unwrap();
+        assert_ascii_eq(&grid, "..s");
+    }
+}
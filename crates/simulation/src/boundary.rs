@@ -0,0 +1,370 @@
+//! What lies beyond each edge of the grid.
+//!
+//! Every edge has a [`Boundary`], chosen when the grid is built
+//! ([`Grid::with_boundaries`]) and kept in `Grid::boundaries`. Reads and
+//! writes through `Grid::get`/`Grid::set`, and therefore `SandApi`, resolve
+//! out-of-bounds coordinates through it:
+//!
+//! - [`Boundary::Wall`] reads as Wall and drops writes: the world is a
+//!   sealed box. This is the default.
+//! - [`Boundary::Void`] reads as Empty and drops writes, so particles step
+//!   off the edge and vanish.
+//! - [`Boundary::Wrap`] continues on the opposite side. Wrapping the left
+//!   edge makes `x = -1` read column `width - 1`; wrapping both left and
+//!   right makes the grid a cylinder, and all four a torus.
+//!
+//! A coordinate past two edges at once (a corner) is solid if either edge
+//! is a Wall. Heat diffuses across Wrap edges too; Wall and Void edges
+//! insulate it.
+
+use crate::chunk::DirtyRect;
+use crate::Grid;
+use wasm_bindgen::prelude::*;
+
+/// Behaviour of one grid edge.
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[repr(u8)]
+pub enum Boundary {
+    /// Solid: reads as Wall, writes are dropped.
+    #[default]
+    Wall = 0,
+    /// Open: reads as Empty, writes are dropped.
+    Void = 1,
+    /// Continues on the opposite edge.
+    Wrap = 2,
+}
+
+impl Boundary {
+    /// Look up a boundary by its numeric id. Returns `None` for unknown ids.
+    #[must_use]
+    pub fn from_id(id: u8) -> Option<Self> {
+        [Self::Wall, Self::Void, Self::Wrap]
+            .into_iter()
+            .find(|&b| b as u8 == id)
+    }
+}
+
+/// The [`Boundary`] of each edge of a grid.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Boundaries {
+    pub left: Boundary,
+    pub right: Boundary,
+    pub top: Boundary,
+    pub bottom: Boundary,
+}
+
+impl Boundaries {
+    /// The same behaviour on every edge.
+    #[must_use]
+    pub const fn all(boundary: Boundary) -> Self {
+        Self {
+            left: boundary,
+            right: boundary,
+            top: boundary,
+            bottom: boundary,
+        }
+    }
+
+    /// Edge ids in saved-state order: left, right, top, bottom.
+    pub(crate) fn to_ids(self) -> [u8; 4] {
+        [self.left, self.right, self.top, self.bottom].map(|b| b as u8)
+    }
+
+    /// Inverse of [`Boundaries::to_ids`]; `None` if any id is unknown.
+    pub(crate) fn from_ids([left, right, top, bottom]: [u8; 4]) -> Option<Self> {
+        Some(Self {
+            left: Boundary::from_id(left)?,
+            right: Boundary::from_id(right)?,
+            top: Boundary::from_id(top)?,
+            bottom: Boundary::from_id(bottom)?,
+        })
+    }
+
+    pub(crate) fn any_wrap(self) -> bool {
+        [self.left, self.right, self.top, self.bottom].contains(&Boundary::Wrap)
+    }
+}
+
+/// Where a coordinate lands once edges are applied.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Position {
+    /// A grid cell, possibly reached by wrapping.
+    Inside(i32, i32),
+    /// Past a Wall or Void edge.
+    Beyond(Boundary),
+}
+
+/// Resolve `pos` along one axis of length `len`.
+///
+/// An axis of length 0 has nothing to wrap onto, so a Wrap edge there acts
+/// as Void.
+fn locate_axis(pos: i32, len: i32, low: Boundary, high: Boundary) -> Result<i32, Boundary> {
+    let edge = if pos < 0 {
+        low
+    } else if pos >= len {
+        high
+    } else {
+        return Ok(pos);
+    };
+    match edge {
+        Boundary::Wrap if len > 0 => Ok(pos.rem_euclid(len)),
+        Boundary::Wrap => Err(Boundary::Void),
+        other => Err(other),
+    }
+}
+
+/// Resolve `(x, y)` through `boundaries` on a `width`×`height` grid.
+fn locate(width: usize, height: usize, boundaries: Boundaries, x: i32, y: i32) -> Position {
+    let b = boundaries;
+    let x = locate_axis(x, width as i32, b.left, b.right);
+    let y = locate_axis(y, height as i32, b.top, b.bottom);
+    match (x, y) {
+        (Ok(x), Ok(y)) => Position::Inside(x, y),
+        (Err(Boundary::Wall), _) | (_, Err(Boundary::Wall)) => Position::Beyond(Boundary::Wall),
+        (Err(edge), _) | (_, Err(edge)) => Position::Beyond(edge),
+    }
+}
+
+impl Grid {
+    /// Create an empty grid with the given edge behaviour.
+    ///
+    /// See [`Grid::with_seed`] for `seed` and the [`boundary`](self) module
+    /// for the modes.
+    #[must_use]
+    pub fn with_boundaries(width: usize, height: usize, seed: u64, boundaries: Boundaries) -> Self {
+        let mut grid = Self::with_seed(width, height, seed);
+        grid.boundaries = boundaries;
+        grid
+    }
+
+    /// Resolve `(x, y)` through the edges.
+    #[inline]
+    pub(crate) fn locate(&self, x: i32, y: i32) -> Position {
+        locate(self.width, self.height, self.boundaries, x, y)
+    }
+
+    /// True if `(x, y)` is a grid cell, directly or through a wrapping edge.
+    #[must_use]
+    pub fn is_reachable(&self, x: i32, y: i32) -> bool {
+        matches!(self.locate(x, y), Position::Inside(..))
+    }
+
+    /// After a write at `(x, y)`, wake the cells within `margin` of it that
+    /// see it through a wrapping edge.
+    pub(crate) fn wake_across_edges(&mut self, x: i32, y: i32, margin: i32) {
+        let edges = self.boundaries;
+        let (w, h) = (self.width as i32, self.height as i32);
+        if !edges.any_wrap() || (x >= margin && x < w - margin && y >= margin && y < h - margin) {
+            return;
+        }
+        // A cell across the right seam sees this one through its own right
+        // edge, and so on, so each side follows the opposite edge's mode.
+        let xs = seam_spans(x - margin, x + margin, w, edges.right, edges.left);
+        let ys = seam_spans(y - margin, y + margin, h, edges.bottom, edges.top);
+        for &(min_y, max_y, wrapped_y) in ys.iter().flatten() {
+            for &(min_x, max_x, wrapped_x) in xs.iter().flatten() {
+                if wrapped_x || wrapped_y {
+                    self.wake(DirtyRect {
+                        min_x,
+                        min_y,
+                        max_x,
+                        max_y,
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Split `lo..=hi` on an axis of length `len` into the part on the grid and
+/// the parts past either end that wrap, moved back onto the grid. Each span
+/// is `(min, max, wrapped)`; `below` and `above` govern the parts past the
+/// low and high ends.
+fn seam_spans(
+    lo: i32,
+    hi: i32,
+    len: i32,
+    below: Boundary,
+    above: Boundary,
+) -> [Option<(i32, i32, bool)>; 3] {
+    [
+        (lo < 0 && below == Boundary::Wrap).then(|| (lo + len, len - 1, true)),
+        Some((lo.max(0), hi.min(len - 1), false)),
+        (hi >= len && above == Boundary::Wrap).then(|| (0, hi - len, true)),
+    ]
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::cell::{Cell, Species};
+    use crate::elements::ghost::{shared_direction, MOVE_DIVISOR};
+    use proptest::prelude::*;
+
+    pub(crate) fn arb_boundary() -> impl Strategy<Value = Boundary> {
+        prop_oneof![
+            Just(Boundary::Wall),
+            Just(Boundary::Void),
+            Just(Boundary::Wrap)
+        ]
+    }
+
+    pub(crate) fn arb_boundaries() -> impl Strategy<Value = Boundaries> {
+        (
+            arb_boundary(),
+            arb_boundary(),
+            arb_boundary(),
+            arb_boundary(),
+        )
+            .prop_map(|(left, right, top, bottom)| Boundaries {
+                left,
+                right,
+                top,
+                bottom,
+            })
+    }
+
+    fn grid(width: usize, height: usize, boundaries: Boundaries) -> Grid {
+        Grid::with_boundaries(width, height, 1, boundaries)
+    }
+
+    #[test]
+    fn reads_and_writes_follow_each_edge() {
+        let boundaries = Boundaries {
+            left: Boundary::Wrap,
+            right: Boundary::Void,
+            top: Boundary::Wall,
+            bottom: Boundary::Wrap,
+        };
+        let mut g = grid(4, 3, boundaries);
+        g.set(3, 1, Cell::new(Species::Sand));
+        g.set(0, 0, Cell::new(Species::Water));
+
+        assert_eq!(
+            g.get(-1, 1).species,
+            Species::Sand,
+            "left wraps to the right column"
+        );
+        assert_eq!(g.get(4, 1).species, Species::Empty, "right is void");
+        assert_eq!(g.get(1, -1).species, Species::Wall, "top is solid");
+        assert_eq!(
+            g.get(0, 3).species,
+            Species::Water,
+            "bottom wraps to the top row"
+        );
+        assert_eq!(
+            g.get(4, -1).species,
+            Species::Wall,
+            "corners with a wall are solid"
+        );
+
+        let before = g.cells.clone();
+        g.set(4, 0, Cell::wall());
+        g.set(0, -1, Cell::wall());
+        assert_eq!(
+            g.cells, before,
+            "writes past void and wall edges are dropped"
+        );
+        g.set(-1, 4, Cell::wall());
+        assert_eq!(g.get(3, 1).species, Species::Wall, "writes wrap too");
+    }
+
+    #[test]
+    fn empty_grids_with_wrapping_edges_do_not_panic() {
+        for (w, h) in [(0, 4), (4, 0), (0, 0)] {
+            let mut g = grid(w, h, Boundaries::all(Boundary::Wrap));
+            assert_eq!(g.get(-1, 1).species, Species::Empty);
+            assert_eq!(g.get(1, 5).species, Species::Empty);
+            g.set(-1, -1, Cell::new(Species::Sand));
+            g.tick();
+        }
+    }
+
+    #[test]
+    fn void_floor_drains_sand() {
+        let mut g = Grid::from_ascii("..s.. / ..s.. / ..s.. / #...#").unwrap();
+        g.boundaries.bottom = Boundary::Void;
+        for _ in 0..10 {
+            g.tick();
+        }
+        assert!(g.cells.iter().all(|c| c.species != Species::Sand));
+    }
+
+    #[test]
+    fn wrapped_sand_falls_forever() {
+        let mut g = grid(
+            3,
+            4,
+            Boundaries {
+                top: Boundary::Wrap,
+                bottom: Boundary::Wrap,
+                ..Boundaries::default()
+            },
+        );
+        g.set(1, 3, Cell::new(Species::Sand));
+        g.tick();
+        assert_eq!(g.get(1, 0).species, Species::Sand);
+        for _ in 0..20 {
+            g.tick();
+        }
+        let sand = g
+            .cells
+            .iter()
+            .filter(|c| c.species == Species::Sand)
+            .count();
+        assert_eq!(sand, 1);
+    }
+
+    #[test]
+    fn writes_wake_cells_across_a_seam() {
+        let mut g = grid(
+            96,
+            8,
+            Boundaries {
+                left: Boundary::Wrap,
+                right: Boundary::Wrap,
+                ..Boundaries::default()
+            },
+        );
+        for x in 0..96 {
+            g.set(x, 7, Cell::wall());
+        }
+        g.set(0, 6, Cell::new(Species::Sand));
+        g.set(0, 5, Cell::new(Species::Sand));
+        for _ in 0..4 {
+            g.tick();
+        }
+        // The top grain is resting on the bottom one; clearing the far
+        // column's floor gives it a diagonal to slide into across the seam.
+        assert_eq!(g.active_chunk_count(), 0);
+        g.set(95, 7, Cell::empty());
+        assert!(
+            g.active_chunk_count() > 1,
+            "the chunk holding x = 0 must wake too"
+        );
+    }
+
+    #[test]
+    fn ghosts_wrap_and_drop_off_void_edges() {
+        for boundary in [Boundary::Wrap, Boundary::Void, Boundary::Wall] {
+            let mut g = grid(5, 5, Boundaries::all(boundary));
+            let group = g.alloc_ghost_group();
+            let mut ghost = Cell::new(Species::Ghost);
+            ghost.ra = group;
+            g.set(0, 0, ghost);
+            g.tick_count = MOVE_DIVISOR * 7;
+            g.move_ghosts();
+
+            let (dx, dy) = shared_direction(g.tick_count, group);
+            let ghosts: Vec<usize> = (0..25)
+                .filter(|&i| g.cells[i].species == Species::Ghost)
+                .collect();
+            match (boundary, g.locate(dx, dy)) {
+                (_, Position::Inside(x, y)) => assert_eq!(ghosts, [y as usize * 5 + x as usize]),
+                (Boundary::Void, _) => assert!(ghosts.is_empty()),
+                _ => assert_eq!(ghosts, [0], "blocked by the wall"),
+            }
+        }
+    }
+}
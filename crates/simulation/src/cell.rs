@@ -1,5 +1,6 @@
 //! Cell and Species types for the simulation grid.
 
+use crate::elements::{element, ELEMENTS};
 use std::fmt;
 
 /// Discriminant values map directly to the `r8uint` GPU texture — do not reorder.
@@ -13,19 +14,28 @@ pub enum Species {
     Fire = 4,
     Ghost = 5,
     Smoke = 6,
+    Steam = 7,
+    Lava = 8,
+    Stone = 9,
+    Acid = 10,
+    Glass = 11,
+    Ice = 12,
+    Wood = 13,
+    Oil = 14,
+    Gunpowder = 15,
+}
+
+impl Species {
+    /// Look up a species by its numeric id. Returns `None` for unknown ids.
+    #[must_use]
+    pub fn from_id(id: u8) -> Option<Self> {
+        ELEMENTS.get(usize::from(id)).map(|def| def.species)
+    }
 }
 
 impl fmt::Display for Species {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Self::Empty => write!(f, "Empty"),
-            Self::Sand => write!(f, "Sand"),
-            Self::Water => write!(f, "Water"),
-            Self::Wall => write!(f, "Wall"),
-            Self::Fire => write!(f, "Fire"),
-            Self::Ghost => write!(f, "Ghost"),
-            Self::Smoke => write!(f, "Smoke"),
-        }
+        f.write_str(element(*self).name)
     }
 }
 
@@ -73,7 +83,6 @@ impl Cell {
     }
 }
 
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -93,6 +102,15 @@ mod tests {
         assert_eq!(Species::Fire as u8, 4);
         assert_eq!(Species::Ghost as u8, 5);
         assert_eq!(Species::Smoke as u8, 6);
+        assert_eq!(Species::Steam as u8, 7);
+        assert_eq!(Species::Lava as u8, 8);
+        assert_eq!(Species::Stone as u8, 9);
+        assert_eq!(Species::Acid as u8, 10);
+        assert_eq!(Species::Glass as u8, 11);
+        assert_eq!(Species::Ice as u8, 12);
+        assert_eq!(Species::Wood as u8, 13);
+        assert_eq!(Species::Oil as u8, 14);
+        assert_eq!(Species::Gunpowder as u8, 15);
     }
 
     #[test]
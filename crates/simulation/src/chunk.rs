@@ -0,0 +1,223 @@
+//! Dirty-rect chunk bookkeeping for the tick scheduler.
+//!
+//! The grid is split into `CHUNK_SIZE`×`CHUNK_SIZE` chunks. Each chunk
+//! tracks two dirty rectangles in grid coordinates:
+//!
+//! - `current`: cells the tick in progress must visit.
+//! - `next`: cells the following tick must visit.
+//!
+//! Every write through `Grid::set` widens both rectangles by the written
+//! cell plus a one-cell margin, so neighbours that may now be able to move
+//! (sand above a cell that just emptied) are woken too. Around water the
+//! margin is `elements::MAX_REACH`, since water reads that far. At the
+//! start of each tick `next` becomes `current` and `next` is cleared, so a
+//! chunk that sees no writes for a full tick goes to sleep and costs
+//! nothing.
+
+use std::ops::Range;
+
+/// Side length of a simulation chunk in cells.
+pub const CHUNK_SIZE: usize = 32;
+
+/// Chunks per side of a scheduling tile.
+pub(crate) const TILE_CHUNKS: usize = 2;
+
+/// Tile update order within a tick, as `(tx % 2, ty % 2)` parities.
+pub(crate) const PHASES: [(usize, usize); 4] = [(0, 0), (1, 0), (0, 1), (1, 1)];
+
+/// A block of up to `TILE_CHUNKS`×`TILE_CHUNKS` chunks that a tick updates
+/// as a unit, with its own random stream.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct Tile {
+    /// Chunk columns covered.
+    pub cols: Range<usize>,
+    /// Chunk rows covered.
+    pub rows: Range<usize>,
+}
+
+impl Tile {
+    /// Key the tile's random stream is forked with.
+    pub fn rng_key(&self) -> u64 {
+        ((self.rows.start as u64) << 32) | self.cols.start as u64
+    }
+}
+
+/// Inclusive bounding rectangle of cells that need updating.
+///
+/// An empty rectangle is represented by `min > max` on either axis.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct DirtyRect {
+    pub min_x: i32,
+    pub min_y: i32,
+    pub max_x: i32,
+    pub max_y: i32,
+}
+
+impl DirtyRect {
+    /// A rectangle containing no cells.
+    pub const EMPTY: Self = Self {
+        min_x: i32::MAX,
+        min_y: i32::MAX,
+        max_x: i32::MIN,
+        max_y: i32::MIN,
+    };
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.min_x > self.max_x || self.min_y > self.max_y
+    }
+
+    #[must_use]
+    pub fn contains_row(&self, y: i32) -> bool {
+        y >= self.min_y && y <= self.max_y
+    }
+
+    /// Grow this rectangle to cover `other`.
+    pub fn union(&mut self, other: Self) {
+        self.min_x = self.min_x.min(other.min_x);
+        self.min_y = self.min_y.min(other.min_y);
+        self.max_x = self.max_x.max(other.max_x);
+        self.max_y = self.max_y.max(other.max_y);
+    }
+
+    /// Shift this rectangle by `(dx, dy)`. Empty rectangles stay empty.
+    #[must_use]
+    pub fn translate(self, dx: i32, dy: i32) -> Self {
+        if self.is_empty() {
+            return Self::EMPTY;
+        }
+        Self {
+            min_x: self.min_x + dx,
+            min_y: self.min_y + dy,
+            max_x: self.max_x + dx,
+            max_y: self.max_y + dy,
+        }
+    }
+
+    /// Clip this rectangle to `other`, returning an empty rect if they don't overlap.
+    #[must_use]
+    pub fn intersect(self, other: Self) -> Self {
+        let clipped = Self {
+            min_x: self.min_x.max(other.min_x),
+            min_y: self.min_y.max(other.min_y),
+            max_x: self.max_x.min(other.max_x),
+            max_y: self.max_y.min(other.max_y),
+        };
+        if clipped.is_empty() {
+            Self::EMPTY
+        } else {
+            clipped
+        }
+    }
+}
+
+/// Scheduling state for one chunk of the grid.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Chunk {
+    /// Cells covered by this chunk, in grid coordinates.
+    pub bounds: DirtyRect,
+    /// Cells to visit during the tick in progress.
+    pub current: DirtyRect,
+    /// Cells to visit during the next tick.
+    pub next: DirtyRect,
+}
+
+impl Chunk {
+    #[must_use]
+    pub fn new(bounds: DirtyRect) -> Self {
+        Self {
+            bounds,
+            current: DirtyRect::EMPTY,
+            next: DirtyRect::EMPTY,
+        }
+    }
+
+    /// True if this chunk has any cells scheduled for the next tick.
+    #[must_use]
+    pub fn is_awake(&self) -> bool {
+        !self.next.is_empty()
+    }
+
+    /// Schedule `rect` (clipped to this chunk) for this tick and the next.
+    pub fn wake(&mut self, rect: DirtyRect) {
+        let clipped = rect.intersect(self.bounds);
+        if clipped.is_empty() {
+            return;
+        }
+        self.current.union(clipped);
+        self.next.union(clipped);
+    }
+
+    /// Schedule `rect` (clipped to this chunk) for the next tick only.
+    pub fn wake_next(&mut self, rect: DirtyRect) {
+        let clipped = rect.intersect(self.bounds);
+        if !clipped.is_empty() {
+            self.next.union(clipped);
+        }
+    }
+
+    /// Roll the schedule over at the start of a tick.
+    pub fn swap(&mut self) {
+        self.current = self.next;
+        self.next = DirtyRect::EMPTY;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(min_x: i32, min_y: i32, max_x: i32, max_y: i32) -> DirtyRect {
+        DirtyRect {
+            min_x,
+            min_y,
+            max_x,
+            max_y,
+        }
+    }
+
+    #[test]
+    fn empty_rect_is_empty() {
+        assert!(DirtyRect::EMPTY.is_empty());
+        assert!(!rect(0, 0, 0, 0).is_empty());
+    }
+
+    #[test]
+    fn union_grows_to_cover_both() {
+        let mut r = rect(2, 2, 3, 3);
+        r.union(rect(0, 5, 1, 6));
+        assert_eq!(r, rect(0, 2, 3, 6));
+
+        let mut e = DirtyRect::EMPTY;
+        e.union(rect(4, 4, 5, 5));
+        assert_eq!(e, rect(4, 4, 5, 5));
+    }
+
+    #[test]
+    fn translate_keeps_empty_rect_empty() {
+        assert_eq!(rect(1, 2, 3, 4).translate(-1, 10), rect(0, 12, 2, 14));
+        assert!(DirtyRect::EMPTY.translate(-5, 5).is_empty());
+    }
+
+    #[test]
+    fn wake_clips_to_chunk_bounds() {
+        let mut chunk = Chunk::new(rect(0, 0, 31, 31));
+        chunk.wake(rect(30, -1, 32, 1));
+        assert_eq!(chunk.current, rect(30, 0, 31, 1));
+        assert_eq!(chunk.next, rect(30, 0, 31, 1));
+
+        chunk.wake(rect(40, 40, 41, 41));
+        assert_eq!(chunk.next, rect(30, 0, 31, 1));
+    }
+
+    #[test]
+    fn swap_moves_next_into_current() {
+        let mut chunk = Chunk::new(rect(0, 0, 31, 31));
+        chunk.wake(rect(1, 1, 2, 2));
+        chunk.swap();
+        assert_eq!(chunk.current, rect(1, 1, 2, 2));
+        assert!(!chunk.is_awake());
+        chunk.swap();
+        assert!(chunk.current.is_empty());
+    }
+}
@@ -0,0 +1,380 @@
+//! Compact run-length encoding of a [`Grid`].
+//!
+//! Settled worlds are mostly long runs of identical cells (sky, borders,
+//! still water), so this format stores each run once with a count. It
+//! carries the same state as a [`snapshot`](crate::snapshot) and
+//! round-trips losslessly; a run breaks wherever any of `species`, `ra`,
+//! `rb` or `clock` changes.
+//!
+//! Layout, little-endian:
+//!
+//! | Size | Field                                                  |
+//! |------|--------------------------------------------------------|
+//! | 4    | magic `b"SNDC"`                                        |
+//! | 2    | format version ([`COMPACT_VERSION`])                   |
+//! | 1    | flags: bit 0 set if the payload is deflate-compressed  |
+//! | 4    | width                                                  |
+//! | 4    | height                                                 |
+//! | …    | payload                                                |
+//!
+//! The payload holds the tick counter (8), RNG state (8), next ghost
+//! group (1) and the left, right, top and bottom edge ids (4), then cell
+//! runs (`LEB128 count, species, ra, rb, clock`) covering every cell, then
+//! heat runs (`LEB128 count, heat`), then the reaction table and the chunk
+//! schedule as in a snapshot. Only the current version is accepted.
+
+use crate::cell::{Cell, Species};
+use crate::elements::{reactions, Reactions};
+use crate::rng::Rng;
+use crate::snapshot::{schedule_len, Reader, SnapshotError};
+use crate::Grid;
+
+/// Current compact format version.
+pub const COMPACT_VERSION: u16 = 4;
+
+const MAGIC: &[u8; 4] = b"SNDC";
+const FLAG_DEFLATE: u8 = 1;
+/// Compression level for the optional deflate pass (0–10).
+const DEFLATE_LEVEL: u8 = 6;
+/// Payload bytes before the runs: tick counter, RNG state, ghost group,
+/// edges.
+const PAYLOAD_HEADER_LEN: usize = 21;
+/// Largest grid this format will decode (4 Mi cells, e.g. 2048×2048, about
+/// 25 MB once loaded). Runs let a few bytes announce any size, so the
+/// header alone can't be trusted.
+pub(crate) const MAX_CELLS: usize = 1 << 22;
+/// Largest possible encoding of one cell plus its heat: a one-long cell
+/// run (1 + 4 bytes) and a one-long heat run (1 + 1 bytes).
+const MAX_BYTES_PER_CELL: usize = 7;
+
+impl Grid {
+    /// Encode the grid as runs of identical cells.
+    ///
+    /// With `compress` set, the run data is also deflate-compressed, which
+    /// is usually worth it for anything sent over a network or stored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a dimension exceeds `u32::MAX`.
+    #[must_use]
+    pub fn to_compact(&self, compress: bool) -> Vec<u8> {
+        let mut payload = Vec::new();
+        payload.extend_from_slice(&self.tick_count.to_le_bytes());
+        payload.extend_from_slice(&self.rng.state().to_le_bytes());
+        payload.push(self.next_ghost_group);
+        payload.extend_from_slice(&self.boundaries.to_ids());
+        for (len, cell) in runs(&self.cells) {
+            write_varint(&mut payload, len);
+            payload.extend_from_slice(&[cell.species as u8, cell.ra, cell.rb, cell.clock]);
+        }
+        for (len, heat) in runs(&self.heat) {
+            write_varint(&mut payload, len);
+            payload.push(heat);
+        }
+        self.reactions.encode(&mut payload);
+        self.encode_schedule(&mut payload);
+
+        let mut out = Vec::with_capacity(payload.len() + 15);
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&COMPACT_VERSION.to_le_bytes());
+        out.push(if compress { FLAG_DEFLATE } else { 0 });
+        out.extend_from_slice(
+            &u32::try_from(self.width)
+                .expect("width fits in u32")
+                .to_le_bytes(),
+        );
+        out.extend_from_slice(
+            &u32::try_from(self.height)
+                .expect("height fits in u32")
+                .to_le_bytes(),
+        );
+        if compress {
+            out.extend_from_slice(&miniz_oxide::deflate::compress_to_vec(
+                &payload,
+                DEFLATE_LEVEL,
+            ));
+        } else {
+            out.extend_from_slice(&payload);
+        }
+        out
+    }
+
+    /// Decode bytes produced by [`Grid::to_compact`], compressed or not.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`SnapshotError`] if the input is not a compact encoding,
+    /// uses an unsupported version, is truncated or has trailing bytes,
+    /// has runs that don't cover the grid exactly, fails to decompress,
+    /// contains an unknown species or boundary id or a chunk schedule that
+    /// doesn't fit the grid, or announces more than 4 Mi cells.
+    pub fn from_compact(bytes: &[u8]) -> Result<Self, SnapshotError> {
+        let mut reader = Reader { bytes };
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(SnapshotError::BadMagic);
+        }
+        let version = reader.u16()?;
+        if version != COMPACT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion(version));
+        }
+        let flags = reader.u8()?;
+        if flags & !FLAG_DEFLATE != 0 {
+            return Err(SnapshotError::Corrupt);
+        }
+        let too_large = || SnapshotError::TooLarge;
+        let width = usize::try_from(reader.u32()?).map_err(|_| too_large())?;
+        let height = usize::try_from(reader.u32()?).map_err(|_| too_large())?;
+        let cells = width
+            .checked_mul(height)
+            .filter(|&n| n <= MAX_CELLS)
+            .ok_or_else(too_large)?;
+
+        let inflated;
+        let payload = if flags & FLAG_DEFLATE != 0 {
+            // Bound the output so a crafted stream can't balloon in memory.
+            let limit = cells
+                .checked_mul(MAX_BYTES_PER_CELL)
+                .and_then(|n| n.checked_add(PAYLOAD_HEADER_LEN + reactions::MAX_ENCODED_LEN))
+                .and_then(|n| n.checked_add(schedule_len(width, height)))
+                .ok_or_else(too_large)?;
+            inflated = miniz_oxide::inflate::decompress_to_vec_with_limit(reader.bytes, limit)
+                .map_err(|_| SnapshotError::Corrupt)?;
+            &inflated[..]
+        } else {
+            reader.bytes
+        };
+
+        let mut reader = Reader { bytes: payload };
+        let tick_count = reader.u64()?;
+        let rng_state = reader.u64()?;
+        let next_ghost_group = reader.u8()?;
+        let boundaries = reader.boundaries()?;
+
+        // Runs are written straight into the grid's own buffers.
+        let mut grid = Self::new(width, height);
+        let mut filled = 0;
+        while filled < cells {
+            let len = read_run_len(&mut reader, cells - filled)?;
+            let raw = reader.take(4)?;
+            let Some(species) = Species::from_id(raw[0]) else {
+                return Err(SnapshotError::UnknownSpecies {
+                    index: filled,
+                    id: raw[0],
+                });
+            };
+            grid.cells[filled..filled + len].fill(Cell {
+                species,
+                ra: raw[1],
+                rb: raw[2],
+                clock: raw[3],
+            });
+            filled += len;
+        }
+        filled = 0;
+        while filled < cells {
+            let len = read_run_len(&mut reader, cells - filled)?;
+            grid.heat[filled..filled + len].fill(reader.u8()?);
+            filled += len;
+        }
+        grid.reactions = Reactions::decode(&mut reader)?;
+        grid.decode_schedule(&mut reader)?;
+        if !reader.bytes.is_empty() {
+            return Err(SnapshotError::TrailingBytes);
+        }
+
+        grid.tick_count = tick_count;
+        grid.generation = tick_count as u8;
+        grid.rng = Rng::from_state(rng_state);
+        grid.next_ghost_group = next_ghost_group.max(1);
+        grid.boundaries = boundaries;
+        Ok(grid)
+    }
+}
+
+/// Split `items` into `(run length, value)` pairs of equal neighbours.
+fn runs<T: Copy + PartialEq>(items: &[T]) -> impl Iterator<Item = (usize, T)> + '_ {
+    items.chunk_by(|a, b| a == b).map(|run| (run.len(), run[0]))
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Read a LEB128 run length in `1..=remaining`.
+fn read_run_len(reader: &mut Reader, remaining: usize) -> Result<usize, SnapshotError> {
+    let corrupt = || SnapshotError::Corrupt;
+    let mut value: u64 = 0;
+    for shift in (0..64).step_by(7) {
+        let byte = reader.u8()?;
+        value |= u64::from(byte & 0x7F) << shift;
+        if byte & 0x80 == 0 {
+            let len = usize::try_from(value).map_err(|_| corrupt())?;
+            return if (1..=remaining).contains(&len) {
+                Ok(len)
+            } else {
+                Err(corrupt())
+            };
+        }
+    }
+    Err(corrupt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boundary::Boundary;
+    use crate::elements::registry::arb_species;
+    use crate::elements::Reaction;
+    use proptest::prelude::*;
+
+    fn settled_world() -> Grid {
+        let mut grid = Grid::with_seed(128, 96, 4);
+        for x in 0..128 {
+            grid.set(x, 95, Cell::wall());
+            for y in 70..95 {
+                grid.set(x, y, Cell::new(Species::Water));
+            }
+        }
+        grid.set(60, 10, Cell::new(Species::Fire));
+        for _ in 0..40 {
+            grid.tick();
+        }
+        grid
+    }
+
+    fn assert_same(a: &Grid, b: &Grid) {
+        assert_eq!((a.width, a.height), (b.width, b.height));
+        assert_eq!(a.cells, b.cells);
+        assert_eq!(a.heat, b.heat);
+        assert_eq!(a.tick_count, b.tick_count);
+        assert_eq!(a.rng, b.rng);
+        assert_eq!(a.next_ghost_group, b.next_ghost_group);
+        assert_eq!(a.boundaries, b.boundaries);
+        assert_eq!(a.reactions, b.reactions);
+        assert_eq!(a.chunks, b.chunks);
+    }
+
+    #[test]
+    fn round_trips_with_and_without_compression() {
+        let mut grid = settled_world();
+        grid.boundaries.left = Boundary::Void;
+        grid.boundaries.bottom = Boundary::Wrap;
+        grid.reactions.insert(
+            Species::Sand,
+            Species::Water,
+            Reaction::new(1, Species::Stone, Species::Water),
+        );
+        for compress in [false, true] {
+            let mut loaded = Grid::from_compact(&grid.to_compact(compress)).unwrap();
+            assert_same(&loaded, &grid);
+
+            let mut original = grid.clone();
+            for _ in 0..20 {
+                original.tick();
+                loaded.tick();
+            }
+            assert_same(&loaded, &original);
+        }
+    }
+
+    #[test]
+    fn is_much_smaller_than_a_snapshot() {
+        let grid = settled_world();
+        let raw = grid.to_snapshot().len();
+        let rle = grid.to_compact(false).len();
+        let deflated = grid.to_compact(true).len();
+        assert!(rle * 4 < raw, "rle {rle} vs raw {raw}");
+        assert!(deflated <= rle, "deflated {deflated} vs rle {rle}");
+    }
+
+    #[test]
+    fn malformed_input_is_rejected() {
+        let bytes = Grid::new(6, 6).to_compact(false);
+        for len in 0..bytes.len() {
+            assert!(Grid::from_compact(&bytes[..len]).is_err());
+        }
+
+        // Overshooting run: one run longer than the whole grid.
+        let mut bad = bytes[..15 + PAYLOAD_HEADER_LEN].to_vec();
+        write_varint(&mut bad, 37);
+        bad.extend_from_slice(&[0, 0, 0, 0]);
+        assert_eq!(
+            Grid::from_compact(&bad).unwrap_err(),
+            SnapshotError::Corrupt
+        );
+
+        let mut bad = bytes.clone();
+        bad[6] = FLAG_DEFLATE;
+        assert_eq!(
+            Grid::from_compact(&bad).unwrap_err(),
+            SnapshotError::Corrupt
+        );
+
+        let mut bad = bytes.clone();
+        bad.push(1);
+        assert_eq!(
+            Grid::from_compact(&bad).unwrap_err(),
+            SnapshotError::TrailingBytes
+        );
+
+        let mut bad = bytes.clone();
+        bad[7..15].copy_from_slice(&[0xFF; 8]);
+        assert_eq!(
+            Grid::from_compact(&bad).unwrap_err(),
+            SnapshotError::TooLarge
+        );
+
+        // Just past the cap is refused before anything is allocated.
+        let mut bad = bytes;
+        bad[7..11].copy_from_slice(&2049u32.to_le_bytes());
+        bad[11..15].copy_from_slice(&2048u32.to_le_bytes());
+        assert_eq!(
+            Grid::from_compact(&bad).unwrap_err(),
+            SnapshotError::TooLarge
+        );
+    }
+
+    #[test]
+    fn varints_round_trip() {
+        for value in [1, 127, 128, 300, 65_535, 1 << 30] {
+            let mut out = Vec::new();
+            write_varint(&mut out, value);
+            let mut reader = Reader { bytes: &out };
+            assert_eq!(read_run_len(&mut reader, usize::MAX).unwrap(), value);
+            assert!(reader.bytes.is_empty());
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn prop_compact_round_trips(
+            cells in proptest::collection::vec(
+                (arb_species(), 0u8..3, 0u8..3, 0u8..3),
+                20 * 15,
+            ),
+            compress in any::<bool>(),
+        ) {
+            let mut grid = Grid::new(20, 15);
+            for (i, (species, ra, rb, clock)) in cells.into_iter().enumerate() {
+                grid.cells[i] = Cell { species, ra, rb, clock };
+            }
+            let loaded = Grid::from_compact(&grid.to_compact(compress)).unwrap();
+            prop_assert_eq!(&loaded.cells, &grid.cells);
+            prop_assert_eq!(&loaded.heat, &grid.heat);
+        }
+
+        #[test]
+        fn prop_random_bytes_never_panic(
+            bytes in proptest::collection::vec(any::<u8>(), 0..256),
+        ) {
+            let _ = Grid::from_compact(&bytes);
+            let mut framed = b"SNDC\x04\x00\x01\x04\x00\x00\x00\x04\x00\x00\x00".to_vec();
+            framed.extend_from_slice(&bytes);
+            let _ = Grid::from_compact(&framed);
+        }
+    }
+}
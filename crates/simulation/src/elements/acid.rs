@@ -0,0 +1,11 @@
+//! Acid element: a liquid slightly heavier than water.
+//!
+//! Dissolving walls is a reaction-table entry, not code here.
+
+use super::movement::step;
+use crate::api::SandApi;
+
+pub fn update_acid(api: &mut SandApi) {
+    let me = api.get(0, 0);
+    step(api, me);
+}
@@ -2,41 +2,69 @@
 //!
 //! `rb` is the lifetime counter (higher = younger fire). The shader maps
 //! `rb` to a color gradient: dark red → red → orange → yellow.
-//! `ra` stores a per-particle random seed for slight flicker.
+//! `ra` stores a per-particle flicker direction (parity).
 //!
 //! Fire strongly prefers rising straight up, with only occasional tiny
 //! lateral flicker — producing a steady, even column from the source.
+//!
+//! Each tick a flame also rolls to ignite every flammable neighbour; the
+//! new flame's lifetime comes from the fuel (`ElementDef::burn_time`).
 
+use super::element;
+use super::movement::can_rise_into;
 use crate::api::SandApi;
 use crate::cell::{Cell, Species};
+use crate::rng::Rng;
 
 /// Default lifetime when fire is placed by the player.
-#[expect(dead_code, reason = "reserved for future use when fire placement uses a standard lifetime")]
+#[expect(
+    dead_code,
+    reason = "reserved for future use when fire placement uses a standard lifetime"
+)]
 pub const DEFAULT_FIRE_LIFETIME: u8 = 120;
 
+/// Lifetime range for freshly placed fire.
+const PLACED_LIFETIME_MIN: u8 = 20;
+const PLACED_LIFETIME_RANGE: u32 = 30;
+
 /// Smoke lifetime range: long enough for the turbulent phase to develop swirls.
 const SMOKE_LIFETIME_MIN: u8 = 80;
-const SMOKE_LIFETIME_RANGE: u8 = 120; // max = MIN + RANGE = 200
+const SMOKE_LIFETIME_RANGE: i32 = 120; // max = MIN + RANGE = 200
 
 /// Fire below this rb has a random chance to convert to smoke each tick,
 /// spreading the fire→smoke transition over several frames instead of
 /// a hard cutoff.
 const FADE_THRESHOLD: u8 = 10;
 
+/// Neighbours a flame can ignite, in roll order.
+const SPREAD_OFFSETS: [(i32, i32); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+/// Fire starts with a randomized lifetime so it doesn't vanish instantly.
+pub fn init_fire(cell: &mut Cell, rng: &mut Rng) {
+    cell.rb = PLACED_LIFETIME_MIN + rng.below(PLACED_LIFETIME_RANGE) as u8;
+    cell.ra = rng.byte();
+}
+
 pub fn update_fire(api: &mut SandApi) {
+    spread(api);
     let me = api.get(0, 0);
 
     // Stochastic early death: when rb is low, each tick has an increasing
-    // chance to convert to smoke. At rb=10 it's ~1-in-5, at rb=1 it's ~1-in-1.
-    let should_die = me.rb == 0
-        || (me.rb <= FADE_THRESHOLD
-            && api.generation.wrapping_add(me.ra).wrapping_mul(3) % me.rb.max(1) == 0);
+    // chance to convert to smoke. At rb=10 it's 1-in-10, at rb=1 it's certain.
+    let should_die = me.rb == 0 || (me.rb <= FADE_THRESHOLD && api.once_in(i32::from(me.rb)));
 
     if should_die {
         let mut smoke = Cell::new(Species::Smoke);
-        smoke.rb = SMOKE_LIFETIME_MIN.wrapping_add(
-            api.generation.wrapping_mul(me.ra) % SMOKE_LIFETIME_RANGE,
-        );
+        smoke.rb = SMOKE_LIFETIME_MIN + api.rand_int(SMOKE_LIFETIME_RANGE) as u8;
         smoke.ra = me.ra;
         api.set(0, 0, smoke);
 
@@ -45,12 +73,8 @@ pub fn update_fire(api: &mut SandApi) {
             let neighbor = api.get(dx, dy);
             if neighbor.species == Species::Empty {
                 let mut extra = Cell::new(Species::Smoke);
-                extra.rb = SMOKE_LIFETIME_MIN.wrapping_add(
-                    api.generation
-                        .wrapping_mul(me.ra.wrapping_add(dx as u8))
-                        % SMOKE_LIFETIME_RANGE,
-                );
-                extra.ra = me.ra.wrapping_add(dx as u8);
+                extra.rb = SMOKE_LIFETIME_MIN + api.rand_int(SMOKE_LIFETIME_RANGE) as u8;
+                extra.ra = api.rand_byte();
                 api.set(dx, dy, extra);
             }
         }
@@ -64,35 +88,38 @@ pub fn update_fire(api: &mut SandApi) {
 
     // Primary: rise straight up — steady candle flame.
     let above = api.get(0, -1);
-    if above.species == Species::Empty {
-        api.set(0, 0, Cell::empty());
+    if can_rise_into(api, Species::Fire, above.species) {
+        api.set(0, 0, above);
         api.set(0, -1, updated);
         return;
     }
 
     // Slight flicker: only ~1-in-6 ticks try a diagonal, keeps the flame tight.
-    let flicker = gen.wrapping_add(me.ra).wrapping_mul(3) % 6 == 0;
-    if flicker {
-        let dx: i32 = if me.ra % 2 == 0 { -1 } else { 1 };
+    if api.once_in(6) {
+        let dx: i32 = if me.ra.is_multiple_of(2) { -1 } else { 1 };
         let diag = api.get(dx, -1);
-        if diag.species == Species::Empty {
-            api.set(0, 0, Cell::empty());
+        if can_rise_into(api, Species::Fire, diag.species) {
+            api.set(0, 0, diag);
             api.set(dx, -1, updated);
             return;
         }
     }
 
     // Blocked straight up and no flicker — try diagonals as fallback.
-    let (dx1, dx2) = if gen % 2 == 0 { (-1, 1) } else { (1, -1) };
+    let (dx1, dx2) = if gen.is_multiple_of(2) {
+        (-1, 1)
+    } else {
+        (1, -1)
+    };
     let diag1 = api.get(dx1, -1);
-    if diag1.species == Species::Empty {
-        api.set(0, 0, Cell::empty());
+    if can_rise_into(api, Species::Fire, diag1.species) {
+        api.set(0, 0, diag1);
         api.set(dx1, -1, updated);
         return;
     }
     let diag2 = api.get(dx2, -1);
-    if diag2.species == Species::Empty {
-        api.set(0, 0, Cell::empty());
+    if can_rise_into(api, Species::Fire, diag2.species) {
+        api.set(0, 0, diag2);
         api.set(dx2, -1, updated);
         return;
     }
@@ -100,3 +127,16 @@ pub fn update_fire(api: &mut SandApi) {
     // Can't move — age in place.
     api.set(0, 0, updated);
 }
+
+/// Roll to set each flammable neighbour alight.
+fn spread(api: &mut SandApi) {
+    for (dx, dy) in SPREAD_OFFSETS {
+        let fuel = element(api.get(dx, dy).species);
+        if fuel.flammability > 0 && api.chance(fuel.flammability) {
+            let mut flame = Cell::new(Species::Fire);
+            flame.rb = fuel.burn_time;
+            flame.ra = api.rand_byte();
+            api.set(dx, dy, flame);
+        }
+    }
+}
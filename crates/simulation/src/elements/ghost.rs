@@ -2,7 +2,7 @@
 //!
 //! Movement is handled as a bulk operation in `Grid::move_ghosts()`,
 //! not per-cell, so the entire shape translates uniformly.
-//! The per-cell `update_ghost` is a no-op.
+//! Ghost has no per-cell update function in the element registry.
 //!
 //! ## Eye system
 //!
@@ -15,6 +15,8 @@
 //! within each group's eye sockets to face the cursor. Without a
 //! cursor, ghosts face their movement direction (or down by default).
 
+/// `rb` value for normal body cells.
+pub const RB_BODY: u8 = 0;
 /// `rb` value for eye-zone cells (potential eye positions, rendered as body).
 pub const RB_EYE_ZONE: u8 = 1;
 /// `rb` value for active eye cells (rendered dark).
@@ -33,23 +35,20 @@ pub const DIRS: [(i32, i32); 8] = [
 ];
 
 /// How many ticks to hold the same direction before picking a new one.
-pub const DIRECTION_HOLD_TICKS: u8 = 10;
+pub const DIRECTION_HOLD_TICKS: u64 = 10;
 
 /// Only move on every Nth tick so the ghost drifts lazily.
-pub const MOVE_DIVISOR: u8 = 8;
+pub const MOVE_DIVISOR: u64 = 8;
 
-/// Derive a direction from the generation counter and group ID.
+/// Derive a direction from the tick counter and group ID.
 /// Different groups get different directions at the same tick.
-pub fn shared_direction(generation: u8, group_id: u8) -> (i32, i32) {
-    let epoch = generation / DIRECTION_HOLD_TICKS;
+pub fn shared_direction(tick: u64, group_id: u8) -> (i32, i32) {
+    let epoch = tick / DIRECTION_HOLD_TICKS;
     // Mix group_id into the hash so each group diverges.
     let h = (epoch as u32)
-        .wrapping_mul(2654435761)
-        .wrapping_add(group_id as u32)
-        .wrapping_mul(2246822519);
+        .wrapping_mul(2_654_435_761)
+        .wrapping_add(u32::from(group_id))
+        .wrapping_mul(2_246_822_519);
     let idx = (h >> 16) as usize % DIRS.len();
     DIRS[idx]
 }
-
-/// Per-cell update is a no-op — bulk movement is in Grid::move_ghosts.
-pub fn update_ghost(_api: &mut crate::api::SandApi) {}
@@ -0,0 +1,10 @@
+//! Gunpowder element: a powder that piles like sand and flashes into
+//! short-lived fire the moment a flame touches it.
+
+use super::movement::step;
+use crate::api::SandApi;
+
+pub fn update_gunpowder(api: &mut SandApi) {
+    let me = api.get(0, 0);
+    step(api, me);
+}
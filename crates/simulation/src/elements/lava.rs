@@ -0,0 +1,24 @@
+//! Lava element: a heavy, sluggish liquid.
+//!
+//! Moves like water but only on some ticks, and sinks slowly through
+//! lighter liquids (see its `displace_chance`).
+
+use super::movement::{has_move, step};
+use crate::api::SandApi;
+
+/// Lava stalls on one tick in this many.
+const SLUGGISHNESS: i32 = 3;
+
+pub fn update_lava(api: &mut SandApi) {
+    let me = api.get(0, 0);
+    // Roll only when there is somewhere to go, so a resting pool draws
+    // nothing and its chunks can sleep.
+    if !has_move(api, me) {
+        return;
+    }
+    if api.once_in(SLUGGISHNESS) || !step(api, me) {
+        // Stalled with a move open: stay scheduled without a write, which
+        // would wake the neighbours too.
+        api.grid.keep_awake(api.x, api.y);
+    }
+}
@@ -1,9 +1,18 @@
 //! Per-element update functions dispatched from the tick loop.
 
+mod acid;
 mod fire;
 pub(crate) mod ghost;
+mod gunpowder;
+mod lava;
+pub mod movement;
+mod oil;
+pub mod reactions;
+pub mod registry;
 mod sand;
 mod smoke;
+mod steam;
+mod stone;
 mod water;
 #[cfg(test)]
 mod water_level_test;
@@ -11,17 +20,23 @@ mod water_level_test;
 use crate::api::SandApi;
 use crate::cell::Species;
 
-/// Dispatch to the appropriate element update function.
+pub use reactions::{Reaction, Reactions};
+pub use registry::{element, spawn_cell, ElementDef, HeatTransition, ELEMENTS};
+
+/// Furthest any element update reads or writes from its own cell, in cells.
 ///
-/// Wall and Empty are no-ops and should be skipped before calling this.
+/// Water's level-swap scan has the longest reach. The parallel tick relies
+/// on this bound to keep concurrently updated tiles apart.
+pub const MAX_REACH: i32 = 21;
+
+/// Run the first matching reaction with a neighbour; if none fires,
+/// dispatch to the element's registered update function, if it has one.
 pub fn update_cell(species: Species, api: &mut SandApi) {
-    match species {
-        Species::Sand => sand::update_sand(api),
-        Species::Water => water::update_water(api),
-        Species::Fire => fire::update_fire(api),
-        Species::Ghost => ghost::update_ghost(api),
-        Species::Smoke => smoke::update_smoke(api),
-        Species::Empty | Species::Wall => {}
+    if reactions::react(api, species) {
+        return;
+    }
+    if let Some(update) = element(species).update {
+        update(api);
     }
 }
 
@@ -38,8 +53,8 @@ mod tests {
     use proptest::prelude::*;
 
     /// Helper: count occurrences of each species in the grid.
-    fn species_counts(grid: &Grid) -> [usize; 7] {
-        let mut counts = [0usize; 7];
+    fn species_counts(grid: &Grid) -> [usize; ELEMENTS.len()] {
+        let mut counts = [0usize; ELEMENTS.len()];
         for cell in &grid.cells {
             counts[cell.species as usize] += 1;
         }
@@ -163,4 +178,76 @@ mod tests {
             prop_assert_eq!(before[Species::Ghost as usize], after[Species::Ghost as usize]);
         }
     }
+
+    #[test]
+    fn flames_ignite_fuel_with_its_burn_time() {
+        let mut grid = Grid::from_ascii(".... / .fp. / ####").unwrap();
+        let mut fire = grid.get(1, 1);
+        fire.rb = 40;
+        grid.set(1, 1, fire);
+        simulate_tick(&mut grid);
+
+        let flame = grid.get(2, 1);
+        assert_eq!(flame.species, Species::Fire);
+        assert_eq!(flame.rb, element(Species::Gunpowder).burn_time);
+    }
+
+    #[test]
+    fn fire_goes_out_next_to_water() {
+        // A flame sealed under a pool: it can neither rise nor escape.
+        let mut grid = Grid::from_ascii("#.# / #~# / #f# / ###").unwrap();
+        let mut fire = grid.get(1, 2);
+        fire.rb = 40;
+        grid.set(1, 2, fire);
+        simulate_tick(&mut grid);
+
+        assert_eq!(species_counts(&grid)[Species::Fire as usize], 0);
+    }
+
+    // Burning only ever consumes fuel: the amount of Wood, Oil and
+    // Gunpowder never grows, and inert material is neither created nor
+    // destroyed by the flames (Sand may only turn to Glass).
+    proptest! {
+        #[test]
+        fn prop_combustion_never_creates_fuel(
+            cells in proptest::collection::vec(
+                prop_oneof![
+                    4 => Just(Species::Empty),
+                    1 => Just(Species::Sand),
+                    1 => Just(Species::Wall),
+                    1 => Just(Species::Fire),
+                    1 => Just(Species::Wood),
+                    1 => Just(Species::Oil),
+                    1 => Just(Species::Gunpowder),
+                ],
+                16 * 16,
+            ),
+            ticks in 1u32..10,
+        ) {
+            let size = 16;
+            let mut grid = Grid::new(size, size);
+            for (i, &sp) in cells.iter().enumerate() {
+                grid.cells[i] = spawn_cell(sp, &mut grid.rng);
+            }
+            let fuel = |counts: &[usize; ELEMENTS.len()]| {
+                counts[Species::Wood as usize]
+                    + counts[Species::Oil as usize]
+                    + counts[Species::Gunpowder as usize]
+            };
+
+            let before = species_counts(&grid);
+            for _ in 0..ticks {
+                let prev = species_counts(&grid);
+                simulate_tick(&mut grid);
+                let after = species_counts(&grid);
+                prop_assert!(fuel(&after) <= fuel(&prev));
+            }
+            let after = species_counts(&grid);
+            prop_assert_eq!(before[Species::Wall as usize], after[Species::Wall as usize]);
+            prop_assert_eq!(
+                before[Species::Sand as usize],
+                after[Species::Sand as usize] + after[Species::Glass as usize]
+            );
+        }
+    }
 }
@@ -0,0 +1,228 @@
+//! Shared movement rules based on element density.
+//!
+//! A cell may always move into Empty. It may also swap places with a
+//! liquid or gas (an element with `MOVE_SIDE` or `MOVE_UP`) when the
+//! densities allow it: heavier cells sink through lighter fluids, and
+//! lighter cells rise through heavier ones. Each swap goes ahead with the
+//! mover's `displace_chance` percent, so slow sinkers can be tuned in the
+//! registry without pairwise code.
+//!
+//! Elements without bespoke movement call [`step`], which applies these
+//! rules in the directions given by the element's `MOVE_*` flags.
+
+use crate::api::SandApi;
+use crate::cell::{Cell, Species};
+
+use super::registry::{
+    element, ElementDef, MOVE_DOWN, MOVE_DOWN_SIDE, MOVE_SIDE, MOVE_UP, MOVE_UP_SIDE,
+};
+
+/// True if the element flows (liquid) or floats (gas).
+#[must_use]
+pub fn is_fluid(def: &ElementDef) -> bool {
+    def.movement & (MOVE_SIDE | MOVE_UP) != 0
+}
+
+/// Can a `me` cell move down into a cell holding `target`?
+pub fn can_sink_into(api: &mut SandApi, me: Species, target: Species) -> bool {
+    target == Species::Empty
+        || (sinks_through(me, target) && api.chance(element(me).displace_chance))
+}
+
+/// Can a `me` cell move up into a cell holding `target`?
+pub fn can_rise_into(api: &mut SandApi, me: Species, target: Species) -> bool {
+    target == Species::Empty
+        || (rises_through(me, target) && api.chance(element(me).displace_chance))
+}
+
+/// Is `target` a fluid light enough for `me` to sink through?
+fn sinks_through(me: Species, target: Species) -> bool {
+    let other = element(target);
+    is_fluid(other) && other.density < element(me).density
+}
+
+/// Is `target` a fluid heavy enough for `me` to rise through?
+fn rises_through(me: Species, target: Species) -> bool {
+    let other = element(target);
+    is_fluid(other) && other.density > element(me).density
+}
+
+/// Does [`step`] have anywhere to try? Ignores `displace_chance`, so it
+/// draws nothing from the RNG.
+#[must_use]
+pub fn has_move(api: &SandApi, me: Cell) -> bool {
+    let movement = element(me.species).movement;
+    let open = |dx: i32, dy: i32| {
+        let target = api.get(dx, dy).species;
+        target == Species::Empty
+            || (dy > 0 && sinks_through(me.species, target))
+            || (dy < 0 && rises_through(me.species, target))
+    };
+    (movement & MOVE_DOWN != 0 && open(0, 1))
+        || (movement & MOVE_DOWN_SIDE != 0 && (open(-1, 1) || open(1, 1)))
+        || (movement & MOVE_UP != 0 && open(0, -1))
+        || (movement & MOVE_UP_SIDE != 0 && (open(-1, -1) || open(1, -1)))
+        || (movement & MOVE_SIDE != 0 && (open(-1, 0) || open(1, 0)))
+}
+
+/// Move `me` one step according to its `MOVE_*` flags.
+///
+/// Tries straight down, then the down diagonals, then straight up, then the
+/// up diagonals, then sideways into Empty, skipping directions the element
+/// lacks. Returns false if the cell could not move; the caller decides
+/// whether to write it back in place.
+pub fn step(api: &mut SandApi, me: Cell) -> bool {
+    let movement = element(me.species).movement;
+    // Alternate diagonal preference each tick to avoid lateral bias.
+    let dir = if api.generation.is_multiple_of(2) {
+        -1
+    } else {
+        1
+    };
+
+    if movement & MOVE_DOWN != 0 && try_move(api, me, 0, 1, can_sink_into) {
+        return true;
+    }
+    if movement & MOVE_DOWN_SIDE != 0
+        && (try_move(api, me, dir, 1, can_sink_into) || try_move(api, me, -dir, 1, can_sink_into))
+    {
+        return true;
+    }
+    if movement & MOVE_UP != 0 && try_move(api, me, 0, -1, can_rise_into) {
+        return true;
+    }
+    if movement & MOVE_UP_SIDE != 0
+        && (try_move(api, me, dir, -1, can_rise_into) || try_move(api, me, -dir, -1, can_rise_into))
+    {
+        return true;
+    }
+    movement & MOVE_SIDE != 0
+        && (try_move(api, me, dir, 0, into_empty) || try_move(api, me, -dir, 0, into_empty))
+}
+
+/// Swap `me` with the cell at `(dx, dy)` if `allowed` says it may move there.
+fn try_move(
+    api: &mut SandApi,
+    me: Cell,
+    dx: i32,
+    dy: i32,
+    allowed: fn(&mut SandApi, Species, Species) -> bool,
+) -> bool {
+    let target = api.get(dx, dy);
+    if !allowed(api, me.species, target.species) {
+        return false;
+    }
+    api.set(0, 0, target);
+    api.set(dx, dy, me);
+    true
+}
+
+fn into_empty(_api: &mut SandApi, _me: Species, target: Species) -> bool {
+    target == Species::Empty
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Grid;
+
+    fn with_api(f: impl FnOnce(&mut SandApi)) {
+        let mut grid = Grid::new(4, 4);
+        let mut api = SandApi::new(&mut grid, 1, 1, 1);
+        f(&mut api);
+    }
+
+    #[test]
+    fn heavier_sinks_only_into_lighter_fluids() {
+        with_api(|api| {
+            assert!(can_sink_into(api, Species::Sand, Species::Empty));
+            assert!(can_sink_into(api, Species::Sand, Species::Water));
+            assert!(can_sink_into(api, Species::Sand, Species::Smoke));
+            assert!(can_sink_into(api, Species::Water, Species::Smoke));
+            assert!(!can_sink_into(api, Species::Water, Species::Sand));
+            assert!(!can_sink_into(api, Species::Sand, Species::Wall));
+            assert!(!can_sink_into(api, Species::Water, Species::Water));
+        });
+    }
+
+    #[test]
+    fn lighter_rises_only_through_heavier_fluids() {
+        with_api(|api| {
+            assert!(can_rise_into(api, Species::Fire, Species::Empty));
+            assert!(can_rise_into(api, Species::Fire, Species::Water));
+            assert!(!can_rise_into(api, Species::Smoke, Species::Sand));
+            assert!(!can_rise_into(api, Species::Smoke, Species::Wall));
+            assert!(!can_rise_into(api, Species::Water, Species::Smoke));
+            // Smoke rolls its displacement chance, so allow a few tries.
+            assert!((0..64).any(|_| can_rise_into(api, Species::Smoke, Species::Water)));
+        });
+    }
+
+    #[test]
+    fn smoke_bubbles_up_through_water() {
+        // One-wide walled tube: water on top, long-lived smoke at the bottom.
+        let mut grid = Grid::new(3, 8);
+        for y in 0..8 {
+            grid.set(0, y, Cell::wall());
+            grid.set(2, y, Cell::wall());
+        }
+        grid.set(1, 7, Cell::wall());
+        for y in 3..6 {
+            let mut water = Cell::new(Species::Water);
+            water.ra = y as u8;
+            grid.set(1, y, water);
+        }
+        let mut smoke = Cell::new(Species::Smoke);
+        smoke.rb = 250;
+        grid.set(1, 6, smoke);
+
+        for _ in 0..40 {
+            grid.tick();
+        }
+        let smoke_y = (0..7).find(|&y| grid.get(1, y).species == Species::Smoke);
+        assert!(smoke_y.is_some_and(|y| y < 3), "smoke stuck at {smoke_y:?}");
+        for y in 4..7 {
+            assert_eq!(grid.get(1, y).species, Species::Water);
+        }
+    }
+
+    #[test]
+    fn unquenched_fire_rises_through_water() {
+        let mut grid = Grid::from_ascii("~ / ~ / ~ / f").unwrap();
+        grid.reactions.remove(Species::Fire, Species::Water);
+        let mut fire = grid.get(0, 3);
+        fire.rb = 40;
+        grid.set(0, 3, fire);
+        for _ in 0..3 {
+            grid.tick();
+        }
+        assert_eq!(grid.get(0, 0).species, Species::Fire);
+    }
+
+    #[test]
+    fn oil_floats_on_water() {
+        // Start upside down: water resting on oil.
+        let mut grid = Grid::new(1, 6);
+        for y in 2..4 {
+            grid.set(0, y, Cell::new(Species::Water));
+        }
+        for y in 4..6 {
+            grid.set(0, y, Cell::new(Species::Oil));
+        }
+        for _ in 0..20 {
+            grid.tick();
+        }
+        let column: Vec<Species> = (0..6).map(|y| grid.get(0, y).species).collect();
+        assert_eq!(
+            column,
+            [
+                Species::Empty,
+                Species::Empty,
+                Species::Oil,
+                Species::Oil,
+                Species::Water,
+                Species::Water
+            ]
+        );
+    }
+}
@@ -0,0 +1,9 @@
+//! Oil element: a light, flammable liquid that floats on water.
+
+use super::movement::step;
+use crate::api::SandApi;
+
+pub fn update_oil(api: &mut SandApi) {
+    let me = api.get(0, 0);
+    step(api, me);
+}
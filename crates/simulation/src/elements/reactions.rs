@@ -0,0 +1,390 @@
+//! Declarative reactions between touching species.
+//!
+//! [`Reactions`] is a `[Species][Species] -> Option<Reaction>` table owned
+//! by the grid (`Grid::reactions`). Before a cell runs its own update, it
+//! checks its four orthogonal neighbours in a fixed order; the first pair
+//! with an entry that passes its chance roll replaces both cells with the
+//! reaction's products and the update is skipped for that tick.
+//!
+//! The table is plain data: entries can be inserted or removed at runtime
+//! without touching element code. Both participants of a pair check the
+//! entry when they update, so a reaction between two updating elements is
+//! rolled up to twice per tick.
+//!
+//! A cell whose roll fails keeps itself scheduled for the next tick, so
+//! settled material next to a partner keeps rolling instead of sleeping.
+//! Code that changes a grid's table must call `Grid::wake_all` for cells
+//! that are already asleep to notice.
+
+use super::{spawn_cell, ELEMENTS};
+use crate::api::SandApi;
+use crate::cell::Species;
+use crate::snapshot::{Reader, SnapshotError};
+
+/// Number of species the table covers.
+const SPECIES_COUNT: usize = ELEMENTS.len();
+
+/// Neighbours checked for reactions, in order: below, above, left, right.
+const NEIGHBOURS: [(i32, i32); 4] = [(0, 1), (0, -1), (-1, 0), (1, 0)];
+
+/// Where a side-effect cell may appear, relative to the reacting cell.
+const SPAWN_OFFSETS: [(i32, i32); 3] = [(0, -1), (-1, -1), (1, -1)];
+
+/// Encoded spawn id of a reaction that spawns nothing.
+const NO_SPAWN: u8 = u8::MAX;
+
+/// Bytes per encoded table entry: both species, chance, both products and
+/// the spawn.
+const ENTRY_LEN: usize = 6;
+
+/// Longest possible encoding of a table: an entry count and every entry.
+pub(crate) const MAX_ENCODED_LEN: usize = 2 + ENTRY_LEN * SPECIES_COUNT * SPECIES_COUNT;
+
+/// Outcome of two species touching.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Reaction {
+    /// Percent chance per check that the reaction happens.
+    pub chance: u8,
+    /// Product replacing the first species of the pair.
+    pub becomes: Species,
+    /// Product replacing the second species of the pair.
+    pub other_becomes: Species,
+    /// Extra cell placed in an empty spot above the first participant.
+    pub spawn: Option<Species>,
+}
+
+impl Reaction {
+    #[must_use]
+    pub const fn new(chance: u8, becomes: Species, other_becomes: Species) -> Self {
+        Self {
+            chance,
+            becomes,
+            other_becomes,
+            spawn: None,
+        }
+    }
+
+    /// Also spawn `species` next to the reaction when it fires.
+    #[must_use]
+    pub const fn spawning(mut self, species: Species) -> Self {
+        self.spawn = Some(species);
+        self
+    }
+
+    /// The same reaction seen from the second participant.
+    const fn mirrored(self) -> Self {
+        Self {
+            becomes: self.other_becomes,
+            other_becomes: self.becomes,
+            ..self
+        }
+    }
+}
+
+/// Lookup table of reactions for every ordered species pair.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Reactions {
+    table: [[Option<Reaction>; SPECIES_COUNT]; SPECIES_COUNT],
+    /// Whether each species appears in any entry, so cells that never
+    /// react skip the neighbour scan.
+    reactive: [bool; SPECIES_COUNT],
+}
+
+impl Reactions {
+    /// A table with no reactions at all.
+    #[must_use]
+    pub fn empty() -> Self {
+        Self {
+            table: [[None; SPECIES_COUNT]; SPECIES_COUNT],
+            reactive: [false; SPECIES_COUNT],
+        }
+    }
+
+    /// Add or replace the reaction between `a` and `b`.
+    ///
+    /// The entry is stored for both orders, so it fires whichever of the
+    /// two cells updates first.
+    pub fn insert(&mut self, a: Species, b: Species, reaction: Reaction) {
+        self.table[a as usize][b as usize] = Some(reaction);
+        self.table[b as usize][a as usize] = Some(reaction.mirrored());
+        self.reactive[a as usize] = true;
+        self.reactive[b as usize] = true;
+    }
+
+    /// Remove the reaction between `a` and `b`, if any.
+    pub fn remove(&mut self, a: Species, b: Species) {
+        self.table[a as usize][b as usize] = None;
+        self.table[b as usize][a as usize] = None;
+        for species in [a, b] {
+            self.reactive[species as usize] =
+                self.table[species as usize].iter().any(Option::is_some);
+        }
+    }
+
+    /// The reaction when `a` touches `b`, with `a`'s product in `becomes`.
+    #[must_use]
+    pub fn get(&self, a: Species, b: Species) -> Option<Reaction> {
+        self.table[a as usize][b as usize]
+    }
+
+    /// True if `species` takes part in any reaction.
+    #[must_use]
+    pub fn is_reactive(&self, species: Species) -> bool {
+        self.reactive[species as usize]
+    }
+
+    /// Append the table to a snapshot: a `u16` entry count, then
+    /// `a, b, chance, becomes, other_becomes, spawn` per ordered pair, with
+    /// `spawn` 255 for none.
+    pub(crate) fn encode(&self, out: &mut Vec<u8>) {
+        let entries: Vec<[u8; ENTRY_LEN]> = self
+            .table
+            .iter()
+            .enumerate()
+            .flat_map(|(a, row)| {
+                row.iter().enumerate().filter_map(move |(b, entry)| {
+                    let r = (*entry)?;
+                    let spawn = r.spawn.map_or(NO_SPAWN, |s| s as u8);
+                    Some([
+                        a as u8,
+                        b as u8,
+                        r.chance,
+                        r.becomes as u8,
+                        r.other_becomes as u8,
+                        spawn,
+                    ])
+                })
+            })
+            .collect();
+        out.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        for entry in entries {
+            out.extend_from_slice(&entry);
+        }
+    }
+
+    /// Read a table written by `encode`. Each ordered pair is restored
+    /// exactly as stored, without mirroring.
+    pub(crate) fn decode(reader: &mut Reader) -> Result<Self, SnapshotError> {
+        let species = |id: u8| Species::from_id(id).ok_or(SnapshotError::Corrupt);
+        let mut reactions = Self::empty();
+        for _ in 0..reader.u16()? {
+            let raw = reader.take(ENTRY_LEN)?;
+            let (a, b) = (species(raw[0])?, species(raw[1])?);
+            let mut reaction = Reaction::new(raw[2], species(raw[3])?, species(raw[4])?);
+            if raw[5] != NO_SPAWN {
+                reaction = reaction.spawning(species(raw[5])?);
+            }
+            reactions.table[a as usize][b as usize] = Some(reaction);
+            reactions.reactive[a as usize] = true;
+        }
+        Ok(reactions)
+    }
+}
+
+impl Default for Reactions {
+    /// The built-in reactions.
+    fn default() -> Self {
+        let mut reactions = Self::empty();
+        reactions.insert(
+            Species::Fire,
+            Species::Water,
+            Reaction::new(100, Species::Steam, Species::Empty),
+        );
+        reactions.insert(
+            Species::Lava,
+            Species::Water,
+            Reaction::new(100, Species::Stone, Species::Steam),
+        );
+        reactions.insert(
+            Species::Acid,
+            Species::Wall,
+            Reaction::new(5, Species::Empty, Species::Empty).spawning(Species::Smoke),
+        );
+        reactions
+    }
+}
+
+/// Fire the first reaction between the cell at the API position and a
+/// touching neighbour. Returns true if one happened.
+pub fn react(api: &mut SandApi, species: Species) -> bool {
+    if !api.grid.reactions.is_reactive(species) {
+        return false;
+    }
+    let mut pending = false;
+    for (dx, dy) in NEIGHBOURS {
+        // Past a Wall or Void edge there is no cell to react with.
+        if !api.grid.is_reachable(api.x + dx, api.y + dy) {
+            continue;
+        }
+        let other = api.get(dx, dy).species;
+        let Some(reaction) = api.grid.reactions.get(species, other) else {
+            continue;
+        };
+        if !api.chance(reaction.chance) {
+            pending |= reaction.chance > 0;
+            continue;
+        }
+
+        let me = spawn_cell(reaction.becomes, &mut api.grid.rng);
+        let them = spawn_cell(reaction.other_becomes, &mut api.grid.rng);
+        api.set(0, 0, me);
+        api.set(dx, dy, them);
+        if let Some(extra) = reaction.spawn {
+            spawn_nearby(api, extra);
+        }
+        return true;
+    }
+    if pending {
+        api.grid.keep_awake(api.x, api.y);
+    }
+    false
+}
+
+/// Place a fresh `species` cell in the first empty spot above the reaction.
+fn spawn_nearby(api: &mut SandApi, species: Species) {
+    for (dx, dy) in SPAWN_OFFSETS {
+        if api.grid.is_reachable(api.x + dx, api.y + dy)
+            && api.get(dx, dy).species == Species::Empty
+        {
+            let cell = spawn_cell(species, &mut api.grid.rng);
+            api.set(dx, dy, cell);
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cell::Cell;
+    use crate::Grid;
+
+    fn count(grid: &Grid, species: Species) -> usize {
+        grid.cells.iter().filter(|c| c.species == species).count()
+    }
+
+    #[test]
+    fn insert_covers_both_orders() {
+        let mut reactions = Reactions::empty();
+        reactions.insert(
+            Species::Sand,
+            Species::Water,
+            Reaction::new(50, Species::Stone, Species::Empty),
+        );
+
+        let forward = reactions.get(Species::Sand, Species::Water).unwrap();
+        let backward = reactions.get(Species::Water, Species::Sand).unwrap();
+        assert_eq!(
+            (forward.becomes, forward.other_becomes),
+            (Species::Stone, Species::Empty)
+        );
+        assert_eq!(
+            (backward.becomes, backward.other_becomes),
+            (Species::Empty, Species::Stone)
+        );
+        assert!(reactions.is_reactive(Species::Sand));
+
+        reactions.remove(Species::Water, Species::Sand);
+        assert_eq!(reactions.get(Species::Sand, Species::Water), None);
+        assert!(!reactions.is_reactive(Species::Sand));
+    }
+
+    #[test]
+    fn fire_touching_water_becomes_steam() {
+        let mut grid = Grid::new(5, 5);
+        let mut fire = Cell::new(Species::Fire);
+        fire.rb = 40;
+        grid.set(2, 2, fire);
+        grid.set(2, 3, Cell::new(Species::Water));
+        grid.tick();
+
+        assert_eq!(count(&grid, Species::Fire), 0);
+        assert_eq!(count(&grid, Species::Water), 0);
+        assert_eq!(count(&grid, Species::Steam), 1);
+    }
+
+    #[test]
+    fn lava_touching_water_becomes_stone_and_steam() {
+        let mut grid = Grid::new(5, 5);
+        grid.set(2, 3, Cell::new(Species::Lava));
+        grid.set(3, 3, Cell::new(Species::Water));
+        grid.tick();
+
+        assert_eq!(count(&grid, Species::Stone), 1);
+        assert_eq!(count(&grid, Species::Steam), 1);
+        assert_eq!(count(&grid, Species::Lava), 0);
+    }
+
+    #[test]
+    fn acid_dissolves_walls_but_not_the_grid_edge() {
+        // One-wide shaft: acid resting on a wall, every other side is edge.
+        let mut grid = Grid::new(1, 2);
+        grid.set(0, 0, Cell::new(Species::Acid));
+        grid.set(0, 1, Cell::wall());
+        let mut alone = Grid::new(1, 1);
+        alone.set(0, 0, Cell::new(Species::Acid));
+
+        for _ in 0..500 {
+            grid.tick();
+            alone.tick();
+        }
+        assert_eq!(count(&grid, Species::Wall), 0);
+        assert_eq!(alone.get(0, 0).species, Species::Acid);
+    }
+
+    #[test]
+    fn unlikely_reactions_fire_between_settled_cells() {
+        let mut grid = Grid::from_ascii("..... / .sss. / #####").unwrap();
+        grid.reactions = Reactions::empty();
+        grid.reactions.insert(
+            Species::Sand,
+            Species::Wall,
+            Reaction::new(1, Species::Stone, Species::Wall),
+        );
+        for _ in 0..3000 {
+            grid.tick();
+        }
+        assert_eq!(count(&grid, Species::Sand), 0);
+    }
+
+    #[test]
+    fn reactions_added_to_a_settled_world_fire() {
+        let mut universe = crate::Universe::new(8, 8);
+        for x in 0..8 {
+            universe.set_cell(x, 0, Species::Sand as u8);
+        }
+        for _ in 0..50 {
+            universe.tick();
+        }
+        assert_eq!(universe.grid.active_chunk_count(), 0);
+
+        assert!(universe.set_reaction(
+            Species::Sand as u8,
+            Species::Sand as u8,
+            100,
+            Species::Stone as u8,
+            Species::Stone as u8,
+            0,
+        ));
+        universe.tick();
+        assert_eq!(count(&universe.grid, Species::Sand), 0);
+    }
+
+    #[test]
+    fn runtime_entries_change_behaviour() {
+        let mut grid = Grid::new(3, 3);
+        grid.reactions = Reactions::empty();
+        grid.reactions.insert(
+            Species::Sand,
+            Species::Water,
+            Reaction::new(100, Species::Stone, Species::Empty).spawning(Species::Steam),
+        );
+        grid.set(1, 1, Cell::new(Species::Water));
+        grid.set(1, 2, Cell::new(Species::Sand));
+        grid.tick();
+
+        assert_eq!(grid.get(1, 2).species, Species::Stone);
+        assert_eq!(count(&grid, Species::Water), 0);
+        assert_eq!(count(&grid, Species::Steam), 1);
+    }
+}
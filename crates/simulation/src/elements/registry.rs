@@ -0,0 +1,399 @@
+//! Data-driven element table.
+//!
+//! Every species has one [`ElementDef`] in [`ELEMENTS`], indexed by its
+//! `Species` discriminant. Dispatch in the tick loop, id→`Species`
+//! conversion, `Display`, and cell initialization in `Universe::set_cell`
+//! all read this table, so adding an element means adding a `Species`
+//! variant and one entry here.
+
+use crate::api::SandApi;
+use crate::cell::{Cell, Species};
+use crate::rng::Rng;
+
+use super::{acid, fire, gunpowder, lava, oil, sand, smoke, steam, stone, water};
+
+/// Falls straight down.
+pub const MOVE_DOWN: u8 = 1 << 0;
+/// Slides diagonally downward.
+pub const MOVE_DOWN_SIDE: u8 = 1 << 1;
+/// Spreads horizontally.
+pub const MOVE_SIDE: u8 = 1 << 2;
+/// Rises straight up.
+pub const MOVE_UP: u8 = 1 << 3;
+/// Rises diagonally.
+pub const MOVE_UP_SIDE: u8 = 1 << 4;
+
+/// A species change triggered by temperature.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct HeatTransition {
+    /// Heat at or above which the change happens.
+    pub threshold: u8,
+    pub into: Species,
+}
+
+/// Static properties and behaviour of one species.
+#[derive(Debug)]
+pub struct ElementDef {
+    pub species: Species,
+    /// Human-readable name, also used by `Display`.
+    pub name: &'static str,
+    /// Relative weight; heavier cells sink through lighter ones.
+    pub density: u8,
+    /// `MOVE_*` bit flags describing how the element moves.
+    pub movement: u8,
+    /// Percent chance per attempt to swap through a fluid of different density.
+    pub displace_chance: u8,
+    /// How fast heat spreads into this cell, out of 256 per tick.
+    pub conductivity: u8,
+    /// Heat the cell holds its own spot at, or 0 if it emits none.
+    pub heat_output: u8,
+    /// Species change once the cell's heat reaches a threshold.
+    pub heat_transition: Option<HeatTransition>,
+    /// Percent chance per tick that a touching flame ignites this cell.
+    pub flammability: u8,
+    /// Lifetime (`rb`) of the flame this cell becomes when it ignites.
+    pub burn_time: u8,
+    /// Representative sRGB colour (the shader adds per-cell variation).
+    pub color: [u8; 3],
+    /// Character for this species in the ASCII grid format (`Grid::from_ascii`).
+    pub glyph: char,
+    /// Seeds `ra`/`rb` when a cell of this species is placed.
+    pub init: fn(&mut Cell, &mut Rng),
+    /// Per-tick behaviour, or `None` for elements that never update.
+    pub update: Option<fn(&mut SandApi)>,
+}
+
+impl ElementDef {
+    /// Numeric id, equal to the `Species` discriminant.
+    #[must_use]
+    pub fn id(&self) -> u8 {
+        self.species as u8
+    }
+}
+
+/// Initializer for elements with no per-cell state.
+fn init_none(_cell: &mut Cell, _rng: &mut Rng) {}
+
+/// Every element, indexed by `Species as usize`.
+pub static ELEMENTS: [ElementDef; 16] = [
+    ElementDef {
+        species: Species::Empty,
+        name: "Empty",
+        density: 0,
+        movement: 0,
+        displace_chance: 0,
+        conductivity: 64,
+        heat_output: 0,
+        heat_transition: None,
+        flammability: 0,
+        burn_time: 0,
+        color: [26, 26, 31],
+        glyph: '.',
+        init: init_none,
+        update: None,
+    },
+    ElementDef {
+        species: Species::Sand,
+        name: "Sand",
+        density: 150,
+        movement: MOVE_DOWN | MOVE_DOWN_SIDE,
+        displace_chance: 100,
+        conductivity: 40,
+        heat_output: 0,
+        heat_transition: Some(HeatTransition {
+            threshold: 180,
+            into: Species::Glass,
+        }),
+        flammability: 0,
+        burn_time: 0,
+        color: [225, 169, 95],
+        glyph: 's',
+        init: sand::init_sand,
+        update: Some(sand::update_sand),
+    },
+    ElementDef {
+        species: Species::Water,
+        name: "Water",
+        density: 100,
+        movement: MOVE_DOWN | MOVE_DOWN_SIDE | MOVE_SIDE,
+        displace_chance: 100,
+        conductivity: 128,
+        heat_output: 0,
+        heat_transition: Some(HeatTransition {
+            threshold: 100,
+            into: Species::Steam,
+        }),
+        flammability: 0,
+        burn_time: 0,
+        color: [51, 102, 204],
+        glyph: '~',
+        init: water::init_water,
+        update: Some(water::update_water),
+    },
+    ElementDef {
+        species: Species::Wall,
+        name: "Wall",
+        density: 255,
+        movement: 0,
+        displace_chance: 0,
+        conductivity: 16,
+        heat_output: 0,
+        heat_transition: None,
+        flammability: 0,
+        burn_time: 0,
+        color: [128, 128, 128],
+        glyph: '#',
+        init: init_none,
+        update: None,
+    },
+    ElementDef {
+        species: Species::Fire,
+        name: "Fire",
+        density: 5,
+        movement: MOVE_UP | MOVE_UP_SIDE,
+        // Water quenches flames on contact, but other heavier fluids (or
+        // water with that reaction removed) let them rise straight through.
+        displace_chance: 100,
+        conductivity: 200,
+        heat_output: 200,
+        heat_transition: None,
+        flammability: 0,
+        burn_time: 0,
+        color: [255, 217, 26],
+        glyph: 'f',
+        init: fire::init_fire,
+        update: Some(fire::update_fire),
+    },
+    ElementDef {
+        // Ghosts move as whole groups in `Grid::move_ghosts`.
+        species: Species::Ghost,
+        name: "Ghost",
+        density: 255,
+        movement: 0,
+        displace_chance: 0,
+        conductivity: 0,
+        heat_output: 0,
+        heat_transition: None,
+        flammability: 0,
+        burn_time: 0,
+        color: [242, 242, 247],
+        glyph: 'g',
+        init: init_none,
+        update: None,
+    },
+    ElementDef {
+        species: Species::Smoke,
+        name: "Smoke",
+        density: 3,
+        movement: MOVE_UP | MOVE_UP_SIDE | MOVE_SIDE,
+        displace_chance: 50,
+        conductivity: 64,
+        heat_output: 0,
+        heat_transition: None,
+        flammability: 0,
+        burn_time: 0,
+        color: [89, 89, 96],
+        glyph: 'm',
+        init: smoke::init_smoke,
+        update: Some(smoke::update_smoke),
+    },
+    ElementDef {
+        species: Species::Steam,
+        name: "Steam",
+        density: 2,
+        movement: MOVE_UP | MOVE_UP_SIDE | MOVE_SIDE,
+        displace_chance: 50,
+        conductivity: 64,
+        heat_output: 0,
+        heat_transition: None,
+        flammability: 0,
+        burn_time: 0,
+        color: [204, 214, 230],
+        glyph: 'v',
+        init: steam::init_steam,
+        update: Some(steam::update_steam),
+    },
+    ElementDef {
+        species: Species::Lava,
+        name: "Lava",
+        density: 200,
+        movement: MOVE_DOWN | MOVE_DOWN_SIDE | MOVE_SIDE,
+        displace_chance: 30,
+        conductivity: 100,
+        heat_output: 240,
+        heat_transition: None,
+        flammability: 0,
+        burn_time: 0,
+        color: [230, 77, 20],
+        glyph: 'l',
+        init: init_none,
+        update: Some(lava::update_lava),
+    },
+    ElementDef {
+        species: Species::Stone,
+        name: "Stone",
+        density: 220,
+        movement: MOVE_DOWN,
+        displace_chance: 100,
+        conductivity: 60,
+        heat_output: 0,
+        heat_transition: None,
+        flammability: 0,
+        burn_time: 0,
+        color: [102, 97, 92],
+        glyph: '@',
+        init: init_none,
+        update: Some(stone::update_stone),
+    },
+    ElementDef {
+        species: Species::Acid,
+        name: "Acid",
+        density: 105,
+        movement: MOVE_DOWN | MOVE_DOWN_SIDE | MOVE_SIDE,
+        displace_chance: 100,
+        conductivity: 128,
+        heat_output: 0,
+        heat_transition: None,
+        flammability: 0,
+        burn_time: 0,
+        color: [122, 230, 61],
+        glyph: 'a',
+        init: init_none,
+        update: Some(acid::update_acid),
+    },
+    ElementDef {
+        species: Species::Glass,
+        name: "Glass",
+        density: 255,
+        movement: 0,
+        displace_chance: 0,
+        conductivity: 80,
+        heat_output: 0,
+        heat_transition: None,
+        flammability: 0,
+        burn_time: 0,
+        color: [179, 217, 224],
+        glyph: '=',
+        init: init_none,
+        update: None,
+    },
+    ElementDef {
+        species: Species::Ice,
+        name: "Ice",
+        density: 90,
+        movement: 0,
+        displace_chance: 0,
+        conductivity: 40,
+        heat_output: 0,
+        heat_transition: Some(HeatTransition {
+            threshold: 40,
+            into: Species::Water,
+        }),
+        flammability: 0,
+        burn_time: 0,
+        color: [173, 224, 240],
+        glyph: 'i',
+        init: init_none,
+        update: None,
+    },
+    ElementDef {
+        species: Species::Wood,
+        name: "Wood",
+        density: 255,
+        movement: 0,
+        displace_chance: 0,
+        conductivity: 30,
+        heat_output: 0,
+        heat_transition: Some(HeatTransition {
+            threshold: 200,
+            into: Species::Fire,
+        }),
+        flammability: 5,
+        burn_time: 150,
+        color: [122, 82, 41],
+        glyph: 'w',
+        init: init_none,
+        update: None,
+    },
+    ElementDef {
+        species: Species::Oil,
+        name: "Oil",
+        density: 80,
+        movement: MOVE_DOWN | MOVE_DOWN_SIDE | MOVE_SIDE,
+        displace_chance: 100,
+        conductivity: 100,
+        heat_output: 0,
+        heat_transition: Some(HeatTransition {
+            threshold: 150,
+            into: Species::Fire,
+        }),
+        flammability: 60,
+        burn_time: 60,
+        color: [77, 61, 38],
+        glyph: 'o',
+        init: init_none,
+        update: Some(oil::update_oil),
+    },
+    ElementDef {
+        species: Species::Gunpowder,
+        name: "Gunpowder",
+        density: 140,
+        movement: MOVE_DOWN | MOVE_DOWN_SIDE,
+        displace_chance: 100,
+        conductivity: 60,
+        heat_output: 0,
+        heat_transition: Some(HeatTransition {
+            threshold: 120,
+            into: Species::Fire,
+        }),
+        flammability: 100,
+        burn_time: 8,
+        color: [89, 84, 82],
+        glyph: 'p',
+        init: init_none,
+        update: Some(gunpowder::update_gunpowder),
+    },
+];
+
+/// Look up the definition for `species`.
+#[must_use]
+pub fn element(species: Species) -> &'static ElementDef {
+    &ELEMENTS[species as usize]
+}
+
+/// Build a freshly placed cell of `species` with its registers initialized.
+#[must_use]
+pub fn spawn_cell(species: Species, rng: &mut Rng) -> Cell {
+    let mut cell = Cell::new(species);
+    (element(species).init)(&mut cell, rng);
+    cell
+}
+
+/// Strategy over every registered species, for property tests.
+#[cfg(test)]
+pub(crate) fn arb_species() -> impl proptest::strategy::Strategy<Value = Species> {
+    proptest::sample::select(ELEMENTS.iter().map(|def| def.species).collect::<Vec<_>>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn table_is_indexed_by_id() {
+        for (i, def) in ELEMENTS.iter().enumerate() {
+            assert_eq!(usize::from(def.id()), i, "{} is out of place", def.name);
+            assert_eq!(Species::from_id(def.id()), Some(def.species));
+        }
+        assert_eq!(Species::from_id(ELEMENTS.len() as u8), None);
+    }
+
+    #[test]
+    fn spawn_cell_runs_initializer() {
+        let mut rng = Rng::new(3);
+        let fire = spawn_cell(Species::Fire, &mut rng);
+        assert!(fire.rb >= 20);
+        let wall = spawn_cell(Species::Wall, &mut rng);
+        assert_eq!(wall, Cell::wall());
+    }
+}
@@ -1,20 +1,21 @@
-//! Sand element: falls down, then diagonally; displaces Water by swapping.
+//! Sand element: falls down, then diagonally; sinks through lighter fluids by swapping.
 
+use super::movement::can_sink_into;
 use crate::api::SandApi;
-use crate::cell::Species;
+use crate::cell::{Cell, Species};
+use crate::rng::Rng;
+
+/// Sand uses rb for per-grain color variation (0–255).
+pub fn init_sand(cell: &mut Cell, rng: &mut Rng) {
+    cell.rb = rng.byte();
+}
 
 pub fn update_sand(api: &mut SandApi) {
+    let me = api.get(0, 0);
+
+    // Fall straight down, swapping with any lighter fluid below.
     let below = api.get(0, 1);
-    if below.species == Species::Empty {
-        // Fall straight down
-        let me = api.get(0, 0);
-        api.set(0, 0, below);
-        api.set(0, 1, me);
-        return;
-    }
-    if below.species == Species::Water {
-        // Displace water by swapping (sand is denser)
-        let me = api.get(0, 0);
+    if can_sink_into(api, Species::Sand, below.species) {
         api.set(0, 0, below);
         api.set(0, 1, me);
         return;
@@ -22,19 +23,21 @@ pub fn update_sand(api: &mut SandApi) {
 
     // Try diagonal, alternating direction to reduce lateral bias
     let gen = api.generation;
-    let (dx1, dx2) = if gen.is_multiple_of(2) { (-1, 1) } else { (1, -1) };
+    let (dx1, dx2) = if gen.is_multiple_of(2) {
+        (-1, 1)
+    } else {
+        (1, -1)
+    };
 
     let diag1 = api.get(dx1, 1);
-    if diag1.species == Species::Empty || diag1.species == Species::Water {
-        let me = api.get(0, 0);
+    if can_sink_into(api, Species::Sand, diag1.species) {
         api.set(0, 0, diag1);
         api.set(dx1, 1, me);
         return;
     }
 
     let diag2 = api.get(dx2, 1);
-    if diag2.species == Species::Empty || diag2.species == Species::Water {
-        let me = api.get(0, 0);
+    if can_sink_into(api, Species::Sand, diag2.species) {
         api.set(0, 0, diag2);
         api.set(dx2, 1, me);
     }
@@ -5,12 +5,24 @@
 //! **Turbulent phase** (older, low `rb`): stalls heavily, drifts
 //! randomly, and spreads out into a diffuse cloud.
 
+use super::movement::can_rise_into;
 use crate::api::SandApi;
 use crate::cell::{Cell, Species};
+use crate::rng::Rng;
 
 /// `rb` below this → turbulent dispersal phase.
 const TURBULENT_THRESHOLD: u8 = 120;
 
+/// Lifetime range for freshly placed smoke.
+const PLACED_LIFETIME_MIN: u8 = 80;
+const PLACED_LIFETIME_RANGE: u32 = 120;
+
+/// Smoke starts with a lifetime for fade-out.
+pub fn init_smoke(cell: &mut Cell, rng: &mut Rng) {
+    cell.rb = PLACED_LIFETIME_MIN + rng.below(PLACED_LIFETIME_RANGE) as u8;
+    cell.ra = rng.byte();
+}
+
 pub fn update_smoke(api: &mut SandApi) {
     let me = api.get(0, 0);
 
@@ -22,25 +34,25 @@ pub fn update_smoke(api: &mut SandApi) {
     let mut updated = me;
     updated.rb = me.rb.saturating_sub(1);
 
-    let gen = api.generation;
     let laminar = me.rb > TURBULENT_THRESHOLD;
 
     // ── Laminar: fast, straight rise ──
     if laminar {
-        if gen.wrapping_add(me.ra).wrapping_mul(7) % 10 < 1 {
+        if api.once_in(10) {
             api.set(0, 0, updated);
             return;
         }
         let above = api.get(0, -1);
-        if above.species == Species::Empty {
-            api.set(0, 0, Cell::empty());
+        if can_rise_into(api, Species::Smoke, above.species) {
+            api.set(0, 0, above);
             api.set(0, -1, updated);
             return;
         }
-        let dir: i32 = if me.ra % 2 == 0 { -1 } else { 1 };
+        let dir: i32 = if me.ra.is_multiple_of(2) { -1 } else { 1 };
         for &d in &[dir, -dir] {
-            if api.get(d, -1).species == Species::Empty {
-                api.set(0, 0, Cell::empty());
+            let diag = api.get(d, -1);
+            if can_rise_into(api, Species::Smoke, diag.species) {
+                api.set(0, 0, diag);
                 api.set(d, -1, updated);
                 return;
             }
@@ -52,38 +64,40 @@ pub fn update_smoke(api: &mut SandApi) {
     // ── Turbulent: stall + random drift ──
 
     // Stall ~60%.
-    let stall_roll = gen.wrapping_add(me.ra).wrapping_mul(7) % 10;
-    if stall_roll < 6 {
+    if api.rand_int(10) < 6 {
         // Re-randomize drift while stalling.
-        if gen.wrapping_add(me.ra) % 5 == 0 {
-            updated.ra = updated.ra.wrapping_add(gen);
+        if api.once_in(5) {
+            updated.ra = api.rand_byte();
         }
         api.set(0, 0, updated);
         return;
     }
 
-    let dir: i32 = if me.ra % 2 == 0 { -1 } else { 1 };
+    let dir: i32 = if me.ra.is_multiple_of(2) { -1 } else { 1 };
 
     // ~1-in-3 ticks: drift diagonally.
-    if me.ra.wrapping_add(gen) % 3 == 0 {
-        if api.get(dir, -1).species == Species::Empty {
-            api.set(0, 0, Cell::empty());
+    if api.once_in(3) {
+        let diag = api.get(dir, -1);
+        if can_rise_into(api, Species::Smoke, diag.species) {
+            api.set(0, 0, diag);
             api.set(dir, -1, updated);
             return;
         }
     }
 
     // Straight up.
-    if api.get(0, -1).species == Species::Empty {
-        api.set(0, 0, Cell::empty());
+    let above = api.get(0, -1);
+    if can_rise_into(api, Species::Smoke, above.species) {
+        api.set(0, 0, above);
         api.set(0, -1, updated);
         return;
     }
 
     // Fallback diagonals.
     for &d in &[dir, -dir] {
-        if api.get(d, -1).species == Species::Empty {
-            api.set(0, 0, Cell::empty());
+        let diag = api.get(d, -1);
+        if can_rise_into(api, Species::Smoke, diag.species) {
+            api.set(0, 0, diag);
             api.set(d, -1, updated);
             return;
         }
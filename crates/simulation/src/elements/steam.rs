@@ -0,0 +1,36 @@
+//! Steam element: a light gas that rises and condenses back into water.
+//!
+//! `rb` is the remaining lifetime; when it runs out the steam turns into
+//! a fresh water cell.
+
+use super::movement::step;
+use super::spawn_cell;
+use crate::api::SandApi;
+use crate::cell::{Cell, Species};
+use crate::rng::Rng;
+
+/// Lifetime range for freshly created steam.
+const LIFETIME_MIN: u8 = 60;
+const LIFETIME_RANGE: u32 = 60;
+
+pub fn init_steam(cell: &mut Cell, rng: &mut Rng) {
+    cell.rb = LIFETIME_MIN + rng.below(LIFETIME_RANGE) as u8;
+    cell.ra = rng.byte();
+}
+
+pub fn update_steam(api: &mut SandApi) {
+    let me = api.get(0, 0);
+    if me.rb == 0 {
+        let water = spawn_cell(Species::Water, &mut api.grid.rng);
+        api.set(0, 0, water);
+        return;
+    }
+
+    let mut updated = me;
+    updated.rb -= 1;
+
+    // Billow: stall now and then so plumes spread instead of forming columns.
+    if api.once_in(4) || !step(api, updated) {
+        api.set(0, 0, updated);
+    }
+}
@@ -0,0 +1,9 @@
+//! Stone element: a heavy solid that drops straight down and never slides.
+
+use super::movement::step;
+use crate::api::SandApi;
+
+pub fn update_stone(api: &mut SandApi) {
+    let me = api.get(0, 0);
+    step(api, me);
+}
@@ -12,9 +12,15 @@
 //! (one with empty above), then swaps with it. The scan follows the
 //! water body's contour around obstacles (stepping down/up when it
 //! hits non-water cells), enabling leveling across sloped terrain.
+//!
+//! A fully blocked cell flips its direction once and marks itself
+//! `BLOCKED`; while the mark is set it is left untouched, so a settled
+//! pool stops writing and its chunks can sleep. Moving clears the mark.
 
+use super::movement::can_sink_into;
 use crate::api::SandApi;
 use crate::cell::{Cell, Species};
+use crate::rng::Rng;
 
 /// How far water ray-casts horizontally for empty cells each tick.
 const HORIZONTAL_RANGE: i32 = 5;
@@ -22,19 +28,36 @@ const HORIZONTAL_RANGE: i32 = 5;
 /// How far the level swap scans through water to find a surface cell.
 const LEVEL_SCAN_RANGE: i32 = 20;
 
+// The scan also peeks one row above or below its current position.
+const _: () = assert!(LEVEL_SCAN_RANGE < super::MAX_REACH);
+
 /// One-in-N chance of re-randomizing flow direction during freefall.
-const FREEFALL_RERANDOMIZE_CHANCE: u8 = 20;
+const FREEFALL_RERANDOMIZE_CHANCE: i32 = 20;
+
+/// `ra` bit set once a fully blocked cell has flipped its direction.
+const BLOCKED: u8 = 0b10;
+
+/// Water uses ra parity as persistent flow direction. Randomize it so
+/// adjacent particles start with varied directions.
+pub fn init_water(cell: &mut Cell, rng: &mut Rng) {
+    cell.ra = rng.byte() & !BLOCKED;
+}
 
 pub fn update_water(api: &mut SandApi) {
-    let me = api.get(0, 0);
-    let dir: i32 = if me.ra % 2 == 0 { -1 } else { 1 };
+    let here = api.get(0, 0);
+    // Every move below writes `me`, so moving clears the mark.
+    let me = Cell {
+        ra: here.ra & !BLOCKED,
+        ..here
+    };
+    let dir: i32 = if me.ra.is_multiple_of(2) { -1 } else { 1 };
 
-    // Phase 1: Gravity — fall straight down.
+    // Phase 1: Gravity — fall straight down, sinking through lighter fluids.
     let below = api.get(0, 1);
-    if below.species == Species::Empty {
+    if can_sink_into(api, Species::Water, below.species) {
         let mut falling = me;
-        if api.generation % FREEFALL_RERANDOMIZE_CHANCE == 0 {
-            falling.ra = api.generation;
+        if api.once_in(FREEFALL_RERANDOMIZE_CHANCE) {
+            falling.ra = api.rand_byte() & !BLOCKED;
         }
         api.set(0, 0, below);
         api.set(0, 1, falling);
@@ -43,23 +66,23 @@ pub fn update_water(api: &mut SandApi) {
 
     // Phase 2: Diagonal fall — per-particle direction for varied flow.
     let diag1 = api.get(dir, 1);
-    if diag1.species == Species::Empty {
+    if can_sink_into(api, Species::Water, diag1.species) {
         api.set(0, 0, diag1);
         api.set(dir, 1, me);
         return;
     }
     let diag2 = api.get(-dir, 1);
-    if diag2.species == Species::Empty {
+    if can_sink_into(api, Species::Water, diag2.species) {
         api.set(0, 0, diag2);
         api.set(-dir, 1, me);
         return;
     }
 
     // Phase 3: Horizontal ray-cast spread — per-particle direction.
-    if try_horizontal(api, &me, dir) {
+    if try_horizontal(api, me, dir) {
         return;
     }
-    if try_horizontal(api, &me, -dir) {
+    if try_horizontal(api, me, -dir) {
         return;
     }
 
@@ -71,11 +94,15 @@ pub fn update_water(api: &mut SandApi) {
     // has support below. Alternate direction by generation parity to
     // prevent scan-order bias from pushing extras to one side.
     if above.species == Species::Empty && below.species == Species::Water {
-        let surf_dir: i32 = if api.generation % 2 == 0 { 1 } else { -1 };
-        if try_surface_hop(api, &me, surf_dir) {
+        let surf_dir: i32 = if api.generation.is_multiple_of(2) {
+            1
+        } else {
+            -1
+        };
+        if try_surface_hop(api, me, surf_dir) {
             return;
         }
-        if try_surface_hop(api, &me, -surf_dir) {
+        if try_surface_hop(api, me, -surf_dir) {
             return;
         }
     }
@@ -84,29 +111,37 @@ pub fn update_water(api: &mut SandApi) {
     // Ray-cast through water to find a surface cell and swap with it.
     // This moves mass from tall columns to short ones.
     if above.species != Species::Empty {
-        let level_dir: i32 = if api.generation % 2 == 0 { 1 } else { -1 };
-        if try_level_swap(api, &me, level_dir) {
+        let level_dir: i32 = if api.generation.is_multiple_of(2) {
+            1
+        } else {
+            -1
+        };
+        if try_level_swap(api, me, level_dir) {
             return;
         }
-        if try_level_swap(api, &me, -level_dir) {
+        if try_level_swap(api, me, -level_dir) {
             return;
         }
     }
 
-    // Phase 6: Fully blocked — flip direction.
-    let mut blocked = me;
-    blocked.ra ^= 1;
-    api.set(0, 0, blocked);
+    // Phase 6: Fully blocked — flip direction, once. Rewriting the cell
+    // every tick would keep its chunk awake forever.
+    if here.ra & BLOCKED == 0 {
+        let mut blocked = me;
+        blocked.ra ^= 1;
+        blocked.ra |= BLOCKED;
+        api.set(0, 0, blocked);
+    }
 }
 
-/// Ray-cast up to HORIZONTAL_RANGE cells in direction `dx`.
+/// Ray-cast up to `HORIZONTAL_RANGE` cells in direction `dx`.
 /// Move to the nearest reachable empty cell (viscous flow).
-fn try_horizontal(api: &mut SandApi, me: &Cell, dx: i32) -> bool {
+fn try_horizontal(api: &mut SandApi, me: Cell, dx: i32) -> bool {
     for step in 1..=HORIZONTAL_RANGE {
         let neighbor = api.get(dx * step, 0);
         if neighbor.species == Species::Empty {
             api.set(0, 0, Cell::empty());
-            api.set(dx * step, 0, *me);
+            api.set(dx * step, 0, me);
             return true;
         }
         if neighbor.species != Species::Water {
@@ -116,7 +151,7 @@ fn try_horizontal(api: &mut SandApi, me: &Cell, dx: i32) -> bool {
     false
 }
 
-/// Scan through water in direction `dx` up to LEVEL_SCAN_RANGE cells.
+/// Scan through water in direction `dx` up to `LEVEL_SCAN_RANGE` cells.
 /// Find a water cell that has empty above it (a surface cell) and swap
 /// with it, redistributing mass from tall columns to short ones.
 ///
@@ -127,7 +162,7 @@ fn try_horizontal(api: &mut SandApi, me: &Cell, dx: i32) -> bool {
 /// When the horizontal scan hits a non-water cell, it tries stepping
 /// down or up to follow the water body's contour around obstacles like
 /// sand slopes or walls.
-fn try_level_swap(api: &mut SandApi, me: &Cell, dx: i32) -> bool {
+fn try_level_swap(api: &mut SandApi, me: Cell, dx: i32) -> bool {
     let mut cx = dx;
     let mut cy: i32 = 0;
     let mut steps_taken: i32 = 0;
@@ -140,7 +175,7 @@ fn try_level_swap(api: &mut SandApi, me: &Cell, dx: i32) -> bool {
                 // Found a surface cell far enough away to be in a
                 // different (shorter) column. Swap to redistribute.
                 api.set(0, 0, neighbor);
-                api.set(cx, cy, *me);
+                api.set(cx, cy, me);
                 return true;
             }
             cx += dx;
@@ -168,7 +203,7 @@ fn try_level_swap(api: &mut SandApi, me: &Cell, dx: i32) -> bool {
 /// If the neighbor is empty with support below, flow sideways into it.
 /// This is the key mechanism for leveling water surfaces near obstacles
 /// where horizontal ray-cast can't reach (blocked by the obstacle).
-fn try_surface_hop(api: &mut SandApi, me: &Cell, dx: i32) -> bool {
+fn try_surface_hop(api: &mut SandApi, me: Cell, dx: i32) -> bool {
     let neighbor = api.get(dx, 0);
     if neighbor.species == Species::Empty {
         // Neighbor column is shorter — flow sideways into it.
@@ -176,7 +211,7 @@ fn try_surface_hop(api: &mut SandApi, me: &Cell, dx: i32) -> bool {
         let below_neighbor = api.get(dx, 1);
         if below_neighbor.species != Species::Empty {
             api.set(0, 0, Cell::empty());
-            api.set(dx, 0, *me);
+            api.set(dx, 0, me);
             return true;
         }
     }
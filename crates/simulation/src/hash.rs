@@ -0,0 +1,195 @@
+//! Stable hashing of simulation state for desync detection.
+//!
+//! Peers and replays compare [`Grid::state_hash`] after every tick; when it
+//! differs, [`Grid::chunk_hashes`] narrows the mismatch down to chunks. The
+//! hash is a fixed function of the state's bytes, with no per-process seed
+//! and nothing that depends on pointer width or endianness, so native and
+//! wasm32 builds agree.
+
+use crate::cell::Cell;
+use crate::chunk::CHUNK_SIZE;
+use crate::Grid;
+
+/// Multiply-rotate word hasher (the `FxHash` step) with a murmur3
+/// finalizer so that nearby states don't produce nearby hashes.
+struct StateHasher(u64);
+
+impl StateHasher {
+    const SEED: u64 = 0x243F_6A88_85A3_08D3;
+    const K: u64 = 0x517C_C1B7_2722_0A95;
+
+    fn new() -> Self {
+        Self(Self::SEED)
+    }
+
+    fn write(&mut self, word: u64) {
+        self.0 = (self.0.rotate_left(5) ^ word).wrapping_mul(Self::K);
+    }
+
+    /// Hash each cell together with the heat at its position.
+    fn write_cells(&mut self, cells: &[Cell], heat: &[u8]) {
+        for (cell, &heat) in cells.iter().zip(heat) {
+            let packed = u32::from_le_bytes([cell.species as u8, cell.ra, cell.rb, cell.clock]);
+            self.write(u64::from(packed) | u64::from(heat) << 32);
+        }
+    }
+
+    fn finish(self) -> u64 {
+        let mut h = self.0;
+        h ^= h >> 33;
+        h = h.wrapping_mul(0xFF51_AFD7_ED55_8CCD);
+        h ^= h >> 33;
+        h = h.wrapping_mul(0xC4CE_B9FE_1A85_EC53);
+        h ^ (h >> 33)
+    }
+}
+
+impl Grid {
+    /// Hash of everything a future tick depends on: dimensions, every
+    /// cell, the heat field, the tick counter, the RNG state, the next
+    /// ghost group, the edge boundaries and the chunk schedule (which cells
+    /// the next tick visits). Cheap enough to call every tick.
+    ///
+    /// Two grids with equal hashes are, for practical purposes, in the
+    /// same state and will stay in step under the same reaction table,
+    /// which is configuration and is not included.
+    #[must_use]
+    pub fn state_hash(&self) -> u64 {
+        let mut hasher = StateHasher::new();
+        hasher.write(self.width as u64);
+        hasher.write(self.height as u64);
+        hasher.write(self.tick_count);
+        hasher.write(self.rng.state());
+        hasher.write(u64::from(self.next_ghost_group));
+        hasher.write(u64::from(u32::from_le_bytes(self.boundaries.to_ids())));
+        hasher.write(u64::from(self.heat_active));
+        for chunk in &self.chunks {
+            let next = chunk.next;
+            for (a, b) in [(next.min_x, next.min_y), (next.max_x, next.max_y)] {
+                hasher.write(u64::from(a as u32) | u64::from(b as u32) << 32);
+            }
+        }
+        hasher.write_cells(&self.cells, &self.heat);
+        hasher.finish()
+    }
+
+    /// Hash of the cells and heat in each chunk, row-major by chunk;
+    /// [`Grid::chunk_origin`] maps an index back to grid coordinates.
+    ///
+    /// Comparing two peers' lists points at the regions that diverged.
+    /// Unlike [`Grid::state_hash`] these don't cover the tick counter or
+    /// RNG state.
+    #[must_use]
+    pub fn chunk_hashes(&self) -> Vec<u64> {
+        self.chunks
+            .iter()
+            .map(|chunk| {
+                let bounds = chunk.bounds;
+                let mut hasher = StateHasher::new();
+                hasher.write(u64::from(bounds.min_x.unsigned_abs()));
+                hasher.write(u64::from(bounds.min_y.unsigned_abs()));
+                let (x0, x1) = (bounds.min_x as usize, bounds.max_x as usize + 1);
+                for y in bounds.min_y as usize..=bounds.max_y as usize {
+                    let row = y * self.width;
+                    hasher.write_cells(
+                        &self.cells[row + x0..row + x1],
+                        &self.heat[row + x0..row + x1],
+                    );
+                }
+                hasher.finish()
+            })
+            .collect()
+    }
+
+    /// Top-left cell of the chunk at `index` in [`Grid::chunk_hashes`].
+    #[must_use]
+    pub fn chunk_origin(&self, index: usize) -> (usize, usize) {
+        (
+            index % self.chunks_w * CHUNK_SIZE,
+            index / self.chunks_w * CHUNK_SIZE,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cell::Species;
+
+    fn sample() -> Grid {
+        let mut grid = Grid::with_seed(70, 40, 9);
+        for x in 10..60 {
+            grid.set(x, 5, Cell::new(Species::Sand));
+            grid.set(x, 30, Cell::new(Species::Water));
+        }
+        grid.set(35, 39, Cell::new(Species::Fire));
+        for _ in 0..25 {
+            grid.tick();
+        }
+        grid
+    }
+
+    #[test]
+    fn hash_is_fixed_across_platforms() {
+        // Pinned value: if this changes, saved replays and peers on other
+        // builds stop agreeing. Only change it with a `REPLAY_VERSION` bump.
+        let mut grid = Grid::with_seed(4, 3, 1);
+        grid.set(1, 1, Cell::new(Species::Sand));
+        assert_eq!(grid.state_hash(), 15_381_707_157_924_499_520);
+    }
+
+    #[test]
+    fn identical_runs_hash_identically() {
+        let a = sample();
+        let b = sample();
+        assert_eq!(a.state_hash(), b.state_hash());
+        assert_eq!(a.chunk_hashes(), b.chunk_hashes());
+    }
+
+    #[test]
+    fn any_state_change_changes_the_hash() {
+        let base = sample();
+        let hash = base.state_hash();
+
+        let mut grid = sample();
+        grid.cells[0].clock ^= 1;
+        assert_ne!(grid.state_hash(), hash);
+
+        let mut grid = sample();
+        grid.tick_count += 1;
+        assert_ne!(grid.state_hash(), hash);
+
+        let mut grid = sample();
+        grid.rng.next_u64();
+        assert_ne!(grid.state_hash(), hash);
+
+        let mut grid = sample();
+        grid.set_heat(3, 3, 99);
+        assert_ne!(grid.state_hash(), hash);
+
+        let mut grid = sample();
+        grid.boundaries.top = crate::boundary::Boundary::Wrap;
+        assert_ne!(grid.state_hash(), hash);
+
+        let mut grid = sample();
+        grid.keep_awake(1, 1);
+        assert_ne!(grid.state_hash(), hash, "the schedule counts too");
+    }
+
+    #[test]
+    fn chunk_hashes_locate_the_change() {
+        let base = sample();
+        let mut grid = sample();
+        grid.cells[38 * 70 + 65] = Cell::wall();
+
+        let differing: Vec<_> = base
+            .chunk_hashes()
+            .iter()
+            .zip(grid.chunk_hashes())
+            .enumerate()
+            .filter(|&(_, (a, b))| *a != b)
+            .map(|(i, _)| grid.chunk_origin(i))
+            .collect();
+        assert_eq!(differing, [(64, 32)]);
+    }
+}
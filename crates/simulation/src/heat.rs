@@ -0,0 +1,192 @@
+//! Per-cell temperature field.
+//!
+//! Heat lives in a `u8` buffer parallel to `Grid::cells` and belongs to
+//! the position, not the material: cells moving through a hot spot pick
+//! up its temperature rather than carrying their own. After the element
+//! updates of each tick the field runs one pass:
+//!
+//! 1. Emitters (`ElementDef::heat_output`) raise their own spot.
+//! 2. Every spot moves towards the average of its four neighbours at the
+//!    rate of the species on it (`ElementDef::conductivity`). Heat flows
+//!    across Wrap edges as cells do; Wall and Void edges are insulating.
+//!    Empty spots also cool towards [`AMBIENT_HEAT`].
+//! 3. Cells whose spot reached their `heat_transition` threshold change
+//!    species, e.g. Water boils to Steam.
+//!
+//! The pass is skipped while the whole field sits at ambient and nothing
+//! emits heat, so a cold world costs nothing.
+
+use crate::boundary::Boundary;
+use crate::cell::Species;
+use crate::elements::{element, spawn_cell};
+use crate::Grid;
+
+/// Resting temperature of the world.
+pub const AMBIENT_HEAT: u8 = 20;
+
+/// Empty spots close about this fraction of their gap to ambient each tick.
+const COOLING_DIVISOR: i32 = 16;
+
+impl Grid {
+    /// The temperature field, row-major like `cells`.
+    #[must_use]
+    pub fn heat(&self) -> &[u8] {
+        &self.heat
+    }
+
+    /// Temperature at `(x, y)`. Out of bounds reads ambient.
+    #[must_use]
+    pub fn heat_at(&self, x: i32, y: i32) -> u8 {
+        if self.in_bounds(x, y) {
+            self.heat[y as usize * self.width + x as usize]
+        } else {
+            AMBIENT_HEAT
+        }
+    }
+
+    /// Set the temperature at `(x, y)`. Out of bounds is a no-op.
+    pub fn set_heat(&mut self, x: i32, y: i32, heat: u8) {
+        if self.in_bounds(x, y) {
+            self.heat[y as usize * self.width + x as usize] = heat;
+            self.heat_active = true;
+        }
+    }
+
+    /// Run one emission, diffusion and transition pass over the field.
+    pub(crate) fn update_heat(&mut self) {
+        if !self.heat_active {
+            return;
+        }
+        let (w, h) = (self.width, self.height);
+        let edges = self.boundaries;
+        let wraps = |edge: Boundary| edge == Boundary::Wrap;
+
+        for (spot, cell) in self.heat.iter_mut().zip(&self.cells) {
+            *spot = (*spot).max(element(cell.species).heat_output);
+        }
+
+        let mut active = false;
+        for y in 0..h {
+            for x in 0..w {
+                let i = y * w + x;
+                let here = i32::from(self.heat[i]);
+                let neighbour = |nx: usize, ny: usize| i32::from(self.heat[ny * w + nx]);
+                let up = match y {
+                    0 if wraps(edges.top) => neighbour(x, h - 1),
+                    0 => here,
+                    _ => neighbour(x, y - 1),
+                };
+                let down = match y + 1 {
+                    ny if ny < h => neighbour(x, ny),
+                    _ if wraps(edges.bottom) => neighbour(x, 0),
+                    _ => here,
+                };
+                let left = match x {
+                    0 if wraps(edges.left) => neighbour(w - 1, y),
+                    0 => here,
+                    _ => neighbour(x - 1, y),
+                };
+                let right = match x + 1 {
+                    nx if nx < w => neighbour(nx, y),
+                    _ if wraps(edges.right) => neighbour(0, y),
+                    _ => here,
+                };
+                let average = (up + down + left + right) / 4;
+
+                let def = element(self.cells[i].species);
+                let mut next = here + (average - here) * i32::from(def.conductivity) / 256;
+                if self.cells[i].species == Species::Empty {
+                    // Always step at least one degree so small gaps close.
+                    let gap = i32::from(AMBIENT_HEAT) - next;
+                    next += gap / COOLING_DIVISOR + gap.signum();
+                }
+                let next = next.clamp(0, 255) as u8;
+                active |= next != AMBIENT_HEAT || def.heat_output > 0;
+                self.heat_next[i] = next;
+            }
+        }
+        std::mem::swap(&mut self.heat, &mut self.heat_next);
+        self.heat_active = active;
+
+        for i in 0..self.cells.len() {
+            let Some(transition) = element(self.cells[i].species).heat_transition else {
+                continue;
+            };
+            if self.heat[i] >= transition.threshold {
+                let cell = spawn_cell(transition.into, &mut self.rng);
+                self.set((i % w) as i32, (i / w) as i32, cell);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cell::Cell;
+
+    #[test]
+    fn cold_world_stays_at_ambient() {
+        let mut grid = Grid::new(8, 8);
+        grid.set(3, 3, Cell::new(Species::Sand));
+        for _ in 0..10 {
+            grid.tick();
+        }
+        assert!(grid.heat().iter().all(|&h| h == AMBIENT_HEAT));
+        assert!(!grid.heat_active);
+    }
+
+    #[test]
+    fn fire_heats_its_surroundings() {
+        let mut grid = Grid::new(9, 9);
+        for x in 0..9 {
+            grid.set(x, 0, Cell::wall());
+        }
+        let mut fire = Cell::new(Species::Fire);
+        fire.rb = 200;
+        grid.set(4, 1, fire);
+        for _ in 0..5 {
+            grid.tick();
+        }
+        assert!(grid.heat_at(4, 2) > AMBIENT_HEAT);
+        assert!(grid.heat_at(4, 2) > grid.heat_at(4, 7));
+    }
+
+    #[test]
+    fn heat_fades_back_to_ambient() {
+        let mut grid = Grid::new(4, 4);
+        grid.set_heat(1, 1, 250);
+        for _ in 0..400 {
+            grid.tick();
+        }
+        assert!(grid.heat().iter().all(|&h| h == AMBIENT_HEAT));
+    }
+
+    #[test]
+    fn heat_crosses_wrapping_edges_only() {
+        let mut grid = Grid::new(8, 8);
+        grid.boundaries.left = Boundary::Wrap;
+        grid.boundaries.right = Boundary::Wrap;
+        grid.set_heat(0, 4, 250);
+        grid.tick();
+        assert!(grid.heat_at(7, 4) > AMBIENT_HEAT, "across the seam");
+        assert_eq!(grid.heat_at(0, 7), AMBIENT_HEAT, "the bottom is a wall");
+        assert_eq!(grid.heat_at(4, 4), AMBIENT_HEAT);
+    }
+
+    #[test]
+    fn thresholds_trigger_transitions() {
+        let mut grid = Grid::new(3, 1);
+        grid.set(0, 0, Cell::new(Species::Water));
+        grid.set(1, 0, Cell::new(Species::Sand));
+        grid.set(2, 0, Cell::new(Species::Ice));
+        grid.set_heat(0, 0, 255);
+        grid.set_heat(1, 0, 255);
+        grid.set_heat(2, 0, 255);
+        grid.tick();
+
+        assert_eq!(grid.get(0, 0).species, Species::Steam);
+        assert_eq!(grid.get(1, 0).species, Species::Glass);
+        assert_eq!(grid.get(2, 0).species, Species::Water);
+    }
+}
@@ -0,0 +1,416 @@
+//! Undo and redo for user edits.
+//!
+//! Edits made between `Universe::begin_stroke` and `Universe::end_stroke`
+//! (`set_cell`, `set_ghost`, the brushes, fills, cuts, pastes and stamps)
+//! form one stroke. A stroke stores only the cells it changed, each as the
+//! cell before its first write and after its last; repeated writes to a
+//! cell are merged as they happen, so an open stroke never holds more
+//! entries than the grid has cells. Edits outside a stroke are not
+//! recorded.
+//!
+//! The world keeps simulating between a stroke and its undo, so a painted
+//! cell may have fallen, burnt or been pushed aside by then. Undo only
+//! restores a cell that still holds what the stroke left there, and redo
+//! only one that still holds what it replaced; cells that no longer match
+//! are left alone. A cell matches if its species and the random part of
+//! its `ra` are unchanged, so painted sand that has since fallen stays
+//! where it landed, and water that flowed into a painted cell is not
+//! mistaken for the painted water.
+//!
+//! Ghost groups move as rigid shapes, so they are undone and redone as a
+//! unit, keyed on their group ID in `ra` as the clipboard and resize do: a
+//! group is only touched if every one of its recorded cells still matches.
+//!
+//! History is capped at [`DEFAULT_HISTORY_LIMIT`] bytes by default
+//! (`Universe::set_history_limit`); the oldest strokes are forgotten first.
+
+use crate::cell::{Cell, Species};
+use crate::Grid;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+
+/// Default cap on memory held by undo and redo history, in bytes.
+pub const DEFAULT_HISTORY_LIMIT: usize = 16 << 20;
+
+/// One cell written by an edit.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct Change {
+    index: u32,
+    before: Cell,
+    after: Cell,
+}
+
+/// The edits of an open stroke, merged to one change per cell.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct EditLog {
+    /// Position in `changes` of each cell written so far.
+    slots: HashMap<u32, usize>,
+    changes: Vec<Change>,
+}
+
+impl EditLog {
+    /// Note that the cell at `index` went from `before` to `after`.
+    fn record(&mut self, index: u32, before: Cell, after: Cell) {
+        match self.slots.entry(index) {
+            Entry::Occupied(slot) => self.changes[*slot.get()].after = after,
+            Entry::Vacant(slot) => {
+                slot.insert(self.changes.len());
+                self.changes.push(Change {
+                    index,
+                    before,
+                    after,
+                });
+            }
+        }
+    }
+}
+
+/// The net changes of one stroke.
+#[derive(Debug)]
+struct Stroke {
+    changes: Vec<Change>,
+}
+
+impl Stroke {
+    /// Keep the cells of `log` whose species did not end up where it
+    /// started.
+    fn from_log(log: EditLog) -> Self {
+        let mut changes = log.changes;
+        changes.retain(|c| c.before.species != c.after.species);
+        Self { changes }
+    }
+
+    fn bytes(&self) -> usize {
+        self.changes.len() * size_of::<Change>()
+    }
+}
+
+/// Undo and redo stacks; see the [module docs](self).
+#[derive(Debug)]
+pub struct History {
+    undo: VecDeque<Stroke>,
+    redo: Vec<Stroke>,
+    /// Memory held by both stacks.
+    bytes: usize,
+    limit: usize,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            bytes: 0,
+            limit: DEFAULT_HISTORY_LIMIT,
+        }
+    }
+}
+
+impl History {
+    #[must_use]
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    #[must_use]
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Memory cap in bytes.
+    #[must_use]
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Change the memory cap, forgetting the oldest strokes (and then any
+    /// redo history) until the rest fits.
+    pub fn set_limit(&mut self, bytes: usize) {
+        self.limit = bytes;
+        self.trim();
+    }
+
+    /// Forget all strokes.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.bytes = 0;
+    }
+
+    /// Record a finished stroke from its edit log. A new stroke makes redo
+    /// impossible, so the redo stack is dropped.
+    pub(crate) fn push(&mut self, log: EditLog) {
+        let stroke = Stroke::from_log(log);
+        if stroke.changes.is_empty() {
+            return;
+        }
+        for dropped in self.redo.drain(..) {
+            self.bytes -= dropped.bytes();
+        }
+        self.bytes += stroke.bytes();
+        self.undo.push_back(stroke);
+        self.trim();
+    }
+
+    /// Revert the latest stroke on `grid`. Returns false if there is none.
+    pub(crate) fn undo(&mut self, grid: &mut Grid) -> bool {
+        let Some(stroke) = self.undo.pop_back() else {
+            return false;
+        };
+        let reverted: Vec<_> = stroke
+            .changes
+            .iter()
+            .rev()
+            .map(|c| (c.index, c.after, c.before))
+            .collect();
+        grid.restore(&reverted);
+        self.redo.push(stroke);
+        true
+    }
+
+    /// Reapply the latest undone stroke on `grid`. Returns false if there
+    /// is none.
+    pub(crate) fn redo(&mut self, grid: &mut Grid) -> bool {
+        let Some(stroke) = self.redo.pop() else {
+            return false;
+        };
+        let reapplied: Vec<_> = stroke
+            .changes
+            .iter()
+            .map(|c| (c.index, c.before, c.after))
+            .collect();
+        grid.restore(&reapplied);
+        self.undo.push_back(stroke);
+        true
+    }
+
+    fn trim(&mut self) {
+        while self.bytes > self.limit {
+            let dropped = match self.undo.pop_front() {
+                Some(stroke) => stroke,
+                None if !self.redo.is_empty() => self.redo.remove(0),
+                None => break,
+            };
+            self.bytes -= dropped.bytes();
+        }
+    }
+}
+
+impl Grid {
+    /// Write a user edit at `(x, y)`, recording it if a stroke is open.
+    /// Out-of-bounds coordinates are ignored rather than wrapped.
+    pub(crate) fn edit(&mut self, x: i32, y: i32, cell: Cell) {
+        if !self.in_bounds(x, y) {
+            return;
+        }
+        if let Some(log) = &mut self.edit_log {
+            let index = y as usize * self.width + x as usize;
+            log.record(index as u32, self.cells[index], cell);
+        }
+        self.set(x, y, cell);
+    }
+
+    /// Start recording edits into a fresh log, returning any open one.
+    pub(crate) fn start_edit_log(&mut self) -> Option<EditLog> {
+        self.edit_log.replace(EditLog::default())
+    }
+
+    /// Stop recording edits and return the log, if one was open.
+    pub(crate) fn take_edit_log(&mut self) -> Option<EditLog> {
+        self.edit_log.take()
+    }
+
+    /// Apply `(index, from, to)` changes in order, setting each cell that
+    /// still holds `from` to `to`. A ghost group is only touched if all of
+    /// its cells match; see the [module docs](self).
+    fn restore(&mut self, changes: &[(u32, Cell, Cell)]) {
+        let mut broken: Vec<u8> = Vec::new();
+        for &(index, from, to) in changes {
+            if !self.holds(index, from) {
+                broken.extend(ghost_groups(from, to));
+            }
+        }
+        for &(index, from, to) in changes {
+            if ghost_groups(from, to).any(|g| broken.contains(&g)) || !self.holds(index, from) {
+                continue;
+            }
+            let index = index as usize;
+            let (x, y) = (index % self.width, index / self.width);
+            self.set(x as i32, y as i32, to);
+        }
+    }
+
+    /// True if the cell at `index` is still the particle `cell`.
+    ///
+    /// Elements keep mutable flow state in the low two bits of `ra` (flow
+    /// direction, water's blocked mark), so only the rest is compared. Ghosts
+    /// keep their group in `ra` and redraw their eyes in `rb`.
+    fn holds(&self, index: u32, cell: Cell) -> bool {
+        self.cells.get(index as usize).is_some_and(|now| {
+            now.species == cell.species
+                && if cell.species == Species::Ghost {
+                    now.ra == cell.ra
+                } else {
+                    now.ra >> 2 == cell.ra >> 2
+                }
+        })
+    }
+}
+
+/// Ghost groups a change from `from` to `to` belongs to.
+fn ghost_groups(from: Cell, to: Cell) -> impl Iterator<Item = u8> {
+    [from, to]
+        .into_iter()
+        .filter(|c| c.species == Species::Ghost)
+        .map(|c| c.ra)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ascii::assert_ascii_eq;
+    use crate::elements::ghost::MOVE_DIVISOR;
+    use crate::paint::PaintMode;
+    use crate::Universe;
+
+    const SAND: u8 = Species::Sand as u8;
+    const WALL: u8 = Species::Wall as u8;
+
+    fn universe(picture: &str) -> Universe {
+        Universe::from_grid(Grid::from_ascii(picture).unwrap())
+    }
+
+    #[test]
+    fn undo_and_redo_a_stroke() {
+        let mut u = universe("..... / ..~.. / .....");
+        u.begin_stroke();
+        u.paint_line(0, 1, 4, 1, 0, WALL, PaintMode::FillEmpty, 0);
+        u.set_cell(0, 0, WALL);
+        u.set_cell(0, 0, SAND);
+        u.end_stroke();
+        assert_ascii_eq(&u.grid, "#.... / ##~## / .....");
+
+        assert!(u.undo());
+        assert_ascii_eq(&u.grid, "..... / ..~.. / .....");
+        assert!(!u.undo(), "the stroke was the only one");
+        assert!(u.redo());
+        assert_ascii_eq(&u.grid, "#.... / ##~## / .....");
+        assert!(!u.redo());
+    }
+
+    #[test]
+    fn undo_leaves_cells_that_moved_since() {
+        let mut u = universe("..... / ..... / ..... / #####");
+        u.begin_stroke();
+        u.set_cell(1, 0, SAND);
+        u.set_cell(3, 0, WALL);
+        u.end_stroke();
+        for _ in 0..5 {
+            u.tick();
+        }
+        assert_ascii_eq(&u.grid, "...#. / ..... / .s... / #####");
+        u.undo();
+        assert_ascii_eq(&u.grid, "..... / ..... / .s... / #####");
+    }
+
+    #[test]
+    fn undo_leaves_look_alikes_that_flowed_in() {
+        let mut u = universe("... / ###");
+        u.begin_stroke();
+        u.set_cell(1, 0, Species::Water as u8);
+        u.end_stroke();
+        let mut other = u.grid.get(1, 0);
+        other.ra ^= 0b100;
+        u.grid.set(1, 0, other);
+        u.undo();
+        assert_ascii_eq(&u.grid, ".~. / ###");
+    }
+
+    #[test]
+    fn ghosts_are_undone_as_a_group() {
+        let ghosts = |u: &Universe| {
+            let cells = u.grid.cells.iter().filter(|c| c.species == Species::Ghost);
+            cells.map(|c| c.ra).collect::<Vec<_>>()
+        };
+        let mut u = Universe::new(64, 64);
+        u.begin_stroke();
+        u.place_stamp("ghost", 32, 32).unwrap();
+        u.end_stroke();
+        let placed = u.grid.cells.clone();
+        let group = ghosts(&u)[0];
+
+        // Eyes are redrawn in `rb` on the first tick; the group still undoes.
+        u.tick();
+        assert_ne!(u.grid.cells, placed);
+        u.undo();
+        assert!(ghosts(&u).is_empty());
+
+        u.redo();
+        assert_eq!(u.grid.cells, placed);
+        for _ in 0..MOVE_DIVISOR {
+            u.tick();
+        }
+        let moved = ghosts(&u);
+        assert!(placed
+            .iter()
+            .zip(&u.grid.cells)
+            .any(|(a, b)| a.species != b.species));
+        u.undo();
+        assert_eq!(ghosts(&u), moved, "a moved group is left whole");
+        assert!(moved.iter().all(|&g| g == group));
+    }
+
+    #[test]
+    fn only_strokes_are_recorded_and_new_ones_drop_redo() {
+        let mut u = universe("...");
+        u.set_cell(0, 0, WALL);
+        assert!(!u.history.can_undo());
+
+        u.begin_stroke();
+        u.set_cell(1, 0, WALL);
+        u.end_stroke();
+        u.undo();
+        assert!(u.history.can_redo());
+        u.begin_stroke();
+        u.set_cell(2, 0, WALL);
+        u.undo();
+        assert_ascii_eq(&u.grid, "#..");
+        assert!(u.history.can_redo(), "undo ends the open stroke first");
+        u.begin_stroke();
+        u.set_cell(2, 0, SAND);
+        u.end_stroke();
+        assert!(!u.history.can_redo());
+    }
+
+    #[test]
+    fn limit_forgets_the_oldest_strokes() {
+        let mut u = universe("....");
+        u.set_history_limit(2 * size_of::<Change>());
+        for x in 0..3 {
+            u.begin_stroke();
+            u.set_cell(x, 0, WALL);
+            u.end_stroke();
+        }
+        assert!(u.undo() && u.undo());
+        assert!(!u.undo(), "the first stroke no longer fits");
+        assert_ascii_eq(&u.grid, "#...");
+
+        u.set_history_limit(0);
+        assert!(!u.history.can_redo());
+        assert_eq!(u.history.bytes, 0);
+    }
+
+    #[test]
+    fn open_strokes_merge_repeated_writes() {
+        let mut u = universe("... / ...");
+        u.begin_stroke();
+        for _ in 0..100 {
+            u.paint_rect(0, 0, 2, 1, WALL, PaintMode::Replace, 0);
+            u.paint_rect(0, 0, 2, 1, SAND, PaintMode::Replace, 0);
+        }
+        assert_eq!(u.grid.edit_log.as_ref().unwrap().changes.len(), 6);
+        u.end_stroke();
+        u.undo();
+        assert_ascii_eq(&u.grid, "... / ...");
+    }
+}
@@ -0,0 +1,358 @@
+//! PNG import and export through the species palette.
+//!
+//! Every species has a palette colour (`ElementDef::color`). Import maps
+//! each pixel to the species with that colour and initializes the cell
+//! like `Universe::set_cell`, so sand gets grain variation and fire a
+//! lifetime. Fully transparent pixels import as Empty. Export writes the
+//! palette colours, which import back to the same species, or shades cells
+//! from `rb` the way the game renderer does.
+
+use crate::cell::{Cell, Species};
+use crate::compact::MAX_CELLS;
+use crate::elements::{spawn_cell, ELEMENTS};
+use crate::Grid;
+use std::fmt;
+
+/// What to do with pixels whose colour is not in the palette.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum UnknownColors {
+    /// Fail the import.
+    Reject,
+    /// Use the species whose palette colour is closest.
+    Nearest,
+}
+
+/// How `Grid::to_image` colours cells.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Shading {
+    /// Exact palette colours; the image imports back losslessly by species.
+    Flat,
+    /// Per-cell shading from `rb` (sand grain, fire age, smoke fade, ghost
+    /// eyes), matching the in-game look.
+    Game,
+}
+
+/// An image could not be turned into a grid.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ImageError {
+    /// The bytes are not a PNG this decoder can read.
+    Decode(png::DecodingError),
+    /// The image has more pixels than a grid may hold (4 Mi, as for
+    /// [`Grid::from_compact`]).
+    TooLarge { width: u32, height: u32 },
+    /// Pixel `(x, y)` has colour `rgb`, which is not in the palette, and
+    /// unknown colours were rejected.
+    UnknownColor { x: usize, y: usize, rgb: [u8; 3] },
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Decode(_) => f.write_str("could not decode PNG"),
+            Self::TooLarge { width, height } => {
+                write!(f, "a {width}×{height} image is too large for a grid")
+            }
+            Self::UnknownColor {
+                x,
+                y,
+                rgb: [r, g, b],
+            } => {
+                write!(f, "pixel ({x}, {y}) has colour #{r:02x}{g:02x}{b:02x}, which is not in the palette")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ImageError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Decode(err) => Some(err),
+            Self::TooLarge { .. } | Self::UnknownColor { .. } => None,
+        }
+    }
+}
+
+impl Grid {
+    /// Build a grid from a PNG whose pixels are species palette colours.
+    ///
+    /// Cells get the same per-species initialization as
+    /// `Universe::set_cell`, drawn from the new grid's RNG. Ghost pixels all
+    /// join one ghost group, allocated at the first of them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`ImageError`] if the PNG can't be decoded or has more
+    /// pixels than a grid may hold, or if a pixel colour is not in the
+    /// palette and `unknown` is [`UnknownColors::Reject`].
+    pub fn from_image(png_bytes: &[u8], unknown: UnknownColors) -> Result<Self, ImageError> {
+        let decode = ImageError::Decode;
+        let mut decoder = png::Decoder::new(png_bytes);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info().map_err(decode)?;
+        // Check the header before allocating anything sized by it.
+        let (width, height) = reader.info().size();
+        if u64::from(width) * u64::from(height) > MAX_CELLS as u64 {
+            return Err(ImageError::TooLarge { width, height });
+        }
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let frame = reader.next_frame(&mut buf).map_err(decode)?;
+        let (width, height) = (frame.width as usize, frame.height as usize);
+        let channels = frame.color_type.samples();
+
+        let mut grid = Self::new(width, height);
+        let mut ghost_group = None;
+        for y in 0..height {
+            let row = &buf[y * frame.line_size..][..width * channels];
+            for (x, px) in row.chunks_exact(channels).enumerate() {
+                let (rgb, alpha) = match *px {
+                    [v] => ([v, v, v], 255),
+                    [v, a] => ([v, v, v], a),
+                    [r, g, b] => ([r, g, b], 255),
+                    [r, g, b, a, ..] => ([r, g, b], a),
+                    [] => unreachable!("PNG pixels have at least one sample"),
+                };
+                let species = if alpha == 0 {
+                    Species::Empty
+                } else {
+                    match (palette_species(rgb), unknown) {
+                        (Some(species), _) => species,
+                        (None, UnknownColors::Nearest) => nearest_species(rgb),
+                        (None, UnknownColors::Reject) => {
+                            return Err(ImageError::UnknownColor { x, y, rgb });
+                        }
+                    }
+                };
+                let mut cell = spawn_cell(species, &mut grid.rng);
+                if species == Species::Ghost {
+                    cell.ra = *ghost_group.get_or_insert_with(|| grid.alloc_ghost_group());
+                }
+                grid.cells[y * width + x] = cell;
+            }
+        }
+        grid.wake_all();
+        Ok(grid)
+    }
+
+    /// Encode the grid as an RGB PNG.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a dimension exceeds `u32::MAX`.
+    #[must_use]
+    pub fn to_image(&self, shading: Shading) -> Vec<u8> {
+        let mut pixels = Vec::with_capacity(self.cells.len() * 3);
+        for cell in &self.cells {
+            let rgb = match shading {
+                Shading::Flat => ELEMENTS[cell.species as usize].color,
+                Shading::Game => game_color(*cell),
+            };
+            pixels.extend_from_slice(&rgb);
+        }
+
+        let width = u32::try_from(self.width).expect("width fits in u32");
+        let height = u32::try_from(self.height).expect("height fits in u32");
+        let mut out = Vec::new();
+        let mut encoder = png::Encoder::new(&mut out, width, height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        // Writing into a `Vec` can't fail and the header matches `pixels`.
+        let mut writer = encoder.write_header().expect("valid PNG header");
+        writer
+            .write_image_data(&pixels)
+            .expect("pixel data matches header");
+        writer.finish().expect("PNG finishes writing");
+        out
+    }
+}
+
+/// The species whose palette colour is exactly `rgb`.
+fn palette_species(rgb: [u8; 3]) -> Option<Species> {
+    ELEMENTS
+        .iter()
+        .find(|def| def.color == rgb)
+        .map(|def| def.species)
+}
+
+/// The species whose palette colour is closest to `rgb`.
+fn nearest_species(rgb: [u8; 3]) -> Species {
+    let distance = |color: [u8; 3]| -> i32 {
+        color
+            .iter()
+            .zip(rgb)
+            .map(|(&a, b)| (i32::from(a) - i32::from(b)).pow(2))
+            .sum()
+    };
+    ELEMENTS
+        .iter()
+        .min_by_key(|def| distance(def.color))
+        .map_or(Species::Empty, |def| def.species)
+}
+
+/// Colour of `cell` as the game's grid shader draws it.
+fn game_color(cell: Cell) -> [u8; 3] {
+    let rb = f32::from(cell.rb);
+    let rgb = match cell.species {
+        Species::Sand => {
+            let grain = rb / 255.0;
+            let (warm, base, pale) = (
+                [0.78, 0.55, 0.28],
+                [0.882, 0.663, 0.373],
+                [0.95, 0.76, 0.47],
+            );
+            let col = if grain < 0.33 {
+                mix(warm, base, grain * 3.0)
+            } else if grain < 0.66 {
+                mix(base, pale, (grain - 0.33) * 3.0)
+            } else {
+                mix(pale, warm, (grain - 0.66) * 3.0)
+            };
+            let brightness = 0.95 + 0.1 * (grain * 7.3).fract();
+            col.map(|c| c * brightness)
+        }
+        Species::Fire => {
+            let t = (rb / 50.0).clamp(0.0, 1.0);
+            [
+                lerp(0.4, 1.0, t),
+                lerp(0.08, 0.85, t * t),
+                lerp(0.02, 0.1, t * t * t),
+            ]
+        }
+        Species::Ghost if cell.rb == crate::elements::ghost::RB_EYE => [0.08, 0.06, 0.15],
+        Species::Smoke => {
+            let gray = lerp(0.12, 0.35, (rb / 100.0).clamp(0.0, 1.0));
+            [gray, gray, gray * 1.08]
+        }
+        _ => return ELEMENTS[cell.species as usize].color,
+    };
+    rgb.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+fn mix(a: [f32; 3], b: [f32; 3], t: f32) -> [f32; 3] {
+    [
+        lerp(a[0], b[0], t),
+        lerp(a[1], b[1], t),
+        lerp(a[2], b[2], t),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elements::element;
+
+    fn encode_rgba(width: u32, height: u32, pixels: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut encoder = png::Encoder::new(&mut out, width, height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(pixels).unwrap();
+        writer.finish().unwrap();
+        out
+    }
+
+    #[test]
+    fn palette_colours_are_unique() {
+        for (i, a) in ELEMENTS.iter().enumerate() {
+            for b in &ELEMENTS[i + 1..] {
+                assert_ne!(a.color, b.color, "{} and {} share a colour", a.name, b.name);
+            }
+        }
+    }
+
+    #[test]
+    fn flat_export_imports_back_to_the_same_species() {
+        let mut grid = Grid::new(6, 4);
+        for (i, def) in ELEMENTS.iter().enumerate() {
+            grid.cells[i] = Cell::new(def.species);
+        }
+        let loaded =
+            Grid::from_image(&grid.to_image(Shading::Flat), UnknownColors::Reject).unwrap();
+        let species = |g: &Grid| g.cells.iter().map(|c| c.species).collect::<Vec<_>>();
+        assert_eq!(species(&loaded), species(&grid));
+    }
+
+    #[test]
+    fn import_initializes_cells_like_set_cell() {
+        let [r, g, b] = element(Species::Fire).color;
+        let [sr, sg, sb] = element(Species::Ghost).color;
+        let png = encode_rgba(3, 1, &[r, g, b, 255, 1, 2, 3, 0, sr, sg, sb, 255]);
+        let grid = Grid::from_image(&png, UnknownColors::Reject).unwrap();
+
+        assert_eq!(grid.cells[0].species, Species::Fire);
+        assert!(grid.cells[0].rb >= 20, "fire needs a lifetime");
+        assert_eq!(grid.cells[1].species, Species::Empty);
+        assert_eq!(grid.cells[2].species, Species::Ghost);
+        assert_ne!(grid.cells[2].ra, 0, "ghosts need a group");
+
+        let png = encode_rgba(1, 1, &[r, g, b, 255]);
+        let grid = Grid::from_image(&png, UnknownColors::Reject).unwrap();
+        assert_eq!(grid.next_ghost_group, Grid::new(1, 1).next_ghost_group);
+    }
+
+    #[test]
+    fn unknown_colours_are_rejected_or_snapped() {
+        // Slightly off water.
+        let [r, g, b] = element(Species::Water).color;
+        let png = encode_rgba(1, 1, &[r + 3, g - 2, b, 255]);
+
+        let err = Grid::from_image(&png, UnknownColors::Reject).unwrap_err();
+        assert!(matches!(
+            err,
+            ImageError::UnknownColor { x: 0, y: 0, rgb } if rgb == [r + 3, g - 2, b]
+        ));
+        let grid = Grid::from_image(&png, UnknownColors::Nearest).unwrap();
+        assert_eq!(grid.cells[0].species, Species::Water);
+    }
+
+    #[test]
+    fn garbage_is_a_decode_error() {
+        let err = Grid::from_image(b"definitely not a png", UnknownColors::Nearest).unwrap_err();
+        assert!(matches!(err, ImageError::Decode(_)));
+    }
+
+    #[test]
+    fn huge_images_are_refused_before_allocating() {
+        // Patch a 1×1 PNG's header to claim 4096×4096 pixels.
+        let mut png = encode_rgba(1, 1, &[0, 0, 0, 0]);
+        png[16..24].copy_from_slice(&[0, 0, 16, 0, 0, 0, 16, 0]);
+        let crc = crc32(&png[12..29]);
+        png[29..33].copy_from_slice(&crc.to_be_bytes());
+
+        let err = Grid::from_image(&png, UnknownColors::Nearest).unwrap_err();
+        assert!(matches!(
+            err,
+            ImageError::TooLarge {
+                width: 4096,
+                height: 4096
+            }
+        ));
+    }
+
+    /// The CRC-32 PNG chunks carry.
+    fn crc32(bytes: &[u8]) -> u32 {
+        let mut crc = !0u32;
+        for &byte in bytes {
+            crc ^= u32::from(byte);
+            for _ in 0..8 {
+                crc = (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg());
+            }
+        }
+        !crc
+    }
+
+    #[test]
+    fn game_shading_follows_rb() {
+        let mut young = Cell::new(Species::Fire);
+        young.rb = 50;
+        let mut old = young;
+        old.rb = 2;
+        assert_ne!(game_color(young), game_color(old));
+        assert_eq!(game_color(Cell::wall()), element(Species::Wall).color);
+    }
+}
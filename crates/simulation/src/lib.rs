@@ -1,31 +1,126 @@
 //! Falling sand simulation engine.
 
 pub mod api;
+pub mod ascii;
+pub mod boundary;
 pub mod cell;
+pub mod chunk;
+pub mod compact;
 pub mod elements;
-
+pub mod hash;
+pub mod heat;
+pub mod history;
+#[cfg(feature = "image")]
+pub mod image;
+pub mod paint;
+#[cfg(feature = "parallel")]
+mod parallel;
+pub mod pattern;
+pub mod replay;
+pub mod resize;
+pub mod rng;
+pub mod snapshot;
+pub mod stamp;
+
+use boundary::{Boundaries, Boundary, Position};
 use cell::{Cell, Species};
+use chunk::{Chunk, DirtyRect, Tile, CHUNK_SIZE, PHASES, TILE_CHUNKS};
+use rng::Rng;
 use std::fmt;
+use std::ops::Range;
 use wasm_bindgen::prelude::*;
 
-/// 2D grid of cells. Out-of-bounds reads return Wall, writes are no-ops.
-#[derive(Debug)]
+/// 2D grid of cells. Out-of-bounds reads and writes follow `boundaries`;
+/// by default they read Wall and are dropped.
+///
+/// The grid is divided into `CHUNK_SIZE`×`CHUNK_SIZE` chunks with dirty
+/// rects; `tick` only visits cells near recent writes. Code that writes to
+/// `cells` directly instead of through `set` must call `wake_all` afterwards
+/// so the scheduler notices the change.
+#[derive(Clone, Debug)]
 pub struct Grid {
     pub width: usize,
     pub height: usize,
     pub cells: Vec<Cell>,
+    /// Number of ticks run so far. Never wraps in practice; use it for
+    /// replays, time display, scheduled events and network frame numbers.
+    pub tick_count: u64,
+    /// Low byte of `tick_count`, stamped into `Cell::clock` to mark cells
+    /// already updated this tick.
     pub generation: u8,
+    /// Source of all randomness in element updates and cell initialization.
+    pub rng: Rng,
+    /// Reactions between touching species, checked before element updates.
+    pub reactions: elements::Reactions,
+    /// Next ghost group ID handed out by `alloc_ghost_group` (1–255, wraps).
+    pub next_ghost_group: u8,
+    /// What lies past each edge; see the `boundary` module.
+    pub boundaries: Boundaries,
+    /// Temperature per cell; see the `heat` module.
+    heat: Vec<u8>,
+    /// Scratch buffer for the diffusion pass.
+    heat_next: Vec<u8>,
+    /// False once the field has settled at ambient with no emitters.
+    heat_active: bool,
+    chunks: Vec<Chunk>,
+    chunks_w: usize,
+    /// Edits recorded by `edit` while a stroke is open; see `history`.
+    edit_log: Option<history::EditLog>,
+}
+
+/// Chunks covering a `width`×`height` grid, row by row, all asleep.
+fn chunk_layout(width: usize, height: usize) -> Vec<Chunk> {
+    let chunks_w = width.div_ceil(CHUNK_SIZE);
+    let chunks_h = height.div_ceil(CHUNK_SIZE);
+    let mut chunks = Vec::with_capacity(chunks_w * chunks_h);
+    for cy in 0..chunks_h {
+        for cx in 0..chunks_w {
+            let min_x = cx * CHUNK_SIZE;
+            let min_y = cy * CHUNK_SIZE;
+            chunks.push(Chunk::new(DirtyRect {
+                min_x: min_x as i32,
+                min_y: min_y as i32,
+                max_x: ((min_x + CHUNK_SIZE).min(width) - 1) as i32,
+                max_y: ((min_y + CHUNK_SIZE).min(height) - 1) as i32,
+            }));
+        }
+    }
+    chunks
 }
 
 impl Grid {
     #[must_use]
     pub fn new(width: usize, height: usize) -> Self {
-        Self {
+        Self::with_seed(width, height, rng::DEFAULT_SEED)
+    }
+
+    /// Create an empty grid whose random stream starts from `seed`.
+    ///
+    /// Two grids built with the same seed and fed the same writes and ticks
+    /// stay bit-identical.
+    #[must_use]
+    pub fn with_seed(width: usize, height: usize, seed: u64) -> Self {
+        let mut grid = Self {
             width,
             height,
             cells: vec![Cell::empty(); width * height],
+            tick_count: 0,
             generation: 0,
-        }
+            rng: Rng::new(seed),
+            reactions: elements::Reactions::default(),
+            next_ghost_group: 1,
+            boundaries: Boundaries::default(),
+            heat: vec![heat::AMBIENT_HEAT; width * height],
+            heat_next: vec![heat::AMBIENT_HEAT; width * height],
+            heat_active: false,
+            chunks: chunk_layout(width, height),
+            chunks_w: width.div_ceil(CHUNK_SIZE),
+            edit_log: None,
+        };
+        // Start fully awake so cells written straight into `cells` before
+        // the first tick still get visited.
+        grid.wake_all();
+        grid
     }
 
     #[must_use]
@@ -33,49 +128,247 @@ impl Grid {
         x >= 0 && (x as usize) < self.width && y >= 0 && (y as usize) < self.height
     }
 
+    /// Cell at `(x, y)`, resolving out-of-bounds coordinates through
+    /// `boundaries`.
     #[must_use]
     pub fn get(&self, x: i32, y: i32) -> Cell {
         if self.in_bounds(x, y) {
-            self.cells[y as usize * self.width + x as usize]
-        } else {
-            Cell::new(Species::Wall)
+            return self.cells[y as usize * self.width + x as usize];
+        }
+        match self.locate(x, y) {
+            Position::Inside(x, y) => self.cells[y as usize * self.width + x as usize],
+            Position::Beyond(Boundary::Void) => Cell::empty(),
+            Position::Beyond(_) => Cell::wall(),
         }
     }
 
+    /// Write a cell and wake it and its 8 neighbours for scheduling (further
+    /// around water; see `wake_margin`).
+    ///
+    /// Out-of-bounds writes wrap or are dropped according to `boundaries`.
     pub fn set(&mut self, x: i32, y: i32, cell: Cell) {
-        if self.in_bounds(x, y) {
-            self.cells[y as usize * self.width + x as usize] = cell;
+        let (x, y) = if self.in_bounds(x, y) {
+            (x, y)
+        } else {
+            match self.locate(x, y) {
+                Position::Inside(x, y) => (x, y),
+                Position::Beyond(_) => return,
+            }
+        };
+        let i = y as usize * self.width + x as usize;
+        let old = self.cells[i].species;
+        self.cells[i] = cell;
+        self.heat_active |= elements::element(cell.species).heat_output > 0;
+        let margin = self.wake_margin(x, y, old, cell.species);
+        self.wake(DirtyRect {
+            min_x: x - margin,
+            min_y: y - margin,
+            max_x: x + margin,
+            max_y: y + margin,
+        });
+        self.wake_across_edges(x, y, margin);
+    }
+
+    /// How far from `(x, y)` a write replacing `old` with `new` can be seen.
+    ///
+    /// Water scans along bodies of water up to `elements::MAX_REACH` cells
+    /// away, reading only water and the cells touching it, so writes of,
+    /// over or next to water wake that far. Everything else only reads its
+    /// neighbours.
+    fn wake_margin(&self, x: i32, y: i32, old: Species, new: Species) -> i32 {
+        let near_water = old == Species::Water
+            || new == Species::Water
+            || (-1..=1)
+                .any(|dy| (-1..=1).any(|dx| self.get(x + dx, y + dy).species == Species::Water));
+        if near_water {
+            elements::MAX_REACH
+        } else {
+            1
+        }
+    }
+
+    /// Schedule every cell for the next tick.
+    ///
+    /// Needed after writing to `cells` directly, which bypasses dirty tracking.
+    pub fn wake_all(&mut self) {
+        for chunk in &mut self.chunks {
+            chunk.wake(chunk.bounds);
+        }
+        self.heat_active = true;
+    }
+
+    /// Schedule the cell at `(x, y)` for the next tick without writing it.
+    pub(crate) fn keep_awake(&mut self, x: i32, y: i32) {
+        let here = DirtyRect {
+            min_x: x,
+            min_y: y,
+            max_x: x,
+            max_y: y,
+        };
+        self.for_each_chunk_in(here, |chunk| chunk.wake_next(here));
+    }
+
+    /// Number of chunks with cells scheduled for the next tick.
+    #[must_use]
+    pub fn active_chunk_count(&self) -> usize {
+        self.chunks.iter().filter(|c| c.is_awake()).count()
+    }
+
+    /// Wake `rect` in every chunk it overlaps, for this tick and the next.
+    fn wake(&mut self, rect: DirtyRect) {
+        self.for_each_chunk_in(rect, |chunk| chunk.wake(rect));
+    }
+
+    /// Apply `f` to every chunk overlapping `rect` (clamped to the grid).
+    #[inline]
+    fn for_each_chunk_in(&mut self, rect: DirtyRect, mut f: impl FnMut(&mut Chunk)) {
+        if self.chunks.is_empty() {
+            return;
+        }
+        let max_x = self.width as i32 - 1;
+        let max_y = self.height as i32 - 1;
+        let cx0 = rect.min_x.clamp(0, max_x) as usize / CHUNK_SIZE;
+        let cx1 = rect.max_x.clamp(0, max_x) as usize / CHUNK_SIZE;
+        let cy0 = rect.min_y.clamp(0, max_y) as usize / CHUNK_SIZE;
+        let cy1 = rect.max_y.clamp(0, max_y) as usize / CHUNK_SIZE;
+        for cy in cy0..=cy1 {
+            for cx in cx0..=cx1 {
+                f(&mut self.chunks[cy * self.chunks_w + cx]);
+            }
         }
     }
 
     /// Advance the simulation by one tick.
     ///
-    /// Scans bottom-to-top, alternating horizontal direction each generation.
-    /// Skips Empty/Wall cells and cells already updated this generation (clock == generation).
+    /// The grid is covered by tiles of `TILE_CHUNKS`×`TILE_CHUNKS` chunks,
+    /// updated in four checkerboard phases by tile parity. Each tile scans
+    /// bottom-to-top, alternating horizontal direction each generation, and
+    /// draws from its own random stream forked from `rng`, so tiles of one
+    /// phase are independent and `par_tick` can run them on threads with the
+    /// same result. Within each row only the dirty span of each awake chunk
+    /// is visited, so sleeping chunks cost nothing and results match
+    /// visiting every cell. Skips Empty/Wall cells and cells already updated
+    /// this generation (clock == generation).
     pub fn tick(&mut self) {
-        self.generation = self.generation.wrapping_add(1);
+        let gen = self.begin_tick();
+        for phase in PHASES {
+            for tile in self.awake_tiles(phase) {
+                let tile_rng = self.rng.fork(tile.rng_key());
+                let grid_rng = std::mem::replace(&mut self.rng, tile_rng);
+                let rows = self.tile_rows(&tile);
+                self.scan(rows, tile.cols, gen);
+                self.rng = grid_rng;
+            }
+        }
+        self.end_tick();
+    }
+
+    /// Advance one tick as a `Universe` does: `tick`, then turn ghost eyes
+    /// toward `cursor` (see `update_ghost_eyes`).
+    pub fn tick_with_cursor(&mut self, cursor: Option<(i32, i32)>) {
+        self.tick();
+        // Only run expensive ghost eye tracking when ghosts exist.
+        if self.has_ghosts() {
+            self.update_ghost_eyes(cursor);
+        }
+    }
+
+    /// Bump the tick counter, roll chunk schedules over and move ghosts.
+    ///
+    /// Shared prelude of every tick schedule; returns the new generation.
+    fn begin_tick(&mut self) -> u8 {
+        self.tick_count += 1;
+        self.generation = self.tick_count as u8;
         let gen = self.generation;
 
+        for chunk in &mut self.chunks {
+            chunk.swap();
+        }
+
         // Only do ghost work if ghosts exist on the grid.
         if self.has_ghosts() {
-            self.move_ghosts(gen);
+            self.move_ghosts();
         }
+        gen
+    }
 
-        let w = self.width as i32;
-        let h = self.height as i32;
-        let scan_right = gen.is_multiple_of(2);
+    /// Run heat and step past the state tile streams were forked from, so
+    /// the next tick forks fresh ones.
+    fn end_tick(&mut self) {
+        self.update_heat();
+        self.rng.next_u64();
+    }
 
-        for y in (0..h).rev() {
-            if scan_right {
-                for x in 0..w {
-                    self.update_cell_at(x, y, gen);
-                }
-            } else {
-                for x in (0..w).rev() {
-                    self.update_cell_at(x, y, gen);
+    /// Tiles of `phase` with a chunk scheduled for the tick in progress.
+    pub(crate) fn awake_tiles(&self, (px, py): (usize, usize)) -> Vec<Tile> {
+        let chunks_h = self.chunks.len() / self.chunks_w.max(1);
+        let tiles_w = self.chunks_w.div_ceil(TILE_CHUNKS);
+        let tiles_h = chunks_h.div_ceil(TILE_CHUNKS);
+
+        let mut tiles = Vec::new();
+        for ty in (py..tiles_h).step_by(2) {
+            for tx in (px..tiles_w).step_by(2) {
+                let tile = Tile {
+                    cols: tx * TILE_CHUNKS..((tx + 1) * TILE_CHUNKS).min(self.chunks_w),
+                    rows: ty * TILE_CHUNKS..((ty + 1) * TILE_CHUNKS).min(chunks_h),
+                };
+                let awake = tile.rows.clone().any(|cy| {
+                    tile.cols
+                        .clone()
+                        .any(|cx| !self.chunks[cy * self.chunks_w + cx].current.is_empty())
+                });
+                if awake {
+                    tiles.push(tile);
                 }
             }
         }
+        tiles
+    }
+
+    /// Cell rows covered by `tile`.
+    pub(crate) fn tile_rows(&self, tile: &Tile) -> Range<i32> {
+        (tile.rows.start * CHUNK_SIZE) as i32..(tile.rows.end * CHUNK_SIZE).min(self.height) as i32
+    }
+
+    /// Update the dirty cells of `rows` within the chunk columns `chunk_cols`.
+    fn scan(&mut self, rows: Range<i32>, chunk_cols: Range<usize>, gen: u8) {
+        let scan_right = gen.is_multiple_of(2);
+        for y in rows.rev() {
+            let cy = y as usize / CHUNK_SIZE;
+            for i in chunk_cols.clone() {
+                let cx = if scan_right {
+                    i
+                } else {
+                    chunk_cols.start + chunk_cols.end - 1 - i
+                };
+                self.update_chunk_row(cy * self.chunks_w + cx, y, gen, scan_right);
+            }
+        }
+    }
+
+    /// Visit the dirty span of row `y` inside one chunk.
+    ///
+    /// The far end of the span is re-read every step because writes earlier
+    /// in the row can widen it.
+    #[inline]
+    fn update_chunk_row(&mut self, chunk: usize, y: i32, gen: u8, scan_right: bool) {
+        let rect = self.chunks[chunk].current;
+        if rect.is_empty() || !rect.contains_row(y) {
+            return;
+        }
+        if scan_right {
+            let mut x = rect.min_x;
+            while x <= self.chunks[chunk].current.max_x {
+                self.update_cell_at(x, y, gen);
+                x += 1;
+            }
+        } else {
+            let mut x = rect.max_x;
+            while x >= self.chunks[chunk].current.min_x {
+                self.update_cell_at(x, y, gen);
+                x -= 1;
+            }
+        }
     }
 
     /// Process a single cell during the tick scan.
@@ -86,6 +379,9 @@ impl Grid {
             return;
         }
         if cell.clock == gen {
+            // A stale clock that happens to match this generation skips the
+            // cell for a tick; keep it scheduled so it isn't left asleep.
+            self.keep_awake(x, y);
             return;
         }
         let species = cell.species;
@@ -93,15 +389,30 @@ impl Grid {
         elements::update_cell(species, &mut sand_api);
     }
 
+    /// Allocate a new ghost group ID (1–255, wraps past 0).
+    pub fn alloc_ghost_group(&mut self) -> u8 {
+        let id = self.next_ghost_group;
+        self.next_ghost_group = self.next_ghost_group.wrapping_add(1);
+        // Skip 0 so group 0 is never used (reserve for "no group").
+        if self.next_ghost_group == 0 {
+            self.next_ghost_group = 1;
+        }
+        id
+    }
+
     /// Move all ghost cells one step in a shared direction.
     ///
     /// Collects ghost positions, checks that every destination is empty,
-    /// then performs the bulk swap. If any ghost cell is blocked, none move
-    /// — this keeps the shape perfectly intact.
-    fn move_ghosts(&mut self, gen: u8) {
+    /// then moves the group in bulk. If any ghost cell is blocked, none move
+    /// — this keeps the shape perfectly intact. Destinations resolve through
+    /// `boundaries`: groups wrap around Wrap edges and lose the cells that
+    /// step past a Void edge.
+    fn move_ghosts(&mut self) {
         use crate::elements::ghost::{shared_direction, MOVE_DIVISOR};
 
-        if gen % MOVE_DIVISOR != 0 {
+        let tick = self.tick_count;
+        let gen = self.generation;
+        if !tick.is_multiple_of(MOVE_DIVISOR) {
             return;
         }
 
@@ -132,7 +443,7 @@ impl Grid {
         // Process each group independently.
         for group_id in groups {
             // Each group gets its own direction by mixing group_id into the hash.
-            let (dx, dy) = shared_direction(gen, group_id);
+            let (dx, dy) = shared_direction(tick, group_id);
 
             let group_cells: Vec<(i32, i32)> = ghost_positions
                 .iter()
@@ -140,38 +451,41 @@ impl Grid {
                 .map(|&(x, y, _)| (x, y))
                 .collect();
 
-            // Check that every destination is either empty or same-group ghost.
-            let can_move = group_cells.iter().all(|&(x, y)| {
-                let nx = x + dx;
-                let ny = y + dy;
-                if !self.in_bounds(nx, ny) {
-                    return false;
-                }
-                let dest = self.get(nx, ny);
-                dest.species == Species::Empty
-                    || (dest.species == Species::Ghost && dest.ra == group_id)
-            });
+            // Resolve every destination through the edges: it must be
+            // empty, part of this group, or past a Void edge (where that
+            // cell drops off the grid).
+            let mut moves = Vec::with_capacity(group_cells.len());
+            let can_move = group_cells
+                .iter()
+                .all(|&(x, y)| match self.locate(x + dx, y + dy) {
+                    Position::Inside(nx, ny) => {
+                        let dest = self.get(nx, ny);
+                        moves.push((x, y, Some((nx, ny))));
+                        dest.species == Species::Empty
+                            || (dest.species == Species::Ghost && dest.ra == group_id)
+                    }
+                    Position::Beyond(edge) => {
+                        moves.push((x, y, None));
+                        edge == Boundary::Void
+                    }
+                });
 
             if !can_move {
                 continue;
             }
 
-            // Sort so we process cells furthest in movement direction first.
-            let mut positions = group_cells;
-            positions.sort_by(|a, b| {
-                let score_a: i32 = a.0 * dx + a.1 * dy;
-                let score_b: i32 = b.0 * dx + b.1 * dy;
-                score_b.cmp(&score_a)
-            });
-
-            for &(x, y) in &positions {
-                let nx = x + dx;
-                let ny = y + dy;
-                let mut ghost_cell = self.get(x, y);
-                ghost_cell.clock = gen;
-                let dest_cell = self.get(nx, ny);
-                self.set(x, y, dest_cell);
-                self.set(nx, ny, ghost_cell);
+            // Lift the whole group, then put it down shifted. Every
+            // destination was empty or held the group, so nothing else is
+            // overwritten, and wrapped groups need no special ordering.
+            let lifted: Vec<Cell> = moves.iter().map(|&(x, y, _)| self.get(x, y)).collect();
+            for &(x, y, _) in &moves {
+                self.set(x, y, Cell::empty());
+            }
+            for (&(_, _, dest), mut ghost_cell) in moves.iter().zip(lifted) {
+                if let Some((nx, ny)) = dest {
+                    ghost_cell.clock = gen;
+                    self.set(nx, ny, ghost_cell);
+                }
             }
         }
     }
@@ -188,6 +502,10 @@ impl Grid {
     /// Within each zone, a 2×2 block of cells is marked as active eyes
     /// (`RB_EYE`), positioned toward the look direction. The rest of
     /// the zone renders as body color.
+    #[expect(
+        clippy::similar_names,
+        reason = "x/y coordinate pairs are named in parallel"
+    )]
     pub fn update_ghost_eyes(&mut self, cursor: Option<(i32, i32)>) {
         use crate::elements::ghost::{RB_EYE, RB_EYE_ZONE};
 
@@ -196,8 +514,7 @@ impl Grid {
 
         // Collect ghost cells grouped by ra (group ID) using a fixed
         // 256-slot array instead of HashMap to avoid allocation.
-        let mut group_data: [Vec<(i32, i32, u8)>; 256] =
-            std::array::from_fn(|_| Vec::new());
+        let mut group_data: [Vec<(i32, i32, u8)>; 256] = std::array::from_fn(|_| Vec::new());
         let mut has_any = false;
         for y in 0..h {
             for x in 0..w {
@@ -218,9 +535,11 @@ impl Grid {
                 continue;
             }
             // Compute group center.
-            let (sum_x, sum_y, count) = cells.iter().fold((0i64, 0i64, 0i64), |(sx, sy, c), &(x, y, _)| {
-                (sx + x as i64, sy + y as i64, c + 1)
-            });
+            let (sum_x, sum_y, count) = cells
+                .iter()
+                .fold((0i64, 0i64, 0i64), |(sx, sy, c), &(x, y, _)| {
+                    (sx + i64::from(x), sy + i64::from(y), c + 1)
+                });
             if count == 0 {
                 continue;
             }
@@ -265,10 +584,12 @@ impl Grid {
                 }
 
                 // Find bounding box of this eye zone.
-                let min_x = eye_cluster.iter().map(|&(x, _)| x).min().unwrap();
-                let max_x = eye_cluster.iter().map(|&(x, _)| x).max().unwrap();
-                let min_y = eye_cluster.iter().map(|&(_, y)| y).min().unwrap();
-                let max_y = eye_cluster.iter().map(|&(_, y)| y).max().unwrap();
+                let (min_x, max_x, min_y, max_y) = eye_cluster.iter().fold(
+                    (i32::MAX, i32::MIN, i32::MAX, i32::MIN),
+                    |(lo_x, hi_x, lo_y, hi_y), &(x, y)| {
+                        (lo_x.min(x), hi_x.max(x), lo_y.min(y), hi_y.max(y))
+                    },
+                );
 
                 let zone_w = max_x - min_x + 1;
                 let zone_h = max_y - min_y + 1;
@@ -292,7 +613,7 @@ impl Grid {
                 ];
 
                 // Mark cells: eye positions get RB_EYE, rest get RB_EYE_ZONE.
-                for &(x, y) in eye_cluster.iter() {
+                for &(x, y) in eye_cluster {
                     let mut cell = self.get(x, y);
                     cell.rb = if eye_set.contains(&(x, y)) {
                         RB_EYE
@@ -318,10 +639,10 @@ pub struct Universe {
     /// Two bytes per cell (species, rb), length = width × height × 2.
     /// Used by the GPU shader for color variation (fire gradient, smoke fade).
     cell_render_buffer: Vec<u8>,
-    /// Monotonically increasing counter for ghost group IDs (1–255, wraps).
-    next_ghost_group: u8,
     /// Cursor grid position for ghost eye tracking. `None` = no cursor visible.
     cursor: Option<(i32, i32)>,
+    /// Undo and redo stacks for user strokes.
+    history: history::History,
 }
 
 impl fmt::Debug for Universe {
@@ -329,7 +650,7 @@ impl fmt::Debug for Universe {
         f.debug_struct("Universe")
             .field("grid", &self.grid)
             .field("species_buffer_len", &self.species_buffer.len())
-            .finish()
+            .finish_non_exhaustive()
     }
 }
 
@@ -339,25 +660,52 @@ impl Universe {
     #[wasm_bindgen(constructor)]
     #[must_use]
     pub fn new(width: usize, height: usize) -> Self {
-        let grid = Grid::new(width, height);
-        let species_buffer = vec![Species::Empty as u8; width * height];
-        let cell_render_buffer = vec![0u8; width * height * 2];
-        Self {
-            grid,
-            species_buffer,
-            cell_render_buffer,
-            next_ghost_group: 1,
-            cursor: None,
-        }
+        Self::new_with_seed(width, height, rng::DEFAULT_SEED)
+    }
+
+    /// Create a new universe whose random stream starts from `seed`.
+    ///
+    /// Identical seeds and identical calls give bit-identical worlds.
+    #[must_use]
+    pub fn new_with_seed(width: usize, height: usize, seed: u64) -> Self {
+        Self::from_grid(Grid::with_seed(width, height, seed))
+    }
+
+    /// Create a new universe with the given behaviour at each edge.
+    #[must_use]
+    pub fn new_with_boundaries(
+        width: usize,
+        height: usize,
+        seed: u64,
+        left: Boundary,
+        right: Boundary,
+        top: Boundary,
+        bottom: Boundary,
+    ) -> Self {
+        let boundaries = Boundaries {
+            left,
+            right,
+            top,
+            bottom,
+        };
+        Self::from_grid(Grid::with_boundaries(width, height, seed, boundaries))
+    }
+
+    /// Number of ticks run since this universe was created.
+    #[must_use]
+    pub fn tick_count(&self) -> u64 {
+        self.grid.tick_count
+    }
+
+    /// Stable hash of the simulation state; see `Grid::state_hash`.
+    #[must_use]
+    pub fn state_hash(&self) -> u64 {
+        self.grid.state_hash()
     }
 
     /// Advance the simulation by one tick and sync the render buffer.
     pub fn tick(&mut self) {
-        self.grid.tick();
-        // Only run expensive ghost eye tracking when ghosts exist.
-        if self.grid.has_ghosts() {
-            self.grid.update_ghost_eyes(self.cursor);
-        }
+        self.grid.tick_with_cursor(self.cursor);
         self.sync_render_buffers();
     }
 
@@ -380,57 +728,198 @@ impl Universe {
         if x >= self.grid.width || y >= self.grid.height {
             return;
         }
-        let s = match species {
-            0 => Species::Empty,
-            1 => Species::Sand,
-            2 => Species::Water,
-            3 => Species::Wall,
-            4 => Species::Fire,
-            5 => Species::Ghost,
-            6 => Species::Smoke,
-            _ => return, // unknown species — ignore
+        let Some(s) = Species::from_id(species) else {
+            return; // unknown species — ignore
         };
 
         // Eraser (Empty) always overwrites; other elements only fill empty cells.
-        if s != Species::Empty
-            && self.grid.get(x as i32, y as i32).species != Species::Empty
-        {
+        if s != Species::Empty && self.grid.get(x as i32, y as i32).species != Species::Empty {
             return;
         }
 
-        let mut cell = Cell::new(s);
-        // Sand uses rb for per-grain color variation (0–255).
-        if s == Species::Sand {
-            cell.rb = (x.wrapping_mul(137) ^ y.wrapping_mul(269)).wrapping_add(x.wrapping_add(y)) as u8;
+        let cell = elements::spawn_cell(s, &mut self.grid.rng);
+        self.grid.edit(x as i32, y as i32, cell);
+    }
+
+    /// Paint a filled circle of radius `r` around `(cx, cy)` in one call.
+    ///
+    /// `mode` picks which cells are overwritten; `target` is the species
+    /// `PaintMode::ReplaceSpecies` replaces and is ignored by other modes.
+    /// Unknown species ids paint nothing. See the `paint` module.
+    pub fn paint_circle(
+        &mut self,
+        cx: i32,
+        cy: i32,
+        r: u32,
+        species: u8,
+        mode: paint::PaintMode,
+        target: u8,
+    ) {
+        if let Some(brush) = Self::brush(species, mode, target) {
+            self.grid.paint_circle(cx, cy, r, brush);
         }
-        // Fire starts with a lifetime counter so it doesn't vanish instantly.
-        if s == Species::Fire {
-            // Randomize lifetime using position as cheap entropy.
-            cell.rb = 20_u8.wrapping_add(((x ^ y) % 30) as u8);
-            cell.ra = (x.wrapping_mul(7) ^ y.wrapping_mul(13)) as u8;
+    }
+
+    /// Paint a line from `(x0, y0)` to `(x1, y1)` with a round brush of
+    /// radius `radius`. Arguments as for `paint_circle`.
+    // `allow`, not `expect`: wasm_bindgen copies the attribute onto its
+    // generated shim, where the lint doesn't fire.
+    #[allow(
+        clippy::too_many_arguments,
+        reason = "flat arguments keep the call cheap from JS"
+    )]
+    pub fn paint_line(
+        &mut self,
+        x0: i32,
+        y0: i32,
+        x1: i32,
+        y1: i32,
+        radius: u32,
+        species: u8,
+        mode: paint::PaintMode,
+        target: u8,
+    ) {
+        if let Some(brush) = Self::brush(species, mode, target) {
+            self.grid.paint_line(x0, y0, x1, y1, radius, brush);
         }
-        // Water uses ra parity as persistent flow direction. Seed from
-        // position so adjacent particles start with varied directions.
-        if s == Species::Water {
-            cell.ra = (x ^ y) as u8;
+    }
+
+    /// Paint the filled rectangle with inclusive corners `(x0, y0)` and
+    /// `(x1, y1)`. Arguments as for `paint_circle`.
+    #[allow(
+        clippy::too_many_arguments,
+        reason = "flat arguments keep the call cheap from JS"
+    )]
+    pub fn paint_rect(
+        &mut self,
+        x0: i32,
+        y0: i32,
+        x1: i32,
+        y1: i32,
+        species: u8,
+        mode: paint::PaintMode,
+        target: u8,
+    ) {
+        if let Some(brush) = Self::brush(species, mode, target) {
+            self.grid.paint_rect(x0, y0, x1, y1, brush);
+        }
+    }
+
+    /// Fill the connected same-species region around `(x, y)` with
+    /// `species`, changing at most `paint::FLOOD_FILL_LIMIT` cells. Returns
+    /// the number changed; unknown species ids change nothing.
+    pub fn flood_fill(
+        &mut self,
+        x: i32,
+        y: i32,
+        species: u8,
+        connectivity: paint::Connectivity,
+    ) -> u32 {
+        let Some(species) = Species::from_id(species) else {
+            return 0;
+        };
+        self.grid
+            .flood_fill(x, y, species, connectivity, paint::FLOOD_FILL_LIMIT) as u32
+    }
+
+    /// Turn every `from` cell in the rectangle with inclusive corners
+    /// `(x0, y0)` and `(x1, y1)` into a freshly initialized `to` cell.
+    /// Unknown species ids change nothing.
+    pub fn replace_species(&mut self, from: u8, to: u8, x0: i32, y0: i32, x1: i32, y1: i32) {
+        if let (Some(from), Some(to)) = (Species::from_id(from), Species::from_id(to)) {
+            self.grid.replace_species(from, to, x0, y0, x1, y1);
         }
-        // Smoke starts with a lifetime for fade-out.
-        if s == Species::Smoke {
-            cell.rb = 80_u8.wrapping_add(((x ^ y) % 120) as u8);
-            cell.ra = (x ^ y) as u8;
+    }
+
+    /// Place the built-in stamp `name` (such as `"ghost"`) centred on
+    /// `(x, y)`, all or nothing; see the `stamp` module.
+    ///
+    /// # Errors
+    ///
+    /// Throws if there is no such stamp or not enough empty room.
+    #[wasm_bindgen(js_name = place_stamp)]
+    pub fn place_stamp_js(&mut self, name: &str, x: i32, y: i32) -> Result<(), JsError> {
+        self.place_stamp(name, x, y).map_err(JsError::from)
+    }
+
+    /// Copy the rectangle with inclusive corners `(x0, y0)` and `(x1, y1)`
+    /// into a `Pattern`; see the `pattern` module.
+    #[must_use]
+    pub fn copy_region(&self, x0: i32, y0: i32, x1: i32, y1: i32) -> pattern::Pattern {
+        self.grid.copy_region(x0, y0, x1, y1)
+    }
+
+    /// Copy a rectangle as `copy_region` does, then empty it.
+    pub fn cut_region(&mut self, x0: i32, y0: i32, x1: i32, y1: i32) -> pattern::Pattern {
+        self.grid.cut_region(x0, y0, x1, y1)
+    }
+
+    /// Paste `pattern` with its top-left corner at `(x, y)`. Pasted ghosts
+    /// get fresh group IDs.
+    pub fn paste_pattern(
+        &mut self,
+        x: i32,
+        y: i32,
+        pattern: &pattern::Pattern,
+        mode: pattern::PasteMode,
+    ) {
+        self.grid.paste_pattern(x, y, pattern, mode);
+    }
+
+    /// Start a stroke: edits until `end_stroke` are undone and redone
+    /// together. An already open stroke is ended first. See `history`.
+    pub fn begin_stroke(&mut self) {
+        if let Some(log) = self.grid.start_edit_log() {
+            self.history.push(log);
         }
-        self.grid.set(x as i32, y as i32, cell);
+    }
+
+    /// Finish the open stroke, if any, and add it to the undo history.
+    pub fn end_stroke(&mut self) {
+        if let Some(log) = self.grid.take_edit_log() {
+            self.history.push(log);
+        }
+    }
+
+    /// Revert the latest stroke, ending an open one first. Cells that have
+    /// changed since, and ghost groups that have moved, are left alone; see
+    /// `history`. Returns false if there was nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        self.end_stroke();
+        self.history.undo(&mut self.grid)
+    }
+
+    /// Reapply the latest undone stroke. Returns false if there was
+    /// nothing to redo.
+    pub fn redo(&mut self) -> bool {
+        self.end_stroke();
+        self.history.redo(&mut self.grid)
+    }
+
+    #[must_use]
+    pub fn can_undo(&self) -> bool {
+        self.history.can_undo()
+    }
+
+    #[must_use]
+    pub fn can_redo(&self) -> bool {
+        self.history.can_redo()
+    }
+
+    /// Cap the memory held by undo history at `bytes`, forgetting the
+    /// oldest strokes beyond it.
+    pub fn set_history_limit(&mut self, bytes: usize) {
+        self.history.set_limit(bytes);
+    }
+
+    /// Forget all undo and redo history.
+    pub fn clear_history(&mut self) {
+        self.history.clear();
     }
 
     /// Allocate a new ghost group ID (1–255, wraps past 0).
     pub fn alloc_ghost_group(&mut self) -> u8 {
-        let id = self.next_ghost_group;
-        self.next_ghost_group = self.next_ghost_group.wrapping_add(1);
-        // Skip 0 so group 0 is never used (reserve for "no group").
-        if self.next_ghost_group == 0 {
-            self.next_ghost_group = 1;
-        }
-        id
+        self.grid.alloc_ghost_group()
     }
 
     /// Place a ghost cell with a specific group ID stored in `ra`.
@@ -446,7 +935,83 @@ impl Universe {
         let mut cell = Cell::new(Species::Ghost);
         cell.ra = group;
         cell.rb = rb;
-        self.grid.set(x as i32, y as i32, cell);
+        self.grid.edit(x as i32, y as i32, cell);
+    }
+
+    /// Add or replace the reaction between species `a` and `b`.
+    ///
+    /// `a` turns into `becomes` and `b` into `other_becomes` with `chance`
+    /// percent probability per check. A non-zero `spawn` also places that
+    /// species in an empty cell above. Returns false, changing nothing, if
+    /// any id is unknown.
+    pub fn set_reaction(
+        &mut self,
+        a: u8,
+        b: u8,
+        chance: u8,
+        becomes: u8,
+        other_becomes: u8,
+        spawn: u8,
+    ) -> bool {
+        let ids = [a, b, becomes, other_becomes, spawn].map(Species::from_id);
+        let [Some(a), Some(b), Some(becomes), Some(other_becomes), Some(spawn)] = ids else {
+            return false;
+        };
+        let mut reaction = elements::Reaction::new(chance, becomes, other_becomes);
+        if spawn != Species::Empty {
+            reaction = reaction.spawning(spawn);
+        }
+        self.grid.reactions.insert(a, b, reaction);
+        // Settled cells are asleep and would never check the new entry.
+        self.grid.wake_all();
+        true
+    }
+
+    /// Remove the reaction between species `a` and `b`. Unknown ids are ignored.
+    pub fn clear_reaction(&mut self, a: u8, b: u8) {
+        if let (Some(a), Some(b)) = (Species::from_id(a), Species::from_id(b)) {
+            self.grid.reactions.remove(a, b);
+            self.grid.wake_all();
+        }
+    }
+
+    /// Serialize the whole world into a binary snapshot (see `snapshot`).
+    #[must_use]
+    pub fn save(&self) -> Vec<u8> {
+        self.grid.to_snapshot()
+    }
+
+    /// Replace the world with one saved by `save`.
+    ///
+    /// Dimensions may differ from the current world; the render buffers are
+    /// resized to match. The saved reaction table replaces the current
+    /// one. On error the current world is left untouched.
+    ///
+    /// # Errors
+    ///
+    /// Throws if the bytes are not a valid snapshot.
+    #[wasm_bindgen(js_name = load)]
+    pub fn load_js(&mut self, bytes: &[u8]) -> Result<(), JsError> {
+        self.load(bytes).map_err(JsError::from)
+    }
+
+    /// Serialize the world in the compact run-length format (see `compact`),
+    /// optionally deflate-compressed. Suited to URLs, storage and network
+    /// join messages.
+    #[must_use]
+    pub fn save_compact(&self, compress: bool) -> Vec<u8> {
+        self.grid.to_compact(compress)
+    }
+
+    /// Replace the world, reaction table included, with one saved by
+    /// `save_compact`.
+    ///
+    /// # Errors
+    ///
+    /// Throws if the bytes are not a valid compact encoding.
+    #[wasm_bindgen(js_name = load_compact)]
+    pub fn load_compact_js(&mut self, bytes: &[u8]) -> Result<(), JsError> {
+        self.load_compact(bytes).map_err(JsError::from)
     }
 
     /// Pointer to the species-only byte buffer for GPU texture upload.
@@ -455,6 +1020,15 @@ impl Universe {
         self.species_buffer.as_ptr()
     }
 
+    /// Pointer to the per-cell temperature buffer, for a thermal view.
+    ///
+    /// Layout matches `species_ptr`: one byte per cell, row-major.
+    /// [`heat::AMBIENT_HEAT`] is room temperature.
+    #[must_use]
+    pub fn heat_ptr(&self) -> *const u8 {
+        self.grid.heat.as_ptr()
+    }
+
     /// Pointer to the 2-byte-per-cell render buffer (species, rb).
     ///
     /// Layout: `[species_0, rb_0, species_1, rb_1, ...]`
@@ -473,9 +1047,94 @@ impl Universe {
     pub fn height(&self) -> usize {
         self.grid.height
     }
+
+    /// Change the world to `width`×`height`, keeping existing content
+    /// placed according to `anchor`; see the `resize` module.
+    ///
+    /// The render buffers are reallocated, so JS must fetch `species_ptr`,
+    /// `cell_render_ptr` and `heat_ptr` again and rebuild its views and
+    /// textures at the new size. Undo history is cleared, and the cursor
+    /// moves with the content.
+    pub fn resize(&mut self, width: usize, height: usize, anchor: resize::Anchor) {
+        let (ox, oy) = anchor.offset((self.grid.width, self.grid.height), (width, height));
+        self.cursor = self.cursor.map(|(x, y)| (x + ox, y + oy));
+        self.grid.resize(width, height, anchor);
+        self.history.clear();
+        let cells = width * height;
+        self.species_buffer = vec![Species::Empty as u8; cells];
+        self.cell_render_buffer = vec![0u8; cells * 2];
+        self.sync_render_buffers();
+    }
 }
 
 impl Universe {
+    /// Wrap `grid`, sizing and filling the render buffers to match it.
+    fn from_grid(grid: Grid) -> Self {
+        let cells = grid.width * grid.height;
+        let mut universe = Self {
+            grid,
+            species_buffer: vec![Species::Empty as u8; cells],
+            cell_render_buffer: vec![0u8; cells * 2],
+            cursor: None,
+            history: history::History::default(),
+        };
+        universe.sync_render_buffers();
+        universe
+    }
+
+    /// Replace the world, edge boundaries and reactions included, with one
+    /// saved by `save`, keeping the cursor. Undo history is cleared.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`snapshot::SnapshotError`] if the bytes are not a valid
+    /// snapshot; the current world is left untouched.
+    pub fn load(&mut self, bytes: &[u8]) -> Result<(), snapshot::SnapshotError> {
+        self.replace_grid(Grid::from_snapshot(bytes)?);
+        Ok(())
+    }
+
+    /// Replace the world, edge boundaries and reactions included, with one
+    /// saved by `save_compact`, keeping the cursor. Undo history is
+    /// cleared.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`snapshot::SnapshotError`] if the bytes are not a valid
+    /// compact encoding; the current world is left untouched.
+    pub fn load_compact(&mut self, bytes: &[u8]) -> Result<(), snapshot::SnapshotError> {
+        self.replace_grid(Grid::from_compact(bytes)?);
+        Ok(())
+    }
+
+    /// Place the built-in stamp `name` centred on `(x, y)`, writing every
+    /// cell or none; see the `stamp` module.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`stamp::StampError`] if there is no such stamp or any of
+    /// its cells would land off the grid or on material.
+    pub fn place_stamp(&mut self, name: &str, x: i32, y: i32) -> Result<(), stamp::StampError> {
+        self.grid.place_stamp(name, x, y)
+    }
+
+    /// Swap in a loaded `grid`, keeping the cursor and history limit.
+    fn replace_grid(&mut self, grid: Grid) {
+        let (cursor, limit) = (self.cursor, self.history.limit());
+        *self = Self::from_grid(grid);
+        self.cursor = cursor;
+        self.history.set_limit(limit);
+    }
+
+    /// The brush for a `paint_*` call, or `None` if either id is unknown.
+    fn brush(species: u8, mode: paint::PaintMode, target: u8) -> Option<paint::Brush> {
+        Some(paint::Brush {
+            species: Species::from_id(species)?,
+            mode,
+            target: Species::from_id(target)?,
+        })
+    }
+
     /// Sync both species buffer and cell render buffer in a single pass.
     fn sync_render_buffers(&mut self) {
         for (i, cell) in self.grid.cells.iter().enumerate() {
@@ -487,23 +1146,12 @@ impl Universe {
     }
 }
 
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::elements::registry::arb_species;
     use proptest::prelude::*;
 
-    fn arb_species() -> impl Strategy<Value = Species> {
-        prop_oneof![
-            Just(Species::Empty),
-            Just(Species::Sand),
-            Just(Species::Water),
-            Just(Species::Wall),
-            Just(Species::Fire),
-            Just(Species::Ghost),
-        ]
-    }
-
     fn arb_cell() -> impl Strategy<Value = Cell> {
         (arb_species(), any::<u8>(), any::<u8>(), any::<u8>()).prop_map(
             |(species, ra, rb, clock)| Cell {
@@ -521,6 +1169,7 @@ mod tests {
         assert_eq!(grid.width, 256);
         assert_eq!(grid.height, 256);
         assert_eq!(grid.cells.len(), 65536);
+        assert_eq!(grid.tick_count, 0);
         assert_eq!(grid.generation, 0);
         for cell in &grid.cells {
             assert_eq!(*cell, Cell::empty());
@@ -611,6 +1260,7 @@ mod tests {
             for _ in 0..n {
                 grid.tick();
             }
+            prop_assert_eq!(grid.tick_count, u64::from(n));
             prop_assert_eq!(grid.generation, (n % 256) as u8);
         }
     }
@@ -641,13 +1291,113 @@ mod tests {
         }
     }
 
+    #[test]
+    fn settled_grid_puts_chunks_to_sleep() {
+        let mut grid = Grid::new(64, 64);
+        assert_eq!(grid.active_chunk_count(), 4);
+        for x in 0..64 {
+            grid.set(x, 63, Cell::new(Species::Sand));
+        }
+        grid.tick();
+        grid.tick();
+        assert_eq!(grid.active_chunk_count(), 0);
+    }
+
+    #[test]
+    fn settled_water_puts_chunks_to_sleep() {
+        let mut grid = Grid::new(64, 64);
+        for y in 40..64 {
+            for x in 0..64 {
+                grid.set(x, y, Cell::new(Species::Water));
+            }
+        }
+        // A few ticks to flip every blocked cell once.
+        for _ in 0..3 {
+            grid.tick();
+        }
+        assert_eq!(grid.active_chunk_count(), 0);
+
+        grid.set(10, 63, Cell::empty());
+        grid.tick();
+        assert!(grid.active_chunk_count() > 0, "a hole wakes the pool");
+    }
+
+    #[test]
+    fn resting_lava_puts_chunks_to_sleep() {
+        let mut grid = Grid::new(64, 64);
+        for y in 56..64 {
+            for x in 0..64 {
+                grid.set(x, y, Cell::new(Species::Lava));
+            }
+        }
+        for _ in 0..3 {
+            grid.tick();
+        }
+        assert_eq!(grid.active_chunk_count(), 0);
+    }
+
+    #[test]
+    fn write_on_chunk_edge_wakes_neighbour_chunk() {
+        let mut grid = Grid::new(64, 64);
+        grid.tick();
+        assert_eq!(grid.active_chunk_count(), 0);
+
+        // (31, 31) is the corner of chunk (0, 0); its margin reaches
+        // chunks (1, 0), (0, 1) and (1, 1).
+        grid.set(31, 31, Cell::wall());
+        assert_eq!(grid.active_chunk_count(), 4);
+    }
+
+    #[test]
+    fn sleeping_sand_falls_when_support_is_removed() {
+        let mut grid = Grid::new(64, 64);
+        for x in 0..64 {
+            grid.set(x, 63, Cell::wall());
+        }
+        grid.set(10, 62, Cell::new(Species::Sand));
+        grid.tick();
+        grid.tick();
+        assert_eq!(grid.active_chunk_count(), 0);
+
+        grid.set(10, 63, Cell::empty());
+        grid.tick();
+        assert_eq!(grid.get(10, 63).species, Species::Sand);
+    }
+
+    // Dirty-rect scheduling must give the same result as visiting every cell.
+    proptest! {
+        #[test]
+        fn prop_chunked_tick_matches_full_scan(
+            cells in proptest::collection::vec(
+                prop_oneof![4 => Just(Species::Empty), 3 => arb_species()],
+                48 * 40,
+            ),
+            ticks in 1u32..40,
+            boundaries in boundary::tests::arb_boundaries(),
+        ) {
+            let mut chunked = Grid::with_boundaries(48, 40, rng::DEFAULT_SEED, boundaries);
+            for (i, &sp) in cells.iter().enumerate() {
+                chunked.cells[i] = elements::spawn_cell(sp, &mut chunked.rng);
+            }
+            let mut full = chunked.clone();
+
+            for _ in 0..ticks {
+                chunked.tick();
+                full.wake_all();
+                full.tick();
+            }
+            prop_assert_eq!(&chunked.cells, &full.cells);
+            prop_assert_eq!(&chunked.heat, &full.heat);
+        }
+    }
+
     // Feature: single-player-simulation-mvp, Property 14: Species buffer matches grid state
     // **Validates: Requirements 6.5**
     proptest! {
         #[test]
         fn prop_species_buffer_matches_grid_state(
             placements in proptest::collection::vec(
-                (0usize..256, 0usize..256, 0u8..7),
+                (0usize..256, 0usize..256, 0..elements::ELEMENTS.len() as u8),
                 0..50,
             ),
             ticks in 1u32..10,
@@ -728,6 +1478,115 @@ mod tests {
         assert_eq!(universe.grid.get(3, 3).species, Species::Water);
     }
 
+    #[test]
+    fn tick_count_keeps_counting_past_clock_wrap() {
+        let mut universe = Universe::new(8, 8);
+        for _ in 0..300 {
+            universe.tick();
+        }
+        assert_eq!(universe.tick_count(), 300);
+        assert_eq!(universe.grid.generation, 300u64 as u8);
+    }
+
+    #[test]
+    fn same_seed_gives_identical_worlds() {
+        let run = |seed: u64| {
+            let mut universe = Universe::new_with_seed(64, 64, seed);
+            for x in 10..50 {
+                universe.set_cell(x, 5, 1); // Sand
+                universe.set_cell(x, 10, 2); // Water
+                universe.set_cell(x, 60, 4); // Fire
+            }
+            for _ in 0..300 {
+                universe.tick();
+            }
+            universe.grid.cells
+        };
+        assert_eq!(run(7), run(7));
+        assert_ne!(run(7), run(8));
+    }
+
+    #[test]
+    fn universe_save_and_load_resume_the_same_run() {
+        let mut original = Universe::new_with_seed(48, 32, 5);
+        for x in 4..44 {
+            original.set_cell(x, 2, 1); // Sand
+            original.set_cell(x, 6, 2); // Water
+        }
+        let group = original.alloc_ghost_group();
+        original.set_ghost(20, 20, group, 0);
+        for _ in 0..15 {
+            original.tick();
+        }
+
+        let mut loaded = Universe::new(4, 4);
+        loaded.load(&original.save()).unwrap();
+        assert_eq!(loaded.width(), 48);
+        assert_eq!(loaded.species_buffer, original.species_buffer);
+        assert_eq!(loaded.alloc_ghost_group(), original.alloc_ghost_group());
+        for _ in 0..30 {
+            original.tick();
+            loaded.tick();
+        }
+        assert_eq!(loaded.grid.cells, original.grid.cells);
+        assert_eq!(loaded.cell_render_buffer, original.cell_render_buffer);
+
+        let before = loaded.save();
+        assert!(loaded.load(&before[..10]).is_err());
+        assert_eq!(loaded.save(), before);
+
+        let mut compact = Universe::new(4, 4);
+        compact.load_compact(&loaded.save_compact(true)).unwrap();
+        assert_eq!(compact.save(), before);
+    }
+
+    #[test]
+    fn set_reaction_validates_species_ids() {
+        let mut universe = Universe::new(8, 8);
+        assert!(!universe.set_reaction(1, 2, 100, 99, 0, 0));
+        assert!(universe.set_reaction(1, 2, 100, 9, 0, 7)); // Sand+Water → Stone, spawn Steam
+        let reaction = universe
+            .grid
+            .reactions
+            .get(Species::Water, Species::Sand)
+            .unwrap();
+        assert_eq!(reaction.other_becomes, Species::Stone);
+        assert_eq!(reaction.spawn, Some(Species::Steam));
+
+        universe.clear_reaction(2, 1);
+        assert_eq!(
+            universe.grid.reactions.get(Species::Sand, Species::Water),
+            None
+        );
+    }
+
+    #[test]
+    fn resizing_moves_the_cursor_with_the_content() {
+        let mut universe = Universe::new(6, 4);
+        universe.set_cursor(1, 2);
+        universe.resize(10, 8, resize::Anchor::Center);
+        assert_eq!(universe.cursor, Some((3, 4)));
+        universe.resize(4, 4, resize::Anchor::BottomCenter);
+        assert_eq!(universe.cursor, Some((0, 0)));
+    }
+
+    #[test]
+    fn loading_restores_the_saved_reactions() {
+        let mut universe = Universe::new(8, 8);
+        assert!(universe.set_reaction(1, 2, 100, 9, 0, 0));
+        universe.clear_reaction(4, 2); // Fire+Water
+        let reactions = universe.grid.reactions.clone();
+        let saved = universe.save();
+        let compact = universe.save_compact(false);
+
+        let mut other = Universe::new(4, 4);
+        other.load(&saved).unwrap();
+        assert_eq!(other.grid.reactions, reactions);
+        let mut other = Universe::new(4, 4);
+        other.load_compact(&compact).unwrap();
+        assert_eq!(other.grid.reactions, reactions);
+    }
+
     #[test]
     fn set_ghost_does_not_overwrite_existing_element() {
         let mut universe = Universe::new(16, 16);
@@ -0,0 +1,521 @@
+//! Brush shapes painted in one call.
+//!
+//! [`Grid::paint_circle`], [`Grid::paint_line`] and [`Grid::paint_rect`]
+//! fill a whole shape with a [`Brush`], so the frontend can draw a stroke
+//! with one call across the WASM boundary instead of one per cell. Every
+//! cell a shape covers is painted once, with the same per-species
+//! initialization as `Universe::set_cell`; parts of a shape outside the
+//! grid are clipped.
+//!
+//! [`Grid::flood_fill`] is the bucket tool and [`Grid::replace_species`]
+//! swaps one species for another within a rectangle.
+
+use crate::cell::Species;
+use crate::chunk::DirtyRect;
+use crate::elements::spawn_cell;
+use crate::Grid;
+use std::collections::VecDeque;
+use wasm_bindgen::prelude::*;
+
+/// Most cells one `Universe::flood_fill` call changes, so a click on a
+/// huge empty world can't stall a frame.
+pub const FLOOD_FILL_LIMIT: usize = 1 << 16;
+
+/// Which existing cells a brush may overwrite.
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[repr(u8)]
+pub enum PaintMode {
+    /// Only fill empty cells, like `Universe::set_cell`. Painting Empty
+    /// (the eraser) still clears everything.
+    #[default]
+    FillEmpty = 0,
+    /// Overwrite whatever is there.
+    Replace = 1,
+    /// Only overwrite cells of the brush's `target` species.
+    ReplaceSpecies = 2,
+}
+
+impl PaintMode {
+    /// The mode whose discriminant is `id`, if any.
+    #[must_use]
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(Self::FillEmpty),
+            1 => Some(Self::Replace),
+            2 => Some(Self::ReplaceSpecies),
+            _ => None,
+        }
+    }
+}
+
+/// Which neighbours count as connected for a flood fill.
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[repr(u8)]
+pub enum Connectivity {
+    /// Edge neighbours only.
+    #[default]
+    Four = 0,
+    /// Edge and corner neighbours.
+    Eight = 1,
+}
+
+impl Connectivity {
+    /// The connectivity whose discriminant is `id`, if any.
+    #[must_use]
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(Self::Four),
+            1 => Some(Self::Eight),
+            _ => None,
+        }
+    }
+
+    fn offsets(self) -> &'static [(i32, i32)] {
+        const OFFSETS: [(i32, i32); 8] = [
+            (1, 0),
+            (-1, 0),
+            (0, 1),
+            (0, -1),
+            (1, 1),
+            (-1, 1),
+            (1, -1),
+            (-1, -1),
+        ];
+        match self {
+            Self::Four => &OFFSETS[..4],
+            Self::Eight => &OFFSETS,
+        }
+    }
+}
+
+/// What to paint and over what.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Brush {
+    pub species: Species,
+    pub mode: PaintMode,
+    /// The species `PaintMode::ReplaceSpecies` overwrites; unused otherwise.
+    pub target: Species,
+}
+
+impl Brush {
+    /// A brush painting `species` into empty cells.
+    #[must_use]
+    pub const fn new(species: Species) -> Self {
+        Self {
+            species,
+            mode: PaintMode::FillEmpty,
+            target: Species::Empty,
+        }
+    }
+
+    /// True if this brush paints over a cell of species `existing`.
+    #[must_use]
+    pub fn paints_over(&self, existing: Species) -> bool {
+        match self.mode {
+            PaintMode::FillEmpty => existing == Species::Empty || self.species == Species::Empty,
+            PaintMode::Replace => true,
+            PaintMode::ReplaceSpecies => existing == self.target,
+        }
+    }
+}
+
+/// Cells of a shape within a rectangle, marked in a row-major mask.
+struct Mask {
+    rect: DirtyRect,
+    covered: Vec<bool>,
+}
+
+impl Mask {
+    fn new(rect: DirtyRect) -> Self {
+        let w = (rect.max_x - rect.min_x + 1).max(0) as usize;
+        let h = (rect.max_y - rect.min_y + 1).max(0) as usize;
+        Self {
+            rect,
+            covered: vec![false; w * h],
+        }
+    }
+
+    fn width(&self) -> i32 {
+        self.rect.max_x - self.rect.min_x + 1
+    }
+
+    /// Mark `(x, y)`, ignoring points outside the rectangle.
+    fn mark(&mut self, x: i32, y: i32) {
+        let r = self.rect;
+        if x >= r.min_x && x <= r.max_x && y >= r.min_y && y <= r.max_y {
+            let i = (y - r.min_y) * self.width() + (x - r.min_x);
+            self.covered[i as usize] = true;
+        }
+    }
+
+    /// Mark the disc of radius `r` around `(cx, cy)`, visiting only the
+    /// part inside the rectangle. Offsets between any two `i32` points and
+    /// their squares overflow `i32` and `i64` respectively, so the maths
+    /// is widened.
+    fn mark_disc(&mut self, cx: i32, cy: i32, r: u32) {
+        let rect = self.rect;
+        let (cx, cy, r) = (i64::from(cx), i64::from(cy), i64::from(r));
+        let min_x = (cx - r).max(i64::from(rect.min_x));
+        let max_x = (cx + r).min(i64::from(rect.max_x));
+        let min_y = (cy - r).max(i64::from(rect.min_y));
+        let max_y = (cy + r).min(i64::from(rect.max_y));
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                let (dx, dy) = (i128::from(x - cx), i128::from(y - cy));
+                if dx * dx + dy * dy <= i128::from(r) * i128::from(r) {
+                    self.mark(x as i32, y as i32);
+                }
+            }
+        }
+    }
+}
+
+impl Grid {
+    /// Paint a filled circle of radius `r` around `(cx, cy)`.
+    ///
+    /// Covers the cells whose offset from the centre is within `r`, the
+    /// same shape as the frontend's `brushCells`; radius 0 is one cell.
+    pub fn paint_circle(&mut self, cx: i32, cy: i32, r: u32, brush: Brush) {
+        let (x, y, r64) = (i64::from(cx), i64::from(cy), i64::from(r));
+        let mut mask = self.mask(x - r64, y - r64, x + r64, y + r64);
+        mask.mark_disc(cx, cy, r);
+        self.paint_mask(&mask, brush);
+    }
+
+    /// Paint a line from `(x0, y0)` to `(x1, y1)` with a round brush of
+    /// radius `radius`, covering the cells Bresenham's algorithm steps
+    /// through.
+    ///
+    /// Only the stretch of the line whose brush can reach the grid is
+    /// walked, so far-off or enormous lines cost no more than ones on the
+    /// grid.
+    pub fn paint_line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, radius: u32, brush: Brush) {
+        let r = i64::from(radius);
+        let (x0, y0, x1, y1) = (i64::from(x0), i64::from(y0), i64::from(x1), i64::from(y1));
+        let mut mask = self.mask(
+            x0.min(x1) - r,
+            y0.min(y1) - r,
+            x0.max(x1) + r,
+            y0.max(y1) + r,
+        );
+        if mask.covered.is_empty() {
+            return;
+        }
+
+        // Step `t` moves one cell along the longer axis and a rounded
+        // share of a cell along the other, halves rounding towards the
+        // end, which is exactly the path Bresenham's algorithm takes.
+        let (dx, dy) = (x1 - x0, y1 - y0);
+        let steps = dx.abs().max(dy.abs());
+        let along = |t: i64, d: i64| {
+            if steps == 0 {
+                return 0;
+            }
+            let (t, n) = (i128::from(t), i128::from(steps));
+            let share = (2 * t * i128::from(d.abs()) + n) / (2 * n);
+            d.signum() * share as i64
+        };
+
+        // Skip the steps whose brush lies beyond the mask along the
+        // longer axis.
+        let rect = mask.rect;
+        let (start, d, lo, hi) = if dx.abs() >= dy.abs() {
+            (x0, dx, rect.min_x, rect.max_x)
+        } else {
+            (y0, dy, rect.min_y, rect.max_y)
+        };
+        let (lo, hi) = (i64::from(lo) - r, i64::from(hi) + r);
+        let (first, last) = match d.signum() {
+            1 => (lo - start, hi - start),
+            -1 => (start - hi, start - lo),
+            _ => (0, 0),
+        };
+        for t in first.max(0)..=last.min(steps) {
+            let (x, y) = (x0 + along(t, dx), y0 + along(t, dy));
+            mask.mark_disc(x as i32, y as i32, radius);
+        }
+        self.paint_mask(&mask, brush);
+    }
+
+    /// Paint the filled rectangle with corners `(x0, y0)` and `(x1, y1)`,
+    /// both inclusive, in either order.
+    pub fn paint_rect(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, brush: Brush) {
+        let mut mask = self.mask(
+            i64::from(x0.min(x1)),
+            i64::from(y0.min(y1)),
+            i64::from(x0.max(x1)),
+            i64::from(y0.max(y1)),
+        );
+        mask.covered.fill(true);
+        self.paint_mask(&mask, brush);
+    }
+
+    /// Turn every `from` cell in the rectangle with inclusive corners
+    /// `(x0, y0)` and `(x1, y1)` into a freshly initialized `to` cell.
+    pub fn replace_species(
+        &mut self,
+        from: Species,
+        to: Species,
+        x0: i32,
+        y0: i32,
+        x1: i32,
+        y1: i32,
+    ) {
+        let brush = Brush {
+            species: to,
+            mode: PaintMode::ReplaceSpecies,
+            target: from,
+        };
+        self.paint_rect(x0, y0, x1, y1, brush);
+    }
+
+    /// Fill the connected region of same-species cells containing
+    /// `(x, y)` with `species`, and return how many cells changed.
+    ///
+    /// The fill spreads breadth-first from the seed and stops after
+    /// `limit` cells, leaving the rest of a larger region untouched. It
+    /// does not follow wrapping edges. Filling a region with its own
+    /// species, or from outside the grid, changes nothing.
+    pub fn flood_fill(
+        &mut self,
+        x: i32,
+        y: i32,
+        species: Species,
+        connectivity: Connectivity,
+        limit: usize,
+    ) -> usize {
+        if !self.in_bounds(x, y) {
+            return 0;
+        }
+        let from = self.get(x, y).species;
+        if from == species {
+            return 0;
+        }
+        // Cells are repainted as they are queued, so the species check
+        // doubles as the visited set and the queue never outgrows `limit`.
+        let mut filled = 0;
+        let mut paint = |grid: &mut Self, x: i32, y: i32| {
+            if filled == limit || !grid.in_bounds(x, y) || grid.get(x, y).species != from {
+                return false;
+            }
+            let cell = spawn_cell(species, &mut grid.rng);
+            grid.edit(x, y, cell);
+            filled += 1;
+            true
+        };
+        paint(self, x, y);
+        let mut queue = VecDeque::from([(x, y)]);
+        while let Some((x, y)) = queue.pop_front() {
+            for &(dx, dy) in connectivity.offsets() {
+                if paint(self, x + dx, y + dy) {
+                    queue.push_back((x + dx, y + dy));
+                }
+            }
+        }
+        filled
+    }
+
+    /// An empty mask over the given bounds, clipped to the grid.
+    fn mask(&self, min_x: i64, min_y: i64, max_x: i64, max_y: i64) -> Mask {
+        // Clamp both ends into the grid; an off-grid span comes out empty.
+        let clip = |v: i64, len: usize| v.clamp(-1, len as i64) as i32;
+        Mask::new(DirtyRect {
+            min_x: clip(min_x.max(0), self.width),
+            min_y: clip(min_y.max(0), self.height),
+            max_x: clip(max_x.min(self.width as i64 - 1), self.width),
+            max_y: clip(max_y.min(self.height as i64 - 1), self.height),
+        })
+    }
+
+    fn paint_mask(&mut self, mask: &Mask, brush: Brush) {
+        let width = mask.width().max(1) as usize;
+        for (i, _) in mask
+            .covered
+            .iter()
+            .enumerate()
+            .filter(|(_, &covered)| covered)
+        {
+            let x = mask.rect.min_x + (i % width) as i32;
+            let y = mask.rect.min_y + (i / width) as i32;
+            if brush.paints_over(self.get(x, y).species) {
+                let cell = spawn_cell(brush.species, &mut self.rng);
+                self.edit(x, y, cell);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ascii::assert_ascii_eq;
+
+    fn brush(species: Species, mode: PaintMode, target: Species) -> Brush {
+        Brush {
+            species,
+            mode,
+            target,
+        }
+    }
+
+    #[test]
+    fn circle_matches_the_frontend_brush() {
+        let mut g = Grid::new(7, 7);
+        g.paint_circle(3, 3, 2, Brush::new(Species::Wall));
+        assert_ascii_eq(
+            &g,
+            "....... / ...#... / ..###.. / .#####. / ..###.. / ...#... / .......",
+        );
+
+        let mut g = Grid::new(3, 3);
+        g.paint_circle(0, 0, 0, Brush::new(Species::Wall));
+        g.paint_circle(-5, 1, 3, Brush::new(Species::Wall));
+        assert_ascii_eq(&g, "#.. / ... / ...");
+    }
+
+    #[test]
+    fn huge_brushes_are_clipped_not_overflowed() {
+        let mut g = Grid::new(4, 3);
+        g.paint_circle(1, 1, u32::MAX, Brush::new(Species::Wall));
+        assert_ascii_eq(&g, "#### / #### / ####");
+
+        let mut g = Grid::new(4, 3);
+        g.paint_circle(i32::MIN, i32::MAX, 46_341, Brush::new(Species::Wall));
+        g.paint_line(-1, -1, -1, 5, u32::MAX, Brush::new(Species::Sand));
+        assert_ascii_eq(&g, "ssss / ssss / ssss");
+
+        let wall = Brush::new(Species::Wall);
+        let mut g = Grid::new(4, 3);
+        g.paint_line(i32::MIN, 1, i32::MAX, 1, 0, wall);
+        g.paint_line(i32::MIN, i32::MIN, i32::MAX, i32::MAX, 0, wall);
+        g.paint_line(i32::MAX, i32::MIN, i32::MIN, i32::MAX, 1, wall);
+        assert_ascii_eq(&g, "#... / #### / ..#.");
+
+        // Entirely off the grid: nothing is painted, and quickly.
+        let mut g = Grid::new(8, 8);
+        g.paint_line(-200_000_000, 3, 200_000_000, 30, 2, wall);
+        g.paint_line(-200_000_000, 12, 200_000_000, 12, 3, wall);
+        assert!(g.cells.iter().all(|c| c.species == Species::Empty));
+    }
+
+    #[test]
+    fn lines_are_continuous_and_thick() {
+        let mut g = Grid::new(6, 4);
+        g.paint_line(5, 3, 0, 0, 0, Brush::new(Species::Wall));
+        assert_ascii_eq(&g, "#..... / .##... / ...##. / .....#");
+
+        let mut g = Grid::new(6, 3);
+        g.paint_line(1, 1, 4, 1, 1, Brush::new(Species::Wall));
+        assert_ascii_eq(&g, ".####. / ###### / .####.");
+    }
+
+    #[test]
+    fn rect_corners_in_any_order() {
+        let mut g = Grid::new(5, 4);
+        g.paint_rect(3, 2, 1, 1, Brush::new(Species::Wall));
+        g.paint_rect(4, 3, 9, 9, Brush::new(Species::Wall));
+        assert_ascii_eq(&g, "..... / .###. / .###. / ....#");
+    }
+
+    #[test]
+    fn modes_choose_what_gets_overwritten() {
+        let world = ".s~# / .s~#";
+        let mut g = Grid::from_ascii(world).unwrap();
+        g.paint_rect(0, 0, 3, 0, Brush::new(Species::Acid));
+        g.paint_rect(
+            0,
+            1,
+            3,
+            1,
+            brush(Species::Acid, PaintMode::Replace, Species::Empty),
+        );
+        assert_ascii_eq(&g, "as~# / aaaa");
+
+        let mut g = Grid::from_ascii(world).unwrap();
+        g.paint_rect(
+            0,
+            0,
+            3,
+            1,
+            brush(Species::Oil, PaintMode::ReplaceSpecies, Species::Water),
+        );
+        assert_ascii_eq(&g, ".so# / .so#");
+
+        let mut g = Grid::from_ascii(world).unwrap();
+        g.paint_circle(1, 0, 1, Brush::new(Species::Empty));
+        assert_ascii_eq(&g, "...# / ..~#");
+    }
+
+    #[test]
+    fn replace_species_stays_in_its_rect() {
+        let mut g = Grid::from_ascii("~~~~ / ~s~~ / ####").unwrap();
+        g.replace_species(Species::Water, Species::Oil, 0, 0, 2, 1);
+        assert_ascii_eq(&g, "ooo~ / oso~ / ####");
+    }
+
+    #[test]
+    fn flood_fill_follows_connectivity() {
+        let world = "..#.. / ..#.. / ##.## / ..#..";
+        let mut g = Grid::from_ascii(world).unwrap();
+        assert_eq!(
+            g.flood_fill(0, 0, Species::Water, Connectivity::Four, FLOOD_FILL_LIMIT),
+            4
+        );
+        assert_ascii_eq(&g, "~~#.. / ~~#.. / ##.## / ..#..");
+
+        let mut g = Grid::from_ascii(world).unwrap();
+        assert_eq!(
+            g.flood_fill(0, 0, Species::Water, Connectivity::Eight, FLOOD_FILL_LIMIT),
+            13
+        );
+        assert_ascii_eq(&g, "~~#~~ / ~~#~~ / ##~## / ~~#~~");
+
+        assert_eq!(
+            g.flood_fill(0, 0, Species::Water, Connectivity::Four, FLOOD_FILL_LIMIT),
+            0
+        );
+        assert_eq!(
+            g.flood_fill(9, 0, Species::Sand, Connectivity::Four, FLOOD_FILL_LIMIT),
+            0
+        );
+    }
+
+    #[test]
+    fn flood_fill_stops_at_the_limit() {
+        let mut g = Grid::new(1000, 1000);
+        assert_eq!(
+            g.flood_fill(500, 500, Species::Wall, Connectivity::Four, 100),
+            100
+        );
+        assert_eq!(
+            g.cells
+                .iter()
+                .filter(|c| c.species == Species::Wall)
+                .count(),
+            100
+        );
+        assert_eq!(
+            g.get(500, 500).species,
+            Species::Wall,
+            "the fill grows from the seed"
+        );
+    }
+
+    #[test]
+    fn painted_cells_are_initialized_and_awake() {
+        let mut g = Grid::new(40, 40);
+        for _ in 0..3 {
+            g.tick();
+        }
+        assert_eq!(g.active_chunk_count(), 0);
+        g.paint_circle(20, 20, 3, Brush::new(Species::Fire));
+        assert!(g
+            .cells
+            .iter()
+            .filter(|c| c.species == Species::Fire)
+            .all(|c| c.rb >= 20));
+        assert!(g.active_chunk_count() > 0);
+    }
+}
@@ -0,0 +1,300 @@
+//! Multi-threaded tick for native builds.
+//!
+//! [`Grid::tick`] already updates the grid tile by tile in four
+//! checkerboard phases, with a random stream per tile. `par_tick` runs the
+//! tiles of each phase on rayon tasks instead of one after another. A task
+//! works on a private window around its tile: cells one chunk out, which
+//! covers everything an element can read or write (`elements::MAX_REACH`),
+//! and chunk schedules two chunks out, which covers everything those
+//! writes can wake. Same-phase tiles never read or write each other's
+//! cells, and schedules only ever grow, so windows can be merged back in
+//! any order and the result is cell-for-cell the same as `tick`, whatever
+//! the thread count.
+
+use crate::cell::Cell;
+use crate::chunk::{Tile, CHUNK_SIZE, PHASES};
+use crate::elements::MAX_REACH;
+use crate::{chunk_layout, Grid};
+use rayon::prelude::*;
+use std::ops::Range;
+
+/// Chunks of schedule around a tile; the outer ring only catches wakes.
+const SCHEDULE_MARGIN: usize = 2;
+
+/// Chunks of cells around a tile.
+const CELL_MARGIN: usize = 1;
+
+// Writes land within MAX_REACH of the tile and wake up to MAX_REACH past that.
+const _: () = assert!(MAX_REACH as usize <= CELL_MARGIN * CHUNK_SIZE);
+const _: () = assert!(2 * MAX_REACH as usize <= SCHEDULE_MARGIN * CHUNK_SIZE);
+
+/// A tile's private working copy of the grid.
+struct Window {
+    /// Top-left cell of the window in grid coordinates.
+    origin: (usize, usize),
+    /// Top-left chunk of the window in chunk coordinates.
+    chunk_origin: (usize, usize),
+    /// Window cells copied from and back to the grid, as
+    /// `(columns, rows)` in window coordinates.
+    copied: (Range<usize>, Range<usize>),
+    /// Tile rows to update, in window coordinates.
+    rows: Range<i32>,
+    /// Tile chunk columns to update, in window chunk coordinates.
+    chunk_cols: Range<usize>,
+    grid: Grid,
+}
+
+impl Grid {
+    /// Advance the simulation by one tick using every rayon worker.
+    ///
+    /// Gives the same result as [`Grid::tick`]; see the `parallel` module
+    /// docs. Grids with a wrapping edge just run `tick`, since a window
+    /// can't see across the seam.
+    pub fn par_tick(&mut self) {
+        if self.boundaries.any_wrap() {
+            self.tick();
+            return;
+        }
+        let gen = self.begin_tick();
+        // Cell buffers are reused from phase to phase.
+        let mut spare = Vec::new();
+        for phase in PHASES {
+            let mut windows: Vec<Window> = self
+                .awake_tiles(phase)
+                .into_iter()
+                .map(|tile| self.extract_window(&tile, spare.pop().unwrap_or_default()))
+                .collect();
+            windows.par_iter_mut().for_each(Window::run);
+            for window in windows {
+                spare.push(self.restore_window(window, gen));
+            }
+        }
+        self.end_tick();
+    }
+
+    /// Copy out the window around `tile`, reusing the `cells` buffer.
+    fn extract_window(&self, tile: &Tile, mut cells: Vec<Cell>) -> Window {
+        let chunks_h = self.chunks.len() / self.chunks_w;
+        let cx0 = tile.cols.start.saturating_sub(SCHEDULE_MARGIN);
+        let cy0 = tile.rows.start.saturating_sub(SCHEDULE_MARGIN);
+        let cx1 = (tile.cols.end + SCHEDULE_MARGIN).min(self.chunks_w);
+        let cy1 = (tile.rows.end + SCHEDULE_MARGIN).min(chunks_h);
+
+        let (ox, oy) = (cx0 * CHUNK_SIZE, cy0 * CHUNK_SIZE);
+        let ow = (cx1 * CHUNK_SIZE).min(self.width) - ox;
+        let oh = (cy1 * CHUNK_SIZE).min(self.height) - oy;
+
+        // Only the cells within CELL_MARGIN are ever read; the rest stay
+        // Empty and are never copied back.
+        let cell_span = |start: usize, end: usize, origin: usize, len: usize| {
+            let lo = (start.saturating_sub(CELL_MARGIN) * CHUNK_SIZE).max(origin);
+            let hi = ((end + CELL_MARGIN) * CHUNK_SIZE).min(origin + len);
+            lo - origin..hi - origin
+        };
+        let cols = cell_span(tile.cols.start, tile.cols.end, ox, ow);
+        let rows = cell_span(tile.rows.start, tile.rows.end, oy, oh);
+        cells.clear();
+        cells.resize(ow * oh, Cell::empty());
+        for row in rows.clone() {
+            let src = (oy + row) * self.width + ox;
+            cells[row * ow + cols.start..row * ow + cols.end]
+                .copy_from_slice(&self.cells[src + cols.start..src + cols.end]);
+        }
+
+        let mut chunks = chunk_layout(ow, oh);
+        let (dx, dy) = (-(ox as i32), -(oy as i32));
+        for cy in cy0..cy1 {
+            for cx in cx0..cx1 {
+                let src = self.chunks[cy * self.chunks_w + cx];
+                let dst = &mut chunks[(cy - cy0) * (cx1 - cx0) + (cx - cx0)];
+                dst.current = src.current.translate(dx, dy);
+                dst.next = src.next.translate(dx, dy);
+            }
+        }
+
+        let grid = Grid {
+            width: ow,
+            height: oh,
+            cells,
+            tick_count: self.tick_count,
+            generation: self.generation,
+            rng: self.rng.fork(tile.rng_key()),
+            reactions: self.reactions.clone(),
+            next_ghost_group: self.next_ghost_group,
+            // Margins keep elements away from inner window edges, so only
+            // the real grid edges are ever resolved.
+            boundaries: self.boundaries,
+            // The scan never touches heat, only whether it is active.
+            heat: Vec::new(),
+            heat_next: Vec::new(),
+            heat_active: false,
+            chunks,
+            chunks_w: cx1 - cx0,
+            edit_log: None,
+        };
+        let tile_rows = self.tile_rows(tile);
+        Window {
+            origin: (ox, oy),
+            chunk_origin: (cx0, cy0),
+            copied: (cols, rows),
+            rows: tile_rows.start - oy as i32..tile_rows.end - oy as i32,
+            chunk_cols: tile.cols.start - cx0..tile.cols.end - cx0,
+            grid,
+        }
+    }
+
+    /// Merge a window back into the grid and hand back its cell buffer.
+    fn restore_window(&mut self, window: Window, gen: u8) -> Vec<Cell> {
+        let Window {
+            origin: (ox, oy),
+            chunk_origin: (cx0, cy0),
+            copied: (cols, rows),
+            grid,
+            ..
+        } = window;
+        debug_assert_eq!(grid.generation, gen);
+        for row in rows {
+            let src = row * grid.width;
+            let dst = (oy + row) * self.width + ox;
+            self.cells[dst + cols.start..dst + cols.end]
+                .copy_from_slice(&grid.cells[src + cols.start..src + cols.end]);
+        }
+        let (dx, dy) = (ox as i32, oy as i32);
+        let chunks_h = grid.chunks.len() / grid.chunks_w;
+        for cy in 0..chunks_h {
+            for cx in 0..grid.chunks_w {
+                let src = grid.chunks[cy * grid.chunks_w + cx];
+                let dst = &mut self.chunks[(cy + cy0) * self.chunks_w + (cx + cx0)];
+                // Neighbouring windows share outer chunks; wakes only add.
+                dst.current.union(src.current.translate(dx, dy));
+                dst.next.union(src.next.translate(dx, dy));
+            }
+        }
+        self.heat_active |= grid.heat_active;
+        grid.cells
+    }
+}
+
+impl Window {
+    fn run(&mut self) {
+        let gen = self.grid.generation;
+        self.grid
+            .scan(self.rows.clone(), self.chunk_cols.clone(), gen);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cell::Species;
+    use crate::chunk::DirtyRect;
+    use crate::elements::registry::arb_species;
+    use crate::elements::{spawn_cell, Reaction};
+    use crate::heat::AMBIENT_HEAT;
+    use proptest::prelude::*;
+
+    fn arb_grid(w: usize, h: usize) -> impl Strategy<Value = Grid> {
+        proptest::collection::vec(
+            prop_oneof![4 => Just(Species::Empty), 3 => arb_species()],
+            w * h,
+        )
+        .prop_map(move |species| {
+            let mut grid = Grid::new(w, h);
+            for (i, &sp) in species.iter().enumerate() {
+                grid.cells[i] = spawn_cell(sp, &mut grid.rng);
+            }
+            grid
+        })
+    }
+
+    #[test]
+    fn sand_falls_across_tile_boundaries() {
+        let mut grid = Grid::new(160, 160);
+        for x in 0..160 {
+            grid.set(x, 159, Cell::wall());
+        }
+        grid.set(70, 0, Cell::new(Species::Sand));
+        for _ in 0..200 {
+            grid.par_tick();
+        }
+        assert_eq!(grid.get(70, 158).species, Species::Sand);
+        assert_eq!(grid.active_chunk_count(), 0);
+    }
+
+    #[test]
+    fn copied_cells_of_a_phase_do_not_overlap() {
+        let grid = Grid::new(300, 200);
+        for phase in PHASES {
+            let rects: Vec<DirtyRect> = grid
+                .awake_tiles(phase)
+                .iter()
+                .map(|tile| {
+                    let w = grid.extract_window(tile, Vec::new());
+                    let (cols, rows) = &w.copied;
+                    DirtyRect {
+                        min_x: (w.origin.0 + cols.start) as i32,
+                        min_y: (w.origin.1 + rows.start) as i32,
+                        max_x: (w.origin.0 + cols.end) as i32 - 1,
+                        max_y: (w.origin.1 + rows.end) as i32 - 1,
+                    }
+                })
+                .collect();
+            assert!(!rects.is_empty());
+            for (i, a) in rects.iter().enumerate() {
+                for b in &rects[i + 1..] {
+                    assert!(a.intersect(*b).is_empty(), "{a:?} overlaps {b:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn heat_wakes_up_from_inside_a_window() {
+        let mut grid = Grid::new(96, 64);
+        grid.reactions.insert(
+            Species::Sand,
+            Species::Wall,
+            Reaction::new(100, Species::Fire, Species::Wall),
+        );
+        for x in 0..96 {
+            grid.set(x, 63, Cell::wall());
+        }
+        grid.par_tick();
+        assert!(!grid.heat_active);
+
+        grid.set(50, 62, Cell::new(Species::Sand));
+        grid.par_tick();
+        assert_eq!(grid.get(50, 62).species, Species::Fire);
+        assert!(grid.heat_active, "the window's flame must reach the grid");
+        grid.par_tick();
+        assert!(grid.heat_at(50, 61) > AMBIENT_HEAT);
+    }
+
+    // The threaded tick must match `Grid::tick` exactly, whatever the
+    // thread count.
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+        #[test]
+        fn prop_par_tick_matches_tick(
+            grid in arb_grid(150, 100),
+            ticks in 1u32..20,
+        ) {
+            let mut sequential = grid.clone();
+            let mut threaded = grid.clone();
+            let mut single = grid;
+            let pool = rayon::ThreadPoolBuilder::new().num_threads(1).build().unwrap();
+
+            for _ in 0..ticks {
+                sequential.tick();
+                threaded.par_tick();
+                pool.install(|| single.par_tick());
+            }
+            for other in [&threaded, &single] {
+                prop_assert_eq!(&sequential.cells, &other.cells);
+                prop_assert_eq!(&sequential.chunks, &other.chunks);
+                prop_assert_eq!(sequential.state_hash(), other.state_hash());
+                prop_assert_eq!(sequential.rng, other.rng);
+            }
+        }
+    }
+}
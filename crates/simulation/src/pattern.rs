@@ -0,0 +1,369 @@
+//! Rectangular blocks of cells for copy, cut and paste.
+//!
+//! A [`Pattern`] holds a block of cells, exactly as they were in the grid,
+//! and an optional mask of which cells it covers; cells outside the mask
+//! are transparent and never pasted. Patterns come from
+//! [`Grid::copy_region`] and [`Grid::cut_region`], can be rotated by
+//! quarter turns and mirrored, and go back into a grid with
+//! [`Grid::paste_pattern`].
+//!
+//! Pasting writes the stored cells as they are, keeping fire lifetimes
+//! and ghost roles, except that every ghost group in the pattern is given
+//! a freshly allocated group ID so the copy moves independently of the
+//! original. Ghost groups move as rigid shapes, so a group is only pasted
+//! whole: if any of its cells would be clipped at the grid edge, or is
+//! blocked under [`PasteMode::OnlyEmpty`], none of it is written.
+
+use crate::cell::{Cell, Species};
+use crate::Grid;
+use wasm_bindgen::prelude::*;
+
+/// Which cells a paste may overwrite.
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[repr(u8)]
+pub enum PasteMode {
+    /// Write every covered cell, Empty ones included.
+    #[default]
+    Overwrite = 0,
+    /// Only write non-empty cells, and only into empty cells.
+    OnlyEmpty = 1,
+}
+
+/// A rectangular block of cells with an optional mask; see the
+/// [module docs](self).
+#[wasm_bindgen]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Pattern {
+    width: usize,
+    height: usize,
+    cells: Vec<Cell>,
+    /// Which cells are part of the pattern, row-major; `None` covers all.
+    mask: Option<Vec<bool>>,
+}
+
+impl Pattern {
+    /// A `width`×`height` pattern from row-major `cells`, covering all of
+    /// them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cells` does not hold `width × height` cells.
+    #[must_use]
+    pub fn from_cells(width: usize, height: usize, cells: Vec<Cell>) -> Self {
+        assert_eq!(
+            cells.len(),
+            width * height,
+            "pattern cells don't match its size"
+        );
+        Self {
+            width,
+            height,
+            cells,
+            mask: None,
+        }
+    }
+
+    /// Restrict the pattern to the cells where `mask` is true.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mask` does not hold one entry per cell.
+    #[must_use]
+    pub fn with_mask(mut self, mask: Vec<bool>) -> Self {
+        assert_eq!(
+            mask.len(),
+            self.cells.len(),
+            "pattern mask doesn't match its size"
+        );
+        self.mask = Some(mask);
+        self
+    }
+
+    /// The cell at `(x, y)`, or `None` if it is outside the pattern or
+    /// masked out.
+    #[must_use]
+    pub fn get(&self, x: usize, y: usize) -> Option<Cell> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let i = y * self.width + x;
+        self.mask
+            .as_ref()
+            .is_none_or(|mask| mask[i])
+            .then(|| self.cells[i])
+    }
+
+    /// Rebuild the pattern as `width`×`height`, taking each new cell from
+    /// the old position `source(x, y)`.
+    fn remap(
+        &mut self,
+        width: usize,
+        height: usize,
+        source: impl Fn(usize, usize) -> (usize, usize),
+    ) {
+        let old_width = self.width;
+        let index = |x, y| {
+            let (sx, sy) = source(x, y);
+            sy * old_width + sx
+        };
+        let positions: Vec<usize> = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| index(x, y))
+            .collect();
+        self.cells = positions.iter().map(|&i| self.cells[i]).collect();
+        if let Some(mask) = &self.mask {
+            self.mask = Some(positions.iter().map(|&i| mask[i]).collect());
+        }
+        self.width = width;
+        self.height = height;
+    }
+}
+
+#[wasm_bindgen]
+impl Pattern {
+    #[must_use]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[must_use]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Make Empty cells transparent, so pasting with
+    /// `PasteMode::Overwrite` replaces material without erasing around it.
+    pub fn mask_empty(&mut self) {
+        let mask = self.cells.iter().enumerate().map(|(i, cell)| {
+            cell.species != Species::Empty && self.mask.as_ref().is_none_or(|mask| mask[i])
+        });
+        self.mask = Some(mask.collect());
+    }
+
+    /// Rotate a quarter turn clockwise.
+    pub fn rotate_cw(&mut self) {
+        let h = self.height;
+        self.remap(self.height, self.width, |x, y| (y, h - 1 - x));
+    }
+
+    /// Rotate a quarter turn counter-clockwise.
+    pub fn rotate_ccw(&mut self) {
+        let w = self.width;
+        self.remap(self.height, self.width, |x, y| (w - 1 - y, x));
+    }
+
+    /// Rotate half a turn.
+    pub fn rotate_180(&mut self) {
+        let (w, h) = (self.width, self.height);
+        self.remap(w, h, |x, y| (w - 1 - x, h - 1 - y));
+    }
+
+    /// Mirror left to right.
+    pub fn flip_horizontal(&mut self) {
+        let (w, h) = (self.width, self.height);
+        self.remap(w, h, |x, y| (w - 1 - x, y));
+    }
+
+    /// Mirror top to bottom.
+    pub fn flip_vertical(&mut self) {
+        let (w, h) = (self.width, self.height);
+        self.remap(w, h, |x, y| (x, h - 1 - y));
+    }
+}
+
+impl Grid {
+    /// The rectangle with inclusive corners `(x0, y0)` and `(x1, y1)`, in
+    /// either order, clipped to the grid as `(min_x, min_y, max_x, max_y)`.
+    /// A rectangle off the grid comes back with `max < min`.
+    fn clip_region(&self, x0: i32, y0: i32, x1: i32, y1: i32) -> (i32, i32, i32, i32) {
+        let (min_x, min_y) = (x0.min(x1).max(0), y0.min(y1).max(0));
+        let max_x = x0.max(x1).min(self.width as i32 - 1);
+        let max_y = y0.max(y1).min(self.height as i32 - 1);
+        (min_x, min_y, max_x, max_y)
+    }
+
+    /// Copy the rectangle with inclusive corners `(x0, y0)` and `(x1, y1)`,
+    /// in either order, clipped to the grid.
+    #[must_use]
+    pub fn copy_region(&self, x0: i32, y0: i32, x1: i32, y1: i32) -> Pattern {
+        let (min_x, min_y, max_x, max_y) = self.clip_region(x0, y0, x1, y1);
+        let width = (max_x - min_x + 1).max(0) as usize;
+        let height = (max_y - min_y + 1).max(0) as usize;
+        let cells = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| self.get(min_x + x as i32, min_y + y as i32))
+            .collect();
+        Pattern::from_cells(width, height, cells)
+    }
+
+    /// Copy a rectangle as `copy_region` does, then empty it.
+    pub fn cut_region(&mut self, x0: i32, y0: i32, x1: i32, y1: i32) -> Pattern {
+        let pattern = self.copy_region(x0, y0, x1, y1);
+        let (min_x, min_y, max_x, max_y) = self.clip_region(x0, y0, x1, y1);
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                if self.get(x, y).species != Species::Empty {
+                    self.edit(x, y, Cell::empty());
+                }
+            }
+        }
+        pattern
+    }
+
+    /// Paste `pattern` with its top-left corner at `(x, y)`, clipped to the
+    /// grid. Ghost groups get fresh IDs; see the [module docs](self).
+    pub fn paste_pattern(&mut self, x: i32, y: i32, pattern: &Pattern, mode: PasteMode) {
+        // Cells that land on the grid and that `mode` lets through, and the
+        // pattern's ghost groups with a cell that doesn't.
+        let mut writes: Vec<(i32, i32, Cell)> = Vec::new();
+        let mut broken: Vec<u8> = Vec::new();
+        for py in 0..pattern.height {
+            for px in 0..pattern.width {
+                let Some(cell) = pattern.get(px, py) else {
+                    continue;
+                };
+                let (gx, gy) = (x + px as i32, y + py as i32);
+                let fits = self.in_bounds(gx, gy)
+                    && (mode == PasteMode::Overwrite
+                        || cell.species != Species::Empty
+                            && self.get(gx, gy).species == Species::Empty);
+                if fits {
+                    writes.push((gx, gy, cell));
+                } else if cell.species == Species::Ghost && !broken.contains(&cell.ra) {
+                    broken.push(cell.ra);
+                }
+            }
+        }
+
+        // (group in the pattern, group in the grid), allocated on first use.
+        let mut groups: Vec<(u8, u8)> = Vec::new();
+        for (gx, gy, mut cell) in writes {
+            if cell.species == Species::Ghost {
+                if broken.contains(&cell.ra) {
+                    continue;
+                }
+                if let Some(&(_, to)) = groups.iter().find(|(from, _)| *from == cell.ra) {
+                    cell.ra = to;
+                } else {
+                    let to = self.alloc_ghost_group();
+                    groups.push((cell.ra, to));
+                    cell.ra = to;
+                }
+            }
+            self.edit(gx, gy, cell);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ascii::assert_ascii_eq;
+
+    fn pattern(picture: &str) -> Pattern {
+        let grid = Grid::from_ascii(picture).unwrap();
+        grid.copy_region(0, 0, grid.width as i32 - 1, grid.height as i32 - 1)
+    }
+
+    /// Paste `pattern` onto an empty grid of its size and picture it.
+    fn picture(pattern: &Pattern) -> Grid {
+        let mut grid = Grid::new(pattern.width, pattern.height);
+        grid.paste_pattern(0, 0, pattern, PasteMode::Overwrite);
+        grid
+    }
+
+    #[test]
+    fn rotations_and_flips() {
+        let original = pattern("s~# / ...");
+        let mut p = original.clone();
+        p.rotate_cw();
+        assert_ascii_eq(&picture(&p), ".s / .~ / .#");
+        p.rotate_ccw();
+        assert_eq!(p, original);
+
+        p.rotate_ccw();
+        assert_ascii_eq(&picture(&p), "#. / ~. / s.");
+        p.rotate_180();
+        assert_ascii_eq(&picture(&p), ".s / .~ / .#");
+
+        let mut p = original.clone();
+        p.flip_horizontal();
+        assert_ascii_eq(&picture(&p), "#~s / ...");
+        p.flip_vertical();
+        assert_ascii_eq(&picture(&p), "... / #~s");
+        p.rotate_180();
+        assert_eq!(p, original);
+    }
+
+    #[test]
+    fn copy_and_paste_modes() {
+        let mut g = Grid::from_ascii("s~.. / ##.. / .... / ..~.").unwrap();
+        let p = g.copy_region(1, 1, 0, 0);
+        assert_eq!((p.width(), p.height()), (2, 2));
+        g.paste_pattern(2, 0, &p, PasteMode::Overwrite);
+        g.paste_pattern(1, 2, &p, PasteMode::OnlyEmpty);
+        g.paste_pattern(3, 3, &p, PasteMode::Overwrite);
+        assert_ascii_eq(&g, "s~s~ / #### / .s~. / .#~s");
+
+        let clipped = g.copy_region(-5, 2, 1, 99);
+        assert_eq!((clipped.width(), clipped.height()), (2, 2));
+    }
+
+    #[test]
+    fn masks_make_cells_transparent() {
+        let mut p = pattern("#. / .#");
+        p.mask_empty();
+        let mut g = Grid::from_ascii("~~ / ~~").unwrap();
+        g.paste_pattern(0, 0, &p, PasteMode::Overwrite);
+        assert_ascii_eq(&g, "#~ / ~#");
+
+        let p = Pattern::from_cells(2, 1, vec![Cell::wall(); 2]).with_mask(vec![false, true]);
+        assert_eq!(p.get(0, 0), None);
+        let mut q = p.clone();
+        q.rotate_cw();
+        assert_eq!((q.get(0, 0), q.get(0, 1)), (None, Some(Cell::wall())));
+    }
+
+    #[test]
+    fn cut_empties_the_region() {
+        let mut g = Grid::from_ascii("s~ / ##").unwrap();
+        let p = g.cut_region(0, 0, 1, 0);
+        assert_ascii_eq(&g, ".. / ##");
+        assert_ascii_eq(&picture(&p), "s~");
+
+        let p = g.cut_region(-1_000_000, i32::MIN, i32::MAX, 1_000_000);
+        assert_eq!((p.width(), p.height()), (2, 2));
+        assert_ascii_eq(&g, ".. / ..");
+        let p = g.cut_region(5, 5, 9, 9);
+        assert_eq!((p.width(), p.height()), (0, 0));
+    }
+
+    #[test]
+    fn ghost_groups_are_pasted_whole_or_not_at_all() {
+        let mut g = Grid::from_ascii("gg... / .#...").unwrap();
+        let p = g.copy_region(0, 0, 1, 0);
+        g.paste_pattern(4, 1, &p, PasteMode::Overwrite);
+        g.paste_pattern(0, 1, &p, PasteMode::OnlyEmpty);
+        assert_ascii_eq(&g, "gg... / .#...");
+        g.paste_pattern(2, 1, &p, PasteMode::OnlyEmpty);
+        assert_ascii_eq(&g, "gg... / .#gg.");
+    }
+
+    #[test]
+    fn pasted_ghosts_get_their_own_group() {
+        let mut g = Grid::from_ascii("g.g. / ....").unwrap();
+        let original = g.get(0, 0).ra;
+        let p = g.copy_region(0, 0, 0, 0);
+        g.paste_pattern(1, 1, &p, PasteMode::Overwrite);
+        g.paste_pattern(3, 1, &p, PasteMode::Overwrite);
+        let (a, b) = (g.get(1, 1).ra, g.get(3, 1).ra);
+        assert_ne!(a, original);
+        assert_ne!(b, original);
+        assert_ne!(a, b, "each paste is its own ghost");
+
+        let p = g.copy_region(0, 0, 3, 0);
+        g.paste_pattern(0, 1, &p, PasteMode::Overwrite);
+        assert_eq!(g.get(0, 1).ra, g.get(2, 1).ra, "one group stays one group");
+    }
+}
@@ -0,0 +1,1092 @@
+//! Input-log recording and deterministic playback.
+//!
+//! A [`Recorder`] wraps a [`Universe`] and logs every edit made through it
+//! with the tick it happened on, plus a state hash after each tick. The
+//! resulting [`Replay`] serializes to a stable binary format, so a bug
+//! report can carry the exact session that triggered it. A [`Replayer`]
+//! feeds the log into a fresh universe built from the same seed,
+//! dimensions and edge boundaries and stops at the first tick whose hash differs.
+//!
+//! Serialized layout, little-endian, sizes in bytes in parentheses: magic
+//! `b"SNDL"`, format version (5), width (4), height (4), seed (8), edge
+//! boundaries (4: left, right, top, bottom), then events until the end of
+//! input.
+//! Each event is a tag byte, the tick number (8) and a tag-specific body:
+//!
+//! | Tag | Event             | Body                                  |
+//! |-----|-------------------|---------------------------------------|
+//! | 0   | `set_cell`        | x (4), y (4), species (1)             |
+//! | 1   | `set_ghost`       | x (4), y (4), group (1), rb (1)       |
+//! | 2   | `alloc_ghost_group` | returned group (1)                  |
+//! | 3   | `set_cursor`      | x (4, signed), y (4, signed)          |
+//! | 4   | `clear_cursor`    | —                                     |
+//! | 5   | `tick`            | `Grid::state_hash` after the tick (8) |
+//! | 6   | `paint_circle`    | cx (4, signed), cy (4, signed), r (4), brush (3) |
+//! | 7   | `paint_line`      | x0, y0, x1, y1 (4 each, signed), radius (4), brush (3) |
+//! | 8   | `paint_rect`      | x0, y0, x1, y1 (4 each, signed), brush (3) |
+//! | 9   | `flood_fill`      | x (4, signed), y (4, signed), species (1), `Connectivity` (1) |
+//! | 10  | `replace_species` | from (1), to (1), x0, y0, x1, y1 (4 each, signed) |
+//! | 11  | `begin_stroke`    | none                                  |
+//! | 12  | `end_stroke`      | none                                  |
+//! | 13  | `undo`            | none                                  |
+//! | 14  | `redo`            | none                                  |
+//! | 15  | `set_history_limit` | bytes (8)                           |
+//! | 16  | `clear_history`   | none                                  |
+//! | 17  | `place_stamp`     | name length (1), name (UTF-8), x (4, signed), y (4, signed) |
+//!
+//! A brush is the species, `PaintMode` and target species ids (1 each).
+//! Only stamp placements that succeeded are logged; a failed one changes
+//! nothing.
+
+use crate::boundary::{Boundaries, Boundary};
+use crate::compact::MAX_CELLS;
+use crate::paint::{Connectivity, PaintMode};
+use crate::snapshot::Reader;
+use crate::{stamp, Universe};
+use std::fmt;
+use wasm_bindgen::prelude::*;
+
+/// Current replay format version.
+pub const REPLAY_VERSION: u16 = 5;
+
+const MAGIC: &[u8; 4] = b"SNDL";
+
+/// One recorded call on a [`Universe`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Event {
+    SetCell {
+        x: u32,
+        y: u32,
+        species: u8,
+    },
+    SetGhost {
+        x: u32,
+        y: u32,
+        group: u8,
+        rb: u8,
+    },
+    /// `group` is the ID the call returned.
+    AllocGhostGroup {
+        group: u8,
+    },
+    SetCursor {
+        x: i32,
+        y: i32,
+    },
+    ClearCursor,
+    /// `hash` is the state hash after the tick.
+    Tick {
+        hash: u64,
+    },
+    PaintCircle {
+        x: i32,
+        y: i32,
+        radius: u32,
+        brush: BrushArgs,
+    },
+    PaintLine {
+        x0: i32,
+        y0: i32,
+        x1: i32,
+        y1: i32,
+        radius: u32,
+        brush: BrushArgs,
+    },
+    PaintRect {
+        x0: i32,
+        y0: i32,
+        x1: i32,
+        y1: i32,
+        brush: BrushArgs,
+    },
+    FloodFill {
+        x: i32,
+        y: i32,
+        species: u8,
+        connectivity: Connectivity,
+    },
+    ReplaceSpecies {
+        from: u8,
+        to: u8,
+        x0: i32,
+        y0: i32,
+        x1: i32,
+        y1: i32,
+    },
+    BeginStroke,
+    EndStroke,
+    Undo,
+    Redo,
+    SetHistoryLimit {
+        bytes: u64,
+    },
+    ClearHistory,
+    /// `name` is one of the built-in stamps in `stamp::STAMPS`.
+    PlaceStamp {
+        name: &'static str,
+        x: i32,
+        y: i32,
+    },
+}
+
+/// The brush arguments of a `paint_*` call, as passed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct BrushArgs {
+    pub species: u8,
+    pub mode: PaintMode,
+    pub target: u8,
+}
+
+impl BrushArgs {
+    fn write(self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&[self.species, self.mode as u8, self.target]);
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, ReplayError> {
+        let [species, mode, target] = reader
+            .take(3)
+            .map_err(|_| ReplayError::Malformed)?
+            .try_into()
+            .map_err(|_| ReplayError::Malformed)?;
+        Ok(Self {
+            species,
+            mode: PaintMode::from_id(mode).ok_or(ReplayError::Malformed)?,
+            target,
+        })
+    }
+}
+
+impl Event {
+    fn tag(self) -> u8 {
+        match self {
+            Self::SetCell { .. } => 0,
+            Self::SetGhost { .. } => 1,
+            Self::AllocGhostGroup { .. } => 2,
+            Self::SetCursor { .. } => 3,
+            Self::ClearCursor => 4,
+            Self::Tick { .. } => 5,
+            Self::PaintCircle { .. } => 6,
+            Self::PaintLine { .. } => 7,
+            Self::PaintRect { .. } => 8,
+            Self::FloodFill { .. } => 9,
+            Self::ReplaceSpecies { .. } => 10,
+            Self::BeginStroke => 11,
+            Self::EndStroke => 12,
+            Self::Undo => 13,
+            Self::Redo => 14,
+            Self::SetHistoryLimit { .. } => 15,
+            Self::ClearHistory => 16,
+            Self::PlaceStamp { .. } => 17,
+        }
+    }
+
+    /// Append the tag-specific body of the event.
+    fn write(self, out: &mut Vec<u8>) {
+        match self {
+            Self::SetCell { x, y, species } => {
+                out.extend_from_slice(&x.to_le_bytes());
+                out.extend_from_slice(&y.to_le_bytes());
+                out.push(species);
+            }
+            Self::SetGhost { x, y, group, rb } => {
+                out.extend_from_slice(&x.to_le_bytes());
+                out.extend_from_slice(&y.to_le_bytes());
+                out.extend_from_slice(&[group, rb]);
+            }
+            Self::AllocGhostGroup { group } => out.push(group),
+            Self::SetCursor { x, y } => {
+                out.extend_from_slice(&x.to_le_bytes());
+                out.extend_from_slice(&y.to_le_bytes());
+            }
+            Self::Tick { hash } => out.extend_from_slice(&hash.to_le_bytes()),
+            Self::PaintCircle {
+                x,
+                y,
+                radius,
+                brush,
+            } => {
+                out.extend_from_slice(&x.to_le_bytes());
+                out.extend_from_slice(&y.to_le_bytes());
+                out.extend_from_slice(&radius.to_le_bytes());
+                brush.write(out);
+            }
+            Self::PaintLine {
+                x0,
+                y0,
+                x1,
+                y1,
+                radius,
+                brush,
+            } => {
+                for v in [x0, y0, x1, y1] {
+                    out.extend_from_slice(&v.to_le_bytes());
+                }
+                out.extend_from_slice(&radius.to_le_bytes());
+                brush.write(out);
+            }
+            Self::PaintRect {
+                x0,
+                y0,
+                x1,
+                y1,
+                brush,
+            } => {
+                for v in [x0, y0, x1, y1] {
+                    out.extend_from_slice(&v.to_le_bytes());
+                }
+                brush.write(out);
+            }
+            Self::FloodFill {
+                x,
+                y,
+                species,
+                connectivity,
+            } => {
+                out.extend_from_slice(&x.to_le_bytes());
+                out.extend_from_slice(&y.to_le_bytes());
+                out.extend_from_slice(&[species, connectivity as u8]);
+            }
+            Self::ReplaceSpecies {
+                from,
+                to,
+                x0,
+                y0,
+                x1,
+                y1,
+            } => {
+                out.extend_from_slice(&[from, to]);
+                for v in [x0, y0, x1, y1] {
+                    out.extend_from_slice(&v.to_le_bytes());
+                }
+            }
+            Self::SetHistoryLimit { bytes } => out.extend_from_slice(&bytes.to_le_bytes()),
+            Self::PlaceStamp { name, x, y } => {
+                out.push(name.len() as u8);
+                out.extend_from_slice(name.as_bytes());
+                out.extend_from_slice(&x.to_le_bytes());
+                out.extend_from_slice(&y.to_le_bytes());
+            }
+            Self::ClearCursor
+            | Self::BeginStroke
+            | Self::EndStroke
+            | Self::Undo
+            | Self::Redo
+            | Self::ClearHistory => {}
+        }
+    }
+
+    /// Read the body of an event tagged `tag`.
+    fn read(tag: u8, reader: &mut Reader) -> Result<Self, ReplayError> {
+        let malformed = |_| ReplayError::Malformed;
+        let event = match tag {
+            0 => Self::SetCell {
+                x: reader.u32().map_err(malformed)?,
+                y: reader.u32().map_err(malformed)?,
+                species: reader.u8().map_err(malformed)?,
+            },
+            1 => Self::SetGhost {
+                x: reader.u32().map_err(malformed)?,
+                y: reader.u32().map_err(malformed)?,
+                group: reader.u8().map_err(malformed)?,
+                rb: reader.u8().map_err(malformed)?,
+            },
+            2 => Self::AllocGhostGroup {
+                group: reader.u8().map_err(malformed)?,
+            },
+            3 => Self::SetCursor {
+                x: reader.u32().map_err(malformed)? as i32,
+                y: reader.u32().map_err(malformed)? as i32,
+            },
+            4 => Self::ClearCursor,
+            5 => Self::Tick {
+                hash: reader.u64().map_err(malformed)?,
+            },
+            6 => Self::PaintCircle {
+                x: reader.u32().map_err(malformed)? as i32,
+                y: reader.u32().map_err(malformed)? as i32,
+                radius: reader.u32().map_err(malformed)?,
+                brush: BrushArgs::read(reader)?,
+            },
+            7 => Self::PaintLine {
+                x0: reader.u32().map_err(malformed)? as i32,
+                y0: reader.u32().map_err(malformed)? as i32,
+                x1: reader.u32().map_err(malformed)? as i32,
+                y1: reader.u32().map_err(malformed)? as i32,
+                radius: reader.u32().map_err(malformed)?,
+                brush: BrushArgs::read(reader)?,
+            },
+            8 => Self::PaintRect {
+                x0: reader.u32().map_err(malformed)? as i32,
+                y0: reader.u32().map_err(malformed)? as i32,
+                x1: reader.u32().map_err(malformed)? as i32,
+                y1: reader.u32().map_err(malformed)? as i32,
+                brush: BrushArgs::read(reader)?,
+            },
+            9 => Self::FloodFill {
+                x: reader.u32().map_err(malformed)? as i32,
+                y: reader.u32().map_err(malformed)? as i32,
+                species: reader.u8().map_err(malformed)?,
+                connectivity: Connectivity::from_id(reader.u8().map_err(malformed)?)
+                    .ok_or(ReplayError::Malformed)?,
+            },
+            10 => Self::ReplaceSpecies {
+                from: reader.u8().map_err(malformed)?,
+                to: reader.u8().map_err(malformed)?,
+                x0: reader.u32().map_err(malformed)? as i32,
+                y0: reader.u32().map_err(malformed)? as i32,
+                x1: reader.u32().map_err(malformed)? as i32,
+                y1: reader.u32().map_err(malformed)? as i32,
+            },
+            11 => Self::BeginStroke,
+            12 => Self::EndStroke,
+            13 => Self::Undo,
+            14 => Self::Redo,
+            15 => Self::SetHistoryLimit {
+                bytes: reader.u64().map_err(malformed)?,
+            },
+            16 => Self::ClearHistory,
+            17 => {
+                let len = reader.u8().map_err(malformed)?;
+                let name = reader.take(usize::from(len)).map_err(malformed)?;
+                let stamp = std::str::from_utf8(name)
+                    .ok()
+                    .and_then(stamp::stamp)
+                    .ok_or(ReplayError::Malformed)?;
+                Self::PlaceStamp {
+                    name: stamp.name,
+                    x: reader.u32().map_err(malformed)? as i32,
+                    y: reader.u32().map_err(malformed)? as i32,
+                }
+            }
+            _ => return Err(ReplayError::Malformed),
+        };
+        Ok(event)
+    }
+}
+
+/// A recorded session: starting conditions plus every event in order.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Replay {
+    pub width: u32,
+    pub height: u32,
+    pub seed: u64,
+    pub boundaries: Boundaries,
+    /// Events tagged with the universe's tick count when they happened.
+    pub events: Vec<(u64, Event)>,
+}
+
+impl Replay {
+    /// Serialize into the stable replay format.
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(26 + self.events.len() * 18);
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&REPLAY_VERSION.to_le_bytes());
+        out.extend_from_slice(&self.width.to_le_bytes());
+        out.extend_from_slice(&self.height.to_le_bytes());
+        out.extend_from_slice(&self.seed.to_le_bytes());
+        out.extend_from_slice(&self.boundaries.to_ids());
+        for &(tick, event) in &self.events {
+            out.push(event.tag());
+            out.extend_from_slice(&tick.to_le_bytes());
+            event.write(&mut out);
+        }
+        out
+    }
+
+    /// Parse bytes produced by [`Replay::to_bytes`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ReplayError::Malformed`] if the bytes are not a replay, use
+    /// an unsupported version, are truncated, or contain an unknown edge
+    /// boundary, event tag or stamp name, and [`ReplayError::TooLarge`] if the grid has more cells than
+    /// [`Grid::from_compact`](crate::Grid::from_compact) accepts.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ReplayError> {
+        let malformed = |_| ReplayError::Malformed;
+        let mut reader = Reader { bytes };
+        if reader.take(MAGIC.len()).map_err(malformed)? != MAGIC {
+            return Err(ReplayError::Malformed);
+        }
+        if reader.u16().map_err(malformed)? != REPLAY_VERSION {
+            return Err(ReplayError::Malformed);
+        }
+        let width = reader.u32().map_err(malformed)?;
+        let height = reader.u32().map_err(malformed)?;
+        check_size(width, height)?;
+        let seed = reader.u64().map_err(malformed)?;
+        let boundaries = reader.boundaries().map_err(malformed)?;
+
+        let mut events = Vec::new();
+        while !reader.bytes.is_empty() {
+            let tag = reader.u8().map_err(malformed)?;
+            let tick = reader.u64().map_err(malformed)?;
+            let event = Event::read(tag, &mut reader)?;
+            events.push((tick, event));
+        }
+        Ok(Self {
+            width,
+            height,
+            seed,
+            boundaries,
+            events,
+        })
+    }
+}
+
+/// A replay could not be parsed or did not reproduce its recording.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ReplayError {
+    /// The log could not be parsed, or its events are out of order.
+    Malformed,
+    /// The recorded grid is larger than a replay may allocate.
+    TooLarge,
+    /// Playback diverged from the recording on this tick.
+    Desync { tick: u64 },
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malformed => f.write_str("malformed replay"),
+            Self::TooLarge => f.write_str("replay grid is too large"),
+            Self::Desync { tick } => {
+                write!(f, "replay diverged from the recording at tick {tick}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+/// The fresh universe a recording starts from.
+fn universe(width: u32, height: u32, seed: u64, edges: Boundaries) -> Universe {
+    let (w, h) = (width as usize, height as usize);
+    Universe::new_with_boundaries(w, h, seed, edges.left, edges.right, edges.top, edges.bottom)
+}
+
+/// Refuse grids a replay header could use to force a huge allocation.
+fn check_size(width: u32, height: u32) -> Result<(), ReplayError> {
+    let cells = u64::from(width) * u64::from(height);
+    if cells > MAX_CELLS as u64 {
+        return Err(ReplayError::TooLarge);
+    }
+    Ok(())
+}
+
+/// A [`Universe`] that logs every mutation for later replay.
+///
+/// Only the calls the replay format can log are available: `set_cell`,
+/// `set_ghost`, `alloc_ghost_group`, the brushes, the fills, stamps, undo
+/// history, the cursor and `tick`, plus the same render buffers as
+/// `Universe`. Clipboard, reactions, `load` and `resize` are not recorded,
+/// so a `Recorder` does not offer them and only hands out its universe
+/// read-only.
+#[wasm_bindgen]
+#[derive(Debug)]
+pub struct Recorder {
+    universe: Universe,
+    replay: Replay,
+}
+
+#[wasm_bindgen]
+impl Recorder {
+    /// Start recording a fresh universe.
+    #[wasm_bindgen(constructor)]
+    #[must_use]
+    pub fn new(width: u32, height: u32, seed: u64) -> Self {
+        Self::with_boundaries(width, height, seed, Boundaries::default())
+    }
+
+    /// Start recording a fresh universe with the given behaviour at each
+    /// edge.
+    #[must_use]
+    pub fn new_with_boundaries(
+        width: u32,
+        height: u32,
+        seed: u64,
+        left: Boundary,
+        right: Boundary,
+        top: Boundary,
+        bottom: Boundary,
+    ) -> Self {
+        let boundaries = Boundaries {
+            left,
+            right,
+            top,
+            bottom,
+        };
+        Self::with_boundaries(width, height, seed, boundaries)
+    }
+
+    fn with_boundaries(width: u32, height: u32, seed: u64, boundaries: Boundaries) -> Self {
+        Self {
+            universe: universe(width, height, seed, boundaries),
+            replay: Replay {
+                width,
+                height,
+                seed,
+                boundaries,
+                events: Vec::new(),
+            },
+        }
+    }
+
+    fn log(&mut self, event: Event) {
+        self.replay.events.push((self.universe.tick_count(), event));
+    }
+
+    pub fn tick(&mut self) {
+        let tick = self.universe.tick_count();
+        self.universe.tick();
+        let hash = self.universe.grid.state_hash();
+        self.replay.events.push((tick, Event::Tick { hash }));
+    }
+
+    pub fn set_cell(&mut self, x: u32, y: u32, species: u8) {
+        self.universe.set_cell(x as usize, y as usize, species);
+        self.log(Event::SetCell { x, y, species });
+    }
+
+    pub fn set_ghost(&mut self, x: u32, y: u32, group: u8, rb: u8) {
+        self.universe.set_ghost(x as usize, y as usize, group, rb);
+        self.log(Event::SetGhost { x, y, group, rb });
+    }
+
+    pub fn alloc_ghost_group(&mut self) -> u8 {
+        let group = self.universe.alloc_ghost_group();
+        self.log(Event::AllocGhostGroup { group });
+        group
+    }
+
+    pub fn paint_circle(
+        &mut self,
+        cx: i32,
+        cy: i32,
+        r: u32,
+        species: u8,
+        mode: PaintMode,
+        target: u8,
+    ) {
+        self.universe.paint_circle(cx, cy, r, species, mode, target);
+        let brush = BrushArgs {
+            species,
+            mode,
+            target,
+        };
+        self.log(Event::PaintCircle {
+            x: cx,
+            y: cy,
+            radius: r,
+            brush,
+        });
+    }
+
+    // `allow`, not `expect`: see `Universe::paint_line`.
+    #[allow(clippy::too_many_arguments, reason = "mirrors Universe::paint_line")]
+    pub fn paint_line(
+        &mut self,
+        x0: i32,
+        y0: i32,
+        x1: i32,
+        y1: i32,
+        radius: u32,
+        species: u8,
+        mode: PaintMode,
+        target: u8,
+    ) {
+        self.universe
+            .paint_line(x0, y0, x1, y1, radius, species, mode, target);
+        let brush = BrushArgs {
+            species,
+            mode,
+            target,
+        };
+        self.log(Event::PaintLine {
+            x0,
+            y0,
+            x1,
+            y1,
+            radius,
+            brush,
+        });
+    }
+
+    #[allow(clippy::too_many_arguments, reason = "mirrors Universe::paint_rect")]
+    pub fn paint_rect(
+        &mut self,
+        x0: i32,
+        y0: i32,
+        x1: i32,
+        y1: i32,
+        species: u8,
+        mode: PaintMode,
+        target: u8,
+    ) {
+        self.universe
+            .paint_rect(x0, y0, x1, y1, species, mode, target);
+        let brush = BrushArgs {
+            species,
+            mode,
+            target,
+        };
+        self.log(Event::PaintRect {
+            x0,
+            y0,
+            x1,
+            y1,
+            brush,
+        });
+    }
+
+    pub fn flood_fill(&mut self, x: i32, y: i32, species: u8, connectivity: Connectivity) -> u32 {
+        let filled = self.universe.flood_fill(x, y, species, connectivity);
+        self.log(Event::FloodFill {
+            x,
+            y,
+            species,
+            connectivity,
+        });
+        filled
+    }
+
+    pub fn replace_species(&mut self, from: u8, to: u8, x0: i32, y0: i32, x1: i32, y1: i32) {
+        self.universe.replace_species(from, to, x0, y0, x1, y1);
+        self.log(Event::ReplaceSpecies {
+            from,
+            to,
+            x0,
+            y0,
+            x1,
+            y1,
+        });
+    }
+
+    pub fn begin_stroke(&mut self) {
+        self.universe.begin_stroke();
+        self.log(Event::BeginStroke);
+    }
+
+    pub fn end_stroke(&mut self) {
+        self.universe.end_stroke();
+        self.log(Event::EndStroke);
+    }
+
+    pub fn undo(&mut self) -> bool {
+        let undone = self.universe.undo();
+        self.log(Event::Undo);
+        undone
+    }
+
+    pub fn redo(&mut self) -> bool {
+        let redone = self.universe.redo();
+        self.log(Event::Redo);
+        redone
+    }
+
+    pub fn set_history_limit(&mut self, bytes: usize) {
+        self.universe.set_history_limit(bytes);
+        self.log(Event::SetHistoryLimit {
+            bytes: bytes as u64,
+        });
+    }
+
+    pub fn clear_history(&mut self) {
+        self.universe.clear_history();
+        self.log(Event::ClearHistory);
+    }
+
+    /// Place a built-in stamp as `Universe::place_stamp` does.
+    ///
+    /// # Errors
+    ///
+    /// Throws if there is no such stamp or not enough empty room.
+    #[wasm_bindgen(js_name = place_stamp)]
+    pub fn place_stamp_js(&mut self, name: &str, x: i32, y: i32) -> Result<(), JsError> {
+        self.place_stamp(name, x, y).map_err(JsError::from)
+    }
+
+    pub fn set_cursor(&mut self, x: i32, y: i32) {
+        self.universe.set_cursor(x, y);
+        self.log(Event::SetCursor { x, y });
+    }
+
+    pub fn clear_cursor(&mut self) {
+        self.universe.clear_cursor();
+        self.log(Event::ClearCursor);
+    }
+
+    /// The log so far in the stable replay format.
+    #[must_use]
+    pub fn save_log(&self) -> Vec<u8> {
+        self.replay.to_bytes()
+    }
+
+    #[must_use]
+    pub fn tick_count(&self) -> u64 {
+        self.universe.tick_count()
+    }
+
+    #[must_use]
+    pub fn species_ptr(&self) -> *const u8 {
+        self.universe.species_ptr()
+    }
+
+    #[must_use]
+    pub fn cell_render_ptr(&self) -> *const u8 {
+        self.universe.cell_render_ptr()
+    }
+
+    #[must_use]
+    pub fn heat_ptr(&self) -> *const u8 {
+        self.universe.heat_ptr()
+    }
+
+    #[must_use]
+    pub fn width(&self) -> usize {
+        self.universe.width()
+    }
+
+    #[must_use]
+    pub fn height(&self) -> usize {
+        self.universe.height()
+    }
+}
+
+impl Recorder {
+    /// Place the built-in stamp `name` centred on `(x, y)`, logging the
+    /// placement if it succeeds.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`stamp::StampError`] if there is no such stamp or not
+    /// enough empty room; nothing is logged then.
+    pub fn place_stamp(&mut self, name: &str, x: i32, y: i32) -> Result<(), stamp::StampError> {
+        self.universe.place_stamp(name, x, y)?;
+        if let Some(stamp) = stamp::stamp(name) {
+            self.log(Event::PlaceStamp {
+                name: stamp.name,
+                x,
+                y,
+            });
+        }
+        Ok(())
+    }
+
+    /// The recorded universe.
+    #[must_use]
+    pub fn universe(&self) -> &Universe {
+        &self.universe
+    }
+
+    /// Stop recording and return the log.
+    #[must_use]
+    pub fn finish(self) -> Replay {
+        self.replay
+    }
+}
+
+/// Plays a [`Replay`] back into a fresh universe, one tick at a time.
+#[derive(Debug)]
+pub struct Replayer {
+    universe: Universe,
+    events: std::vec::IntoIter<(u64, Event)>,
+}
+
+impl Replayer {
+    /// Set up playback of `replay`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ReplayError::TooLarge`] if the grid has more cells than
+    /// [`Replay::from_bytes`] accepts.
+    pub fn new(replay: Replay) -> Result<Self, ReplayError> {
+        check_size(replay.width, replay.height)?;
+        Ok(Self {
+            universe: universe(replay.width, replay.height, replay.seed, replay.boundaries),
+            events: replay.events.into_iter(),
+        })
+    }
+
+    /// The universe being replayed into.
+    #[must_use]
+    pub fn universe(&self) -> &Universe {
+        &self.universe
+    }
+
+    /// Apply events up to and including the next tick.
+    ///
+    /// Returns `Ok(false)` once the log is exhausted.
+    ///
+    /// # Errors
+    ///
+    /// Returns a desync [`ReplayError`] if the state hash after the tick
+    /// or an allocated ghost group differs from the recording or a stamp
+    /// no longer fits, and a
+    /// malformed one if an event is tagged with the wrong tick.
+    pub fn step(&mut self) -> Result<bool, ReplayError> {
+        let mut applied = false;
+        for (tick, event) in self.events.by_ref() {
+            applied = true;
+            if tick != self.universe.tick_count() {
+                return Err(ReplayError::Malformed);
+            }
+            let desync = || ReplayError::Desync { tick };
+            match event {
+                Event::AllocGhostGroup { group } => {
+                    if self.universe.alloc_ghost_group() != group {
+                        return Err(desync());
+                    }
+                }
+                Event::PlaceStamp { name, x, y } => {
+                    if self.universe.place_stamp(name, x, y).is_err() {
+                        return Err(desync());
+                    }
+                }
+                Event::Tick { hash } => {
+                    self.universe.tick();
+                    if self.universe.grid.state_hash() != hash {
+                        return Err(ReplayError::Desync { tick: tick + 1 });
+                    }
+                    return Ok(true);
+                }
+                edit => Self::apply(&mut self.universe, edit),
+            }
+        }
+        Ok(applied)
+    }
+
+    /// Apply an event that edits the universe without returning anything
+    /// the log checks.
+    fn apply(universe: &mut Universe, event: Event) {
+        match event {
+            Event::SetCell { x, y, species } => {
+                universe.set_cell(x as usize, y as usize, species);
+            }
+            Event::SetGhost { x, y, group, rb } => {
+                universe.set_ghost(x as usize, y as usize, group, rb);
+            }
+            Event::SetCursor { x, y } => universe.set_cursor(x, y),
+            Event::ClearCursor => universe.clear_cursor(),
+            Event::PaintCircle {
+                x,
+                y,
+                radius,
+                brush,
+            } => universe.paint_circle(x, y, radius, brush.species, brush.mode, brush.target),
+            Event::PaintLine {
+                x0,
+                y0,
+                x1,
+                y1,
+                radius,
+                brush,
+            } => universe.paint_line(
+                x0,
+                y0,
+                x1,
+                y1,
+                radius,
+                brush.species,
+                brush.mode,
+                brush.target,
+            ),
+            Event::PaintRect {
+                x0,
+                y0,
+                x1,
+                y1,
+                brush,
+            } => universe.paint_rect(x0, y0, x1, y1, brush.species, brush.mode, brush.target),
+            Event::FloodFill {
+                x,
+                y,
+                species,
+                connectivity,
+            } => {
+                universe.flood_fill(x, y, species, connectivity);
+            }
+            Event::ReplaceSpecies {
+                from,
+                to,
+                x0,
+                y0,
+                x1,
+                y1,
+            } => universe.replace_species(from, to, x0, y0, x1, y1),
+            Event::BeginStroke => universe.begin_stroke(),
+            Event::EndStroke => universe.end_stroke(),
+            Event::Undo => {
+                universe.undo();
+            }
+            Event::Redo => {
+                universe.redo();
+            }
+            Event::SetHistoryLimit { bytes } => {
+                universe.set_history_limit(usize::try_from(bytes).unwrap_or(usize::MAX));
+            }
+            Event::ClearHistory => universe.clear_history(),
+            Event::AllocGhostGroup { .. } | Event::PlaceStamp { .. } | Event::Tick { .. } => {
+                unreachable!("checked by `step`")
+            }
+        }
+    }
+
+    /// Replay every remaining event and return the final universe.
+    ///
+    /// # Errors
+    ///
+    /// See [`Replayer::step`].
+    pub fn run(mut self) -> Result<Universe, ReplayError> {
+        while self.step()? {}
+        Ok(self.universe)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A short session touching every kind of event.
+    fn record_session() -> Recorder {
+        let mut rec = Recorder::new(64, 48, 11);
+        for x in 10..50 {
+            rec.set_cell(x, 3, 1); // Sand
+            rec.set_cell(x, 8, 2); // Water
+        }
+        rec.set_cell(30, 40, 4); // Fire
+        let group = rec.alloc_ghost_group();
+        for y in 20..23 {
+            for x in 5..8 {
+                rec.set_ghost(x, y, group, 0);
+            }
+        }
+        rec.place_stamp("ghost", 20, 26).unwrap();
+        let events = rec.replay.events.len();
+        assert!(rec.place_stamp("ghost", 20, 26).is_err());
+        assert_eq!(
+            rec.replay.events.len(),
+            events,
+            "failed stamps aren't logged"
+        );
+        rec.paint_circle(50, 30, 3, 3, PaintMode::FillEmpty, 0); // Wall
+        rec.paint_line(-5, 44, 70, 40, 1, 13, PaintMode::Replace, 0); // Wood
+        rec.paint_rect(20, 20, 24, 18, 14, PaintMode::ReplaceSpecies, 0); // Oil
+        assert!(rec.flood_fill(0, 47, 12, Connectivity::Eight) > 0); // Ice
+        rec.replace_species(13, 8, 0, 0, 30, 47); // Wood to Lava
+        rec.set_history_limit(1 << 20);
+        rec.begin_stroke();
+        rec.paint_circle(12, 30, 2, 9, PaintMode::Replace, 0); // Stone
+        rec.end_stroke();
+        rec.begin_stroke();
+        rec.paint_circle(56, 30, 2, 11, PaintMode::Replace, 0); // Glass
+        assert!(rec.undo());
+        assert!(rec.undo());
+        assert!(rec.redo());
+        rec.set_cursor(40, 10);
+        for i in 0..60 {
+            if i == 30 {
+                rec.clear_cursor();
+                rec.set_cell(12, 1, 6); // Smoke
+            }
+            rec.tick();
+        }
+        rec
+    }
+
+    #[test]
+    fn replay_reproduces_the_recording_headless() {
+        let rec = record_session();
+        let bytes = rec.save_log();
+        let expected = rec.universe().grid.cells.clone();
+
+        let replay = Replay::from_bytes(&bytes).unwrap();
+        assert_eq!(replay, rec.finish());
+        let universe = Replayer::new(replay).unwrap().run().unwrap();
+        assert_eq!(universe.tick_count(), 60);
+        assert_eq!(universe.grid.cells, expected);
+    }
+
+    #[test]
+    fn tampered_log_reports_the_desync_tick() {
+        let mut replay = record_session().finish();
+        // Drop the smoke placed before tick 31.
+        let i = replay
+            .events
+            .iter()
+            .position(|&(_, e)| {
+                e == Event::SetCell {
+                    x: 12,
+                    y: 1,
+                    species: 6,
+                }
+            })
+            .unwrap();
+        replay.events.remove(i);
+
+        let err = Replayer::new(replay).unwrap().run().unwrap_err();
+        assert_eq!(err, ReplayError::Desync { tick: 31 });
+    }
+
+    #[test]
+    fn edge_boundaries_are_replayed() {
+        let (wrap, void) = (Boundary::Wrap, Boundary::Void);
+        let mut rec = Recorder::new_with_boundaries(16, 12, 3, wrap, wrap, void, void);
+        for y in 0..6 {
+            rec.set_cell(0, y, 2); // Water
+            rec.set_cell(15, y, 1); // Sand
+        }
+        for _ in 0..30 {
+            rec.tick();
+        }
+        let expected = rec.universe().grid.cells.clone();
+
+        let replay = Replay::from_bytes(&rec.save_log()).unwrap();
+        assert_eq!(replay.boundaries, rec.universe().grid.boundaries);
+        let universe = Replayer::new(replay).unwrap().run().unwrap();
+        assert_eq!(universe.grid.cells, expected);
+    }
+
+    #[test]
+    fn malformed_logs_are_rejected() {
+        let bytes = record_session().save_log();
+        let malformed = Err(ReplayError::Malformed);
+        assert_eq!(Replay::from_bytes(&bytes[..bytes.len() - 1]), malformed);
+        assert_eq!(Replay::from_bytes(b"SNDX"), malformed);
+
+        let mut bad = bytes.clone();
+        bad[26] = 99; // first event tag
+        assert_eq!(Replay::from_bytes(&bad), malformed);
+        let mut bad = bytes.clone();
+        bad[22] = 9; // left edge
+        assert_eq!(Replay::from_bytes(&bad), malformed);
+
+        let mut rec = Recorder::new(4, 4, 1);
+        rec.paint_circle(1, 1, 1, 1, PaintMode::Replace, 0);
+        let mut bad = rec.save_log();
+        let len = bad.len();
+        bad[len - 2] = 7; // mode
+        assert_eq!(Replay::from_bytes(&bad), malformed);
+
+        let mut replay = Replay::from_bytes(&bytes).unwrap();
+        replay.events[0].0 = 5;
+        assert_eq!(Replayer::new(replay).unwrap().run().err(), malformed.err());
+    }
+
+    #[test]
+    fn huge_grids_are_refused_before_allocating() {
+        let mut bytes = Recorder::new(4, 4, 1).save_log();
+        bytes[6..14].copy_from_slice(&[0xFF; 8]);
+        assert_eq!(Replay::from_bytes(&bytes), Err(ReplayError::TooLarge));
+
+        let replay = Replay {
+            width: 1 << 16,
+            height: 1 << 16,
+            seed: 1,
+            boundaries: Boundaries::default(),
+            events: Vec::new(),
+        };
+        assert_eq!(Replayer::new(replay).err(), Some(ReplayError::TooLarge));
+    }
+}
@@ -0,0 +1,221 @@
+//! Changing grid dimensions while keeping the world.
+//!
+//! [`Grid::resize`] copies every cell (and its heat) to a new grid of the
+//! requested size, placed relative to an [`Anchor`]. New space is Empty at
+//! ambient heat; content that falls outside is cropped. The tick counter,
+//! RNG, reactions, boundaries and ghost group counter carry over.
+//!
+//! Ghost groups move as rigid shapes, so cropping one would leave a broken
+//! fragment. A group the new border would cut is instead nudged back
+//! inside by the smallest shift that fits it, provided every cell it
+//! lands on is Empty; otherwise the whole group is removed.
+
+use crate::cell::{Cell, Species};
+use crate::Grid;
+use wasm_bindgen::prelude::*;
+
+/// Which part of the world stays put when the grid is resized.
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[repr(u8)]
+pub enum Anchor {
+    /// Keep the top-left corner; grow or crop on the right and bottom.
+    #[default]
+    TopLeft = 0,
+    /// Keep the floor: centred horizontally, grow or crop at the top.
+    BottomCenter = 1,
+    /// Keep the middle; grow or crop evenly on all sides.
+    Center = 2,
+}
+
+impl Anchor {
+    /// Where the old grid's top-left cell lands in the new grid.
+    pub(crate) fn offset(self, old: (usize, usize), new: (usize, usize)) -> (i32, i32) {
+        let dw = new.0 as i32 - old.0 as i32;
+        let dh = new.1 as i32 - old.1 as i32;
+        match self {
+            Self::TopLeft => (0, 0),
+            Self::BottomCenter => (dw.div_euclid(2), dh),
+            Self::Center => (dw.div_euclid(2), dh.div_euclid(2)),
+        }
+    }
+}
+
+/// A ghost group's cells and their heat, at their positions in the
+/// resized grid.
+type GhostCells = Vec<(i32, i32, Cell, u8)>;
+
+/// Shift that moves the span `min..=max` inside `0..len`, or `None` if it
+/// is too long to fit.
+fn fit(min: i32, max: i32, len: i32) -> Option<i32> {
+    if max - min >= len {
+        None
+    } else if min < 0 {
+        Some(-min)
+    } else {
+        Some((len - 1 - max).min(0))
+    }
+}
+
+impl Grid {
+    /// Change the grid to `width`×`height`, keeping content placed
+    /// according to `anchor`; see the [module docs](self).
+    pub fn resize(&mut self, width: usize, height: usize, anchor: Anchor) {
+        let (ox, oy) = anchor.offset((self.width, self.height), (width, height));
+        let mut resized = Self::with_boundaries(width, height, 0, self.boundaries);
+        resized.tick_count = self.tick_count;
+        resized.generation = self.generation;
+        resized.rng = self.rng;
+        resized.next_ghost_group = self.next_ghost_group;
+        std::mem::swap(&mut resized.reactions, &mut self.reactions);
+
+        // Ghost cells per group, in new coordinates, placed once every
+        // other cell is down.
+        let mut ghosts: Vec<(u8, GhostCells)> = Vec::new();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let i = y * self.width + x;
+                let (cell, heat) = (self.cells[i], self.heat[i]);
+                let (nx, ny) = (x as i32 + ox, y as i32 + oy);
+                if cell.species == Species::Ghost {
+                    let ghost = (nx, ny, cell, heat);
+                    match ghosts.iter_mut().find(|(group, _)| *group == cell.ra) {
+                        Some((_, cells)) => cells.push(ghost),
+                        None => ghosts.push((cell.ra, vec![ghost])),
+                    }
+                } else if resized.in_bounds(nx, ny) {
+                    let j = ny as usize * width + nx as usize;
+                    resized.cells[j] = cell;
+                    resized.heat[j] = heat;
+                }
+            }
+        }
+        resized.heat_active = self.heat_active;
+
+        // Whole groups first, so a nudged group can't take their place.
+        let (whole, cut): (Vec<_>, Vec<_>) = ghosts
+            .into_iter()
+            .map(|(_, cells)| cells)
+            .partition(|cells| cells.iter().all(|&(x, y, ..)| resized.in_bounds(x, y)));
+        for cells in whole.iter().chain(&cut) {
+            let (mut min_x, mut max_x, mut min_y, mut max_y) =
+                (i32::MAX, i32::MIN, i32::MAX, i32::MIN);
+            for &(x, y, ..) in cells {
+                (min_x, max_x) = (min_x.min(x), max_x.max(x));
+                (min_y, max_y) = (min_y.min(y), max_y.max(y));
+            }
+            let shift = fit(min_x, max_x, width as i32).zip(fit(min_y, max_y, height as i32));
+            let Some((sx, sy)) = shift else { continue };
+            let index = |x: i32, y: i32| (y + sy) as usize * width + (x + sx) as usize;
+            if cells
+                .iter()
+                .all(|&(x, y, ..)| resized.cells[index(x, y)].species == Species::Empty)
+            {
+                for &(x, y, cell, heat) in cells {
+                    resized.cells[index(x, y)] = cell;
+                    resized.heat[index(x, y)] = heat;
+                }
+            }
+        }
+
+        resized.wake_all();
+        *self = resized;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ascii::assert_ascii_eq;
+
+    fn world() -> Grid {
+        Grid::from_ascii(
+            "
+            ....
+            .s~.
+            ####
+            ",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn anchors_place_the_old_content() {
+        let mut g = world();
+        g.resize(6, 4, Anchor::TopLeft);
+        assert_ascii_eq(&g, "...... / .s~... / ####.. / ......");
+
+        let mut g = world();
+        g.resize(6, 4, Anchor::BottomCenter);
+        assert_ascii_eq(&g, "...... / ...... / ..s~.. / .####.");
+
+        let mut g = world();
+        g.resize(6, 5, Anchor::Center);
+        assert_ascii_eq(&g, "...... / ...... / ..s~.. / .####. / ......");
+    }
+
+    #[test]
+    fn shrinking_crops() {
+        let mut g = world();
+        g.resize(2, 2, Anchor::BottomCenter);
+        assert_ascii_eq(&g, "s~ / ##");
+        let mut g = world();
+        g.resize(3, 2, Anchor::TopLeft);
+        assert_ascii_eq(&g, "... / .s~");
+    }
+
+    #[test]
+    fn keeps_state_and_heat() {
+        let mut g = world();
+        g.tick();
+        g.set_heat(2, 1, 90);
+        let (tick, rng, group) = (g.tick_count, g.rng, g.next_ghost_group);
+        g.resize(8, 8, Anchor::Center);
+        assert_eq!(
+            (g.tick_count, g.rng, g.next_ghost_group),
+            (tick, rng, group)
+        );
+        assert_eq!(g.heat_at(4, 3), 90);
+        assert_eq!(g.heat_at(0, 0), crate::heat::AMBIENT_HEAT);
+        assert_eq!(g.heat().len(), 64);
+        g.tick();
+    }
+
+    #[test]
+    fn ghosts_keep_their_heat() {
+        let mut g = Grid::from_ascii("gg.. / gg.. / .... / .###").unwrap();
+        g.set_heat(0, 0, 80);
+        g.set_heat(1, 1, 70);
+        g.resize(3, 4, Anchor::Center);
+        // Nudged back to x = 0..=1, as in `cut_ghosts_are_nudged_inside_or_removed`.
+        assert_eq!(g.heat_at(0, 0), 80);
+        assert_eq!(g.heat_at(1, 1), 70);
+
+        let mut g = Grid::from_ascii(".gg. / .gg. / ....").unwrap();
+        g.set_heat(2, 1, 90);
+        g.resize(6, 3, Anchor::TopLeft);
+        assert_eq!(g.heat_at(2, 1), 90);
+    }
+
+    #[test]
+    fn cut_ghosts_are_nudged_inside_or_removed() {
+        let mut g = Grid::from_ascii(".gg... / .gg... / ...... / ....##").unwrap();
+        g.resize(5, 4, Anchor::BottomCenter);
+        // The ghost at x = 1..=2 lands at x = 0..=1 without a cut: untouched.
+        assert_ascii_eq(&g, "gg... / gg... / ..... / ...##");
+
+        let mut g = Grid::from_ascii("gg.. / gg.. / .... / .###").unwrap();
+        g.resize(3, 4, Anchor::Center);
+        // Offset -1 would cut the ghost; it is pushed right into the space.
+        assert_ascii_eq(&g, "gg. / gg. / ... / ###");
+
+        let mut g = Grid::from_ascii("gg. / gg# / ...").unwrap();
+        g.resize(2, 3, Anchor::Center);
+        // Shifted back in, it would land on the wall: removed instead.
+        assert_ascii_eq(&g, ".. / .# / ..");
+
+        let mut g = Grid::from_ascii("ggg / ggg").unwrap();
+        g.resize(2, 2, Anchor::TopLeft);
+        assert_ascii_eq(&g, ".. / ..");
+    }
+}
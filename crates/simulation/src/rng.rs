@@ -0,0 +1,139 @@
+//! Seedable pseudo-random number generator owned by the grid.
+//!
+//! Uses xorshift64* — a single `u64` of state, a few instructions per
+//! draw, and no platform- or pointer-width-dependent behaviour, so the
+//! same seed produces the same stream on native and wasm32 builds.
+
+/// Seed used by `Grid::new` and `Universe::new` when none is given.
+pub const DEFAULT_SEED: u64 = 0x5EED_5A4D;
+
+/// xorshift64* generator. The state is never zero.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Create a generator from any seed, including zero.
+    #[must_use]
+    pub fn new(seed: u64) -> Self {
+        // splitmix64 spreads low-entropy seeds (0, 1, 2, ...) across the
+        // state space and never yields zero for xorshift.
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        Self { state: z.max(1) }
+    }
+
+    /// Raw generator state, for snapshots.
+    #[must_use]
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+
+    /// Rebuild a generator from a value returned by [`Rng::state`].
+    ///
+    /// A zero state is invalid for xorshift and is bumped to one.
+    #[must_use]
+    pub fn from_state(state: u64) -> Self {
+        Self {
+            state: state.max(1),
+        }
+    }
+
+    /// Next 64 random bits.
+    pub fn next_u64(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.state = x;
+        x.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Next 32 random bits (the high half, which is the better-mixed one).
+    pub fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    /// Uniform value in `0..n`. Returns 0 when `n` is 0.
+    pub fn below(&mut self, n: u32) -> u32 {
+        ((u64::from(self.next_u32()) * u64::from(n)) >> 32) as u32
+    }
+
+    /// Uniform random byte.
+    pub fn byte(&mut self) -> u8 {
+        (self.next_u32() >> 24) as u8
+    }
+
+    /// Derive an independent generator for `key` without advancing this one.
+    #[must_use]
+    pub fn fork(&self, key: u64) -> Self {
+        Self::new(self.state ^ key.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+    }
+}
+
+impl Default for Rng {
+    fn default() -> Self {
+        Self::new(DEFAULT_SEED)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::{any, prop_assert, proptest};
+
+    #[test]
+    fn zero_seed_is_usable() {
+        let mut rng = Rng::new(0);
+        assert_ne!(rng.state(), 0);
+        assert_ne!(rng.next_u64(), rng.next_u64());
+    }
+
+    #[test]
+    fn state_round_trips() {
+        let mut rng = Rng::new(42);
+        rng.next_u64();
+        let mut restored = Rng::from_state(rng.state());
+        assert_eq!(rng.next_u64(), restored.next_u64());
+    }
+
+    #[test]
+    fn known_stream_is_stable() {
+        // Pin the first outputs so an accidental algorithm change shows up
+        // as a test failure rather than as a replay desync.
+        let mut rng = Rng::new(1);
+        let first: Vec<u64> = (0..3).map(|_| rng.next_u64()).collect();
+        assert_eq!(
+            first,
+            [
+                0x4b46_a55d_f361_1b9b,
+                0xd7e1_f141_0e76_3ef4,
+                0x5f14_ec66_975f_9b06
+            ]
+        );
+        assert_ne!(Rng::new(1).next_u64(), Rng::new(2).next_u64());
+    }
+
+    #[test]
+    fn fork_does_not_advance_parent() {
+        let rng = Rng::new(7);
+        let before = rng.state();
+        let a = rng.fork(1);
+        let b = rng.fork(2);
+        assert_eq!(rng.state(), before);
+        assert_ne!(a, b);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_below_stays_in_range(seed in any::<u64>(), n in 1u32..1000) {
+            let mut rng = Rng::new(seed);
+            for _ in 0..100 {
+                prop_assert!(rng.below(n) < n);
+            }
+        }
+    }
+}
@@ -0,0 +1,494 @@
+//! Versioned binary snapshots of a [`Grid`].
+//!
+//! A snapshot holds everything a future tick reads, so loading one and
+//! ticking gives exactly what the original grid would have produced. All
+//! integers are little-endian:
+//!
+//! | Offset | Size        | Field                                  |
+//! |--------|-------------|----------------------------------------|
+//! | 0      | 4           | magic `b"SNDS"`                        |
+//! | 4      | 2           | format version ([`SNAPSHOT_VERSION`])  |
+//! | 6      | 4           | width                                  |
+//! | 10     | 4           | height                                 |
+//! | 14     | 8           | tick counter                           |
+//! | 22     | 8           | RNG state                              |
+//! | 30     | 1           | next ghost group                       |
+//! | 31     | 4           | left, right, top, bottom edge ids      |
+//! | 35     | 4 × cells   | `species, ra, rb, clock` per cell      |
+//! | …      | cells       | heat per cell                          |
+//! | …      | 2 + 6 × n   | reaction table (`Reactions::encode`)   |
+//! | …      | 1 + 32 × k  | chunk schedule (`encode_schedule`)     |
+//!
+//! Cells are row-major. The chunk schedule is stored because it decides
+//! which cells a tick visits, and so what they draw from the RNG; a loaded
+//! grid resumes exactly where the original left off. Only the current
+//! version is accepted.
+
+use crate::boundary::Boundaries;
+use crate::cell::{Cell, Species};
+use crate::chunk::{DirtyRect, CHUNK_SIZE};
+use crate::elements::Reactions;
+use crate::rng::Rng;
+use crate::Grid;
+use std::fmt;
+
+/// Current snapshot format version.
+pub const SNAPSHOT_VERSION: u16 = 4;
+
+const MAGIC: &[u8; 4] = b"SNDS";
+const HEADER_LEN: usize = 35;
+/// Bytes stored per cell: four for the `Cell`, one for its heat.
+const BYTES_PER_CELL: usize = 5;
+
+/// A snapshot could not be decoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SnapshotError {
+    /// The input does not start with the snapshot magic bytes.
+    BadMagic,
+    /// The snapshot was written by a newer or unknown format version.
+    UnsupportedVersion(u16),
+    /// The input ends before the data its header announces.
+    Truncated,
+    /// The dimensions don't fit in memory on this platform.
+    TooLarge,
+    /// Cell `index` holds a species id this build doesn't know.
+    UnknownSpecies { index: usize, id: u8 },
+    /// Extra bytes follow the end of the snapshot.
+    TrailingBytes,
+    /// Encoded data is inconsistent, e.g. runs that overshoot the grid or
+    /// a compressed payload that fails to inflate.
+    Corrupt,
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            SnapshotError::BadMagic => f.write_str("not a sandsim snapshot"),
+            SnapshotError::UnsupportedVersion(v) => write!(f, "unsupported snapshot version {v}"),
+            SnapshotError::Truncated => f.write_str("snapshot is truncated"),
+            SnapshotError::TooLarge => f.write_str("snapshot dimensions are too large"),
+            SnapshotError::UnknownSpecies { index, id } => {
+                write!(f, "unknown species id {id} in cell {index}")
+            }
+            SnapshotError::TrailingBytes => f.write_str("unexpected bytes after snapshot"),
+            SnapshotError::Corrupt => f.write_str("snapshot data is corrupt"),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+/// Bounds-checked little-endian reader.
+pub(crate) struct Reader<'a> {
+    pub(crate) bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn take(&mut self, n: usize) -> Result<&'a [u8], SnapshotError> {
+        if self.bytes.len() < n {
+            return Err(SnapshotError::Truncated);
+        }
+        let (head, rest) = self.bytes.split_at(n);
+        self.bytes = rest;
+        Ok(head)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], SnapshotError> {
+        let mut out = [0; N];
+        out.copy_from_slice(self.take(N)?);
+        Ok(out)
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, SnapshotError> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub(crate) fn u16(&mut self) -> Result<u16, SnapshotError> {
+        self.array().map(u16::from_le_bytes)
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, SnapshotError> {
+        self.array().map(u32::from_le_bytes)
+    }
+
+    /// Four edge ids, as written by `Boundaries::to_ids`.
+    pub(crate) fn boundaries(&mut self) -> Result<Boundaries, SnapshotError> {
+        Boundaries::from_ids(self.array()?).ok_or(SnapshotError::Corrupt)
+    }
+
+    pub(crate) fn u64(&mut self) -> Result<u64, SnapshotError> {
+        self.array().map(u64::from_le_bytes)
+    }
+
+    fn i32(&mut self) -> Result<i32, SnapshotError> {
+        self.array().map(i32::from_le_bytes)
+    }
+
+    /// A dirty rect that must be empty or lie inside `bounds`.
+    fn rect(&mut self, bounds: DirtyRect) -> Result<DirtyRect, SnapshotError> {
+        let rect = DirtyRect {
+            min_x: self.i32()?,
+            min_y: self.i32()?,
+            max_x: self.i32()?,
+            max_y: self.i32()?,
+        };
+        if rect.is_empty() {
+            Ok(DirtyRect::EMPTY)
+        } else if rect.intersect(bounds) == rect {
+            Ok(rect)
+        } else {
+            Err(SnapshotError::Corrupt)
+        }
+    }
+}
+
+/// Length of the chunk schedule of a `width`×`height` grid.
+pub(crate) fn schedule_len(width: usize, height: usize) -> usize {
+    1 + width.div_ceil(CHUNK_SIZE) * height.div_ceil(CHUNK_SIZE) * 32
+}
+
+impl Grid {
+    /// Append the chunk schedule: the heat-active flag (1), then each
+    /// chunk's `current` and `next` rects in chunk order, each as
+    /// `min_x, min_y, max_x, max_y` (4 each, signed).
+    pub(crate) fn encode_schedule(&self, out: &mut Vec<u8>) {
+        out.push(u8::from(self.heat_active));
+        for chunk in &self.chunks {
+            for rect in [chunk.current, chunk.next] {
+                for v in [rect.min_x, rect.min_y, rect.max_x, rect.max_y] {
+                    out.extend_from_slice(&v.to_le_bytes());
+                }
+            }
+        }
+    }
+
+    /// Restore a schedule written by [`Grid::encode_schedule`] for a grid
+    /// of the same size.
+    pub(crate) fn decode_schedule(&mut self, reader: &mut Reader) -> Result<(), SnapshotError> {
+        self.heat_active = match reader.u8()? {
+            0 => false,
+            1 => true,
+            _ => return Err(SnapshotError::Corrupt),
+        };
+        for chunk in &mut self.chunks {
+            chunk.current = reader.rect(chunk.bounds)?;
+            chunk.next = reader.rect(chunk.bounds)?;
+        }
+        Ok(())
+    }
+}
+
+impl Grid {
+    /// Serialize the full grid state into a versioned binary snapshot.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a dimension exceeds `u32::MAX`, which no grid can reach
+    /// in practice.
+    #[must_use]
+    pub fn to_snapshot(&self) -> Vec<u8> {
+        let cells = self.cells.len();
+        let mut out = Vec::with_capacity(HEADER_LEN + cells * BYTES_PER_CELL);
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&SNAPSHOT_VERSION.to_le_bytes());
+        out.extend_from_slice(
+            &u32::try_from(self.width)
+                .expect("width fits in u32")
+                .to_le_bytes(),
+        );
+        out.extend_from_slice(
+            &u32::try_from(self.height)
+                .expect("height fits in u32")
+                .to_le_bytes(),
+        );
+        out.extend_from_slice(&self.tick_count.to_le_bytes());
+        out.extend_from_slice(&self.rng.state().to_le_bytes());
+        out.push(self.next_ghost_group);
+        out.extend_from_slice(&self.boundaries.to_ids());
+        for cell in &self.cells {
+            out.extend_from_slice(&[cell.species as u8, cell.ra, cell.rb, cell.clock]);
+        }
+        out.extend_from_slice(&self.heat);
+        self.reactions.encode(&mut out);
+        self.encode_schedule(&mut out);
+        out
+    }
+
+    /// Rebuild a grid from bytes produced by [`Grid::to_snapshot`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`SnapshotError`] if the input is not a snapshot, uses an
+    /// unsupported version, is truncated or has trailing bytes, has
+    /// dimensions too large for this platform, or contains an unknown
+    /// species or boundary id or a chunk schedule that doesn't fit the
+    /// grid.
+    pub fn from_snapshot(bytes: &[u8]) -> Result<Self, SnapshotError> {
+        let mut reader = Reader { bytes };
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(SnapshotError::BadMagic);
+        }
+        let version = reader.u16()?;
+        if version != SNAPSHOT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion(version));
+        }
+        let too_large = || SnapshotError::TooLarge;
+        let width = usize::try_from(reader.u32()?).map_err(|_| too_large())?;
+        let height = usize::try_from(reader.u32()?).map_err(|_| too_large())?;
+        let tick_count = reader.u64()?;
+        let rng_state = reader.u64()?;
+        let next_ghost_group = reader.u8()?;
+        let boundaries = reader.boundaries()?;
+
+        // Check the length before allocating anything sized by the header.
+        let cells = width.checked_mul(height).ok_or_else(too_large)?;
+        let body = cells.checked_mul(BYTES_PER_CELL).ok_or_else(too_large)?;
+        let body = reader.take(body)?;
+        let reactions = Reactions::decode(&mut reader)?;
+        let (cell_bytes, heat) = body.split_at(cells * 4);
+
+        let mut grid = Self::new(width, height);
+        for (index, (raw, cell)) in cell_bytes.chunks_exact(4).zip(&mut grid.cells).enumerate() {
+            let Some(species) = Species::from_id(raw[0]) else {
+                return Err(SnapshotError::UnknownSpecies { index, id: raw[0] });
+            };
+            *cell = Cell {
+                species,
+                ra: raw[1],
+                rb: raw[2],
+                clock: raw[3],
+            };
+        }
+        grid.heat.copy_from_slice(heat);
+        grid.tick_count = tick_count;
+        grid.generation = tick_count as u8;
+        grid.rng = Rng::from_state(rng_state);
+        grid.next_ghost_group = next_ghost_group.max(1);
+        grid.boundaries = boundaries;
+        grid.reactions = reactions;
+        grid.decode_schedule(&mut reader)?;
+        if !reader.bytes.is_empty() {
+            return Err(SnapshotError::TrailingBytes);
+        }
+        Ok(grid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boundary::Boundary;
+    use crate::elements::registry::arb_species;
+    use crate::elements::Reaction;
+    use proptest::prelude::*;
+
+    fn arb_grid(w: usize, h: usize) -> impl Strategy<Value = Grid> {
+        (
+            proptest::collection::vec(
+                (arb_species(), any::<u8>(), any::<u8>(), any::<u8>()),
+                w * h,
+            ),
+            any::<u64>(),
+        )
+            .prop_map(move |(cells, seed)| {
+                let mut grid = Grid::with_seed(w, h, seed);
+                for (i, (species, ra, rb, clock)) in cells.into_iter().enumerate() {
+                    // Ghosts move as groups; keep their register meaningful.
+                    let ra = if species == Species::Ghost { 1 } else { ra };
+                    grid.cells[i] = Cell {
+                        species,
+                        ra,
+                        rb,
+                        clock,
+                    };
+                }
+                grid.wake_all();
+                grid
+            })
+    }
+
+    #[test]
+    fn round_trip_keeps_every_field() {
+        let mut grid = Grid::with_seed(40, 30, 9);
+        grid.boundaries = Boundaries {
+            left: Boundary::Wrap,
+            right: Boundary::Wrap,
+            top: Boundary::Void,
+            bottom: Boundary::Wall,
+        };
+        for x in 0..40 {
+            grid.set(x, 5, Cell::new(Species::Sand));
+            grid.set(x, 29, Cell::wall());
+        }
+        grid.set(3, 20, Cell::new(Species::Fire));
+        for _ in 0..25 {
+            grid.tick();
+        }
+        grid.alloc_ghost_group();
+
+        let loaded = Grid::from_snapshot(&grid.to_snapshot()).unwrap();
+        assert_eq!(loaded.width, grid.width);
+        assert_eq!(loaded.height, grid.height);
+        assert_eq!(loaded.cells, grid.cells);
+        assert_eq!(loaded.heat, grid.heat);
+        assert_eq!(loaded.tick_count, grid.tick_count);
+        assert_eq!(loaded.generation, grid.generation);
+        assert_eq!(loaded.rng, grid.rng);
+        assert_eq!(loaded.next_ghost_group, grid.next_ghost_group);
+        assert_eq!(loaded.boundaries, grid.boundaries);
+        assert_eq!(loaded.reactions, grid.reactions);
+    }
+
+    #[test]
+    fn custom_reactions_tick_the_same_after_loading() {
+        let mut grid = Grid::with_seed(16, 12, 5);
+        grid.reactions.remove(Species::Fire, Species::Water);
+        grid.reactions.insert(
+            Species::Sand,
+            Species::Water,
+            Reaction::new(30, Species::Stone, Species::Steam).spawning(Species::Smoke),
+        );
+        for x in 2..14 {
+            grid.set(x, 2, Cell::new(Species::Sand));
+            grid.set(x, 8, Cell::new(Species::Water));
+        }
+        grid.set(8, 10, Cell::new(Species::Fire));
+        grid.tick();
+
+        let mut loaded = Grid::from_snapshot(&grid.to_snapshot()).unwrap();
+        assert_eq!(loaded.reactions, grid.reactions);
+        for _ in 0..30 {
+            grid.tick();
+            loaded.tick();
+        }
+        assert_eq!(loaded.cells, grid.cells);
+    }
+
+    #[test]
+    fn loaded_grid_keeps_the_chunk_schedule() {
+        let mut grid = Grid::with_seed(60, 40, 7);
+        for x in 0..60 {
+            grid.set(x, 39, Cell::wall());
+        }
+        grid.set(1, 38, Cell::wall());
+        grid.set(2, 38, Cell::new(Species::Lava));
+        grid.set(3, 38, Cell::wall());
+        grid.set(20, 38, Cell::new(Species::Acid));
+        grid.set(30, 20, Cell::new(Species::Steam));
+        for _ in 0..5 {
+            grid.tick();
+        }
+
+        let mut loaded = Grid::from_snapshot(&grid.to_snapshot()).unwrap();
+        assert_eq!(loaded.chunks, grid.chunks);
+        for _ in 0..40 {
+            for g in [&mut grid, &mut loaded] {
+                g.set(50, 0, Cell::new(Species::Sand));
+                g.set(52, 0, Cell::new(Species::Water));
+                g.tick();
+            }
+        }
+        assert_eq!(loaded.cells, grid.cells);
+        assert_eq!(loaded.rng, grid.rng);
+        assert_eq!(loaded.chunks, grid.chunks);
+    }
+
+    #[test]
+    fn every_truncation_is_an_error() {
+        let bytes = Grid::new(5, 4).to_snapshot();
+        for len in 0..bytes.len() {
+            let err = Grid::from_snapshot(&bytes[..len]).unwrap_err();
+            assert_eq!(err, SnapshotError::Truncated, "prefix of {len} bytes");
+        }
+    }
+
+    #[test]
+    fn malformed_input_is_rejected() {
+        let bytes = Grid::new(3, 3).to_snapshot();
+
+        let mut bad = bytes.clone();
+        bad[0] = b'X';
+        assert_eq!(
+            Grid::from_snapshot(&bad).unwrap_err(),
+            SnapshotError::BadMagic
+        );
+
+        let mut bad = bytes.clone();
+        bad[4] = 99;
+        assert_eq!(
+            Grid::from_snapshot(&bad).unwrap_err(),
+            SnapshotError::UnsupportedVersion(99)
+        );
+
+        let mut bad = bytes.clone();
+        bad[HEADER_LEN + 4] = 200;
+        assert_eq!(
+            Grid::from_snapshot(&bad).unwrap_err(),
+            SnapshotError::UnknownSpecies { index: 1, id: 200 }
+        );
+
+        let mut bad = bytes.clone();
+        bad[31] = 7; // left edge
+        assert_eq!(
+            Grid::from_snapshot(&bad).unwrap_err(),
+            SnapshotError::Corrupt
+        );
+
+        // The one chunk's `next` rect reaching past the grid.
+        let mut bad = bytes.clone();
+        let next = bad.len() - 16;
+        for (i, v) in [0i32, 0, 40, 0].into_iter().enumerate() {
+            bad[next + i * 4..][..4].copy_from_slice(&v.to_le_bytes());
+        }
+        assert_eq!(
+            Grid::from_snapshot(&bad).unwrap_err(),
+            SnapshotError::Corrupt
+        );
+
+        let mut bad = bytes.clone();
+        bad.push(0);
+        assert_eq!(
+            Grid::from_snapshot(&bad).unwrap_err(),
+            SnapshotError::TrailingBytes
+        );
+
+        // A header announcing a huge world must fail on length, not allocate.
+        let mut bad = bytes;
+        bad[6..14].copy_from_slice(&[0xFF; 8]);
+        let err = Grid::from_snapshot(&bad).unwrap_err();
+        assert!(matches!(
+            err,
+            SnapshotError::Truncated | SnapshotError::TooLarge
+        ));
+    }
+
+    // Loading a snapshot mid-run and ticking on must match the original run.
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+        #[test]
+        fn prop_loaded_snapshot_replays_identically(
+            grid in arb_grid(40, 36),
+            before in 0u32..10,
+            after in 1u32..20,
+        ) {
+            let mut original = grid;
+            for _ in 0..before {
+                original.tick();
+            }
+            let mut loaded = Grid::from_snapshot(&original.to_snapshot()).unwrap();
+            for _ in 0..after {
+                original.tick();
+                loaded.tick();
+            }
+            prop_assert_eq!(&loaded.cells, &original.cells);
+            prop_assert_eq!(&loaded.heat, &original.heat);
+            prop_assert_eq!(loaded.rng, original.rng);
+        }
+
+        #[test]
+        fn prop_random_bytes_never_panic(bytes in proptest::collection::vec(any::<u8>(), 0..256)) {
+            let _ = Grid::from_snapshot(&bytes);
+        }
+    }
+}
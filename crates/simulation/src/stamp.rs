@@ -0,0 +1,248 @@
+//! Built-in stamps: fixed sprites placed whole with one call.
+//!
+//! A [`Stamp`] is a picture of rows of characters plus a legend mapping
+//! each character to a species and, optionally, an `rb` role. `.` is
+//! transparent and leaves the grid untouched. The ghost sprite lives here
+//! next to the `RB_*` roles in `elements::ghost` that its legend uses, so
+//! the frontend never has to know the layout.
+//!
+//! [`Grid::place_stamp`] centres a stamp on a point and writes every cell,
+//! or nothing at all if any of them would land off the grid or on a
+//! non-empty cell. All ghost cells of one placement share a freshly
+//! allocated ghost group.
+
+use crate::cell::Species;
+use crate::elements::ghost::{RB_BODY, RB_EYE_ZONE};
+use crate::elements::spawn_cell;
+use crate::Grid;
+use std::fmt;
+
+/// What a stamp character places.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct StampGlyph {
+    pub glyph: char,
+    pub species: Species,
+    /// `rb` to write over the species' own initialization, e.g. a ghost
+    /// cell's visual role.
+    pub rb: Option<u8>,
+}
+
+/// A named sprite; see the [module docs](self).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Stamp {
+    pub name: &'static str,
+    /// Rows top first, all the same width.
+    pub rows: &'static [&'static str],
+    pub legend: &'static [StampGlyph],
+}
+
+/// 19×21 ghost: tall smooth dome, wide body, short subtle tendrils.
+/// `*` is body and `Z` eye zone, where `update_ghost_eyes` draws the eyes.
+pub const GHOST: Stamp = Stamp {
+    name: "ghost",
+    rows: &[
+        "......*******......",
+        "....***********....",
+        "...*************...",
+        "..***************..",
+        ".*****************.",
+        ".*****************.",
+        "*******************",
+        "*****ZZZZ*ZZZZ*****",
+        "*****ZZZZ*ZZZZ*****",
+        "*****ZZZZ*ZZZZ*****",
+        "*****ZZZZ*ZZZZ*****",
+        "*****ZZZZ*ZZZZ*****",
+        "*******************",
+        "*******************",
+        "*******************",
+        "*******************",
+        "*******************",
+        "*******************",
+        "******.*****.******",
+        "*****..*****..****.",
+        "****...****....*...",
+    ],
+    legend: &[
+        StampGlyph {
+            glyph: '*',
+            species: Species::Ghost,
+            rb: Some(RB_BODY),
+        },
+        StampGlyph {
+            glyph: 'Z',
+            species: Species::Ghost,
+            rb: Some(RB_EYE_ZONE),
+        },
+    ],
+};
+
+/// Every built-in stamp.
+pub const STAMPS: &[Stamp] = &[GHOST];
+
+/// The built-in stamp called `name`.
+#[must_use]
+pub fn stamp(name: &str) -> Option<&'static Stamp> {
+    STAMPS.iter().find(|stamp| stamp.name == name)
+}
+
+impl Stamp {
+    #[must_use]
+    pub fn width(&self) -> usize {
+        self.rows.first().map_or(0, |row| row.chars().count())
+    }
+
+    #[must_use]
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Each non-transparent cell as `(x, y, glyph)`, relative to the
+    /// top-left corner. Characters missing from the legend are skipped.
+    pub fn cells(&self) -> impl Iterator<Item = (usize, usize, &StampGlyph)> + '_ {
+        self.rows.iter().enumerate().flat_map(move |(y, row)| {
+            row.chars().enumerate().filter_map(move |(x, ch)| {
+                self.legend
+                    .iter()
+                    .find(|g| g.glyph == ch)
+                    .map(|glyph| (x, y, glyph))
+            })
+        })
+    }
+}
+
+/// A stamp could not be placed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum StampError {
+    /// No built-in stamp has the requested name.
+    UnknownStamp(String),
+    /// A cell of the stamp centred on `(x, y)` would land off the grid or
+    /// on material.
+    NoRoom { x: i32, y: i32 },
+}
+
+impl fmt::Display for StampError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownStamp(name) => write!(f, "no stamp named {name:?}"),
+            Self::NoRoom { x, y } => write!(f, "no room for the stamp at ({x}, {y})"),
+        }
+    }
+}
+
+impl std::error::Error for StampError {}
+
+impl Grid {
+    /// Place the built-in stamp `name` centred on `(x, y)`; see the
+    /// [module docs](self).
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`StampError`] if there is no such stamp, or if any of its
+    /// cells would land off the grid or on a non-empty cell. Nothing is
+    /// written in either case.
+    pub fn place_stamp(&mut self, name: &str, x: i32, y: i32) -> Result<(), StampError> {
+        let stamp = stamp(name).ok_or_else(|| StampError::UnknownStamp(name.to_owned()))?;
+        let left = x - (stamp.width() / 2) as i32;
+        let top = y - (stamp.height() / 2) as i32;
+        let at = |cx: usize, cy: usize| (left + cx as i32, top + cy as i32);
+
+        let blocked = stamp.cells().any(|(cx, cy, _)| {
+            let (gx, gy) = at(cx, cy);
+            !self.in_bounds(gx, gy) || self.get(gx, gy).species != Species::Empty
+        });
+        if blocked {
+            return Err(StampError::NoRoom { x, y });
+        }
+
+        let group = stamp
+            .cells()
+            .any(|(_, _, g)| g.species == Species::Ghost)
+            .then(|| self.alloc_ghost_group());
+        for (cx, cy, glyph) in stamp.cells() {
+            let mut cell = spawn_cell(glyph.species, &mut self.rng);
+            if let Some(rb) = glyph.rb {
+                cell.rb = rb;
+            }
+            if let (Species::Ghost, Some(group)) = (glyph.species, group) {
+                cell.ra = group;
+            }
+            let (gx, gy) = at(cx, cy);
+            self.edit(gx, gy, cell);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cell::Cell;
+
+    #[test]
+    fn stamps_are_rectangular_and_fully_described() {
+        for stamp in STAMPS {
+            for row in stamp.rows {
+                assert_eq!(
+                    row.chars().count(),
+                    stamp.width(),
+                    "{} has a ragged row",
+                    stamp.name
+                );
+                for ch in row.chars().filter(|&ch| ch != '.') {
+                    assert!(
+                        stamp.legend.iter().any(|g| g.glyph == ch),
+                        "{}: {ch:?} not in legend",
+                        stamp.name
+                    );
+                }
+            }
+        }
+        assert_eq!((GHOST.width(), GHOST.height()), (19, 21));
+    }
+
+    #[test]
+    fn ghost_is_one_new_group_with_eye_zones() {
+        let mut g = Grid::new(40, 40);
+        let first = g.alloc_ghost_group();
+        g.place_stamp("ghost", 20, 20).unwrap();
+
+        let ghosts: Vec<Cell> = g
+            .cells
+            .iter()
+            .copied()
+            .filter(|c| c.species == Species::Ghost)
+            .collect();
+        assert_eq!(ghosts.len(), GHOST.cells().count());
+        assert!(ghosts.iter().all(|c| c.ra == first + 1));
+        assert_eq!(ghosts.iter().filter(|c| c.rb == RB_EYE_ZONE).count(), 40);
+        // Centred: the top row's run of body starts 6 cells in from x = 20 - 9.
+        assert_eq!(g.get(17, 10).species, Species::Ghost);
+        assert_eq!(g.get(16, 10).species, Species::Empty);
+        assert_eq!(g.get(11, 16).species, Species::Ghost);
+    }
+
+    #[test]
+    fn no_room_writes_nothing() {
+        let mut g = Grid::new(40, 40);
+        let group = g.next_ghost_group;
+        assert_eq!(
+            g.place_stamp("ghost", 5, 20),
+            Err(StampError::NoRoom { x: 5, y: 20 }),
+            "off the left edge"
+        );
+
+        g.set(20, 20, Cell::wall());
+        let err = g.place_stamp("ghost", 20, 20).unwrap_err();
+        assert_eq!(err, StampError::NoRoom { x: 20, y: 20 });
+        assert_eq!(err.to_string(), "no room for the stamp at (20, 20)");
+        assert!(g.cells.iter().all(|c| c.species != Species::Ghost));
+        assert_eq!(g.next_ghost_group, group, "no group is used up");
+
+        assert_eq!(
+            g.place_stamp("dragon", 20, 20),
+            Err(StampError::UnknownStamp("dragon".into()))
+        );
+    }
+}
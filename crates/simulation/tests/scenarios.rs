@@ -0,0 +1,250 @@
+//! Golden scenario regression suite.
+//!
+//! Every `*.txt` file in `tests/scenarios/` describes a world, a tick
+//! count and the expected outcome:
+//!
+//! ```text
+//! // Sand dropped onto a floor piles up.
+//! ticks: 40
+//!
+//! input:
+//! ..s..
+//! .....
+//! #####
+//!
+//! expected:
+//! .....
+//! ..s..
+//! #####
+//! ```
+//!
+//! The input is either an ASCII picture (see `simulation::ascii`) in an
+//! `input:` block, or `input: <file>` naming a snapshot or compact
+//! encoding next to the scenario. The outcome is checked against an
+//! `expected:` picture, a `hash:` of the final `Grid::state_hash`, or
+//! both. Lines starting with `//` before the first block are comments.
+//!
+//! When a behaviour change is intentional, rerun with `BLESS=1` to rewrite
+//! the expected pictures and hashes from the current engine, then review
+//! the diff. Scenarios with no expectation yet get a picture (ASCII input)
+//! or a hash (file input).
+
+use simulation::{ascii, Grid};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const BLESS_VAR: &str = "BLESS";
+
+#[derive(Debug, Default)]
+struct Scenario {
+    /// `//` comment lines from the header, kept for blessing.
+    comments: Vec<String>,
+    ticks: u64,
+    input_file: Option<String>,
+    input_picture: Option<String>,
+    expected_picture: Option<String>,
+    hash: Option<u64>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Block {
+    Header,
+    Input,
+    Expected,
+}
+
+fn parse(text: &str) -> Result<Scenario, String> {
+    let mut scenario = Scenario::default();
+    let mut ticks = None;
+    let mut block = Block::Header;
+    let mut input = String::new();
+    let mut expected = String::new();
+    for line in text.lines() {
+        let trimmed = line.trim();
+        match trimmed {
+            "input:" => block = Block::Input,
+            "expected:" => block = Block::Expected,
+            _ if block == Block::Input => {
+                let _ = writeln!(input, "{line}");
+            }
+            _ if block == Block::Expected => {
+                let _ = writeln!(expected, "{line}");
+            }
+            _ if trimmed.is_empty() => {}
+            _ if trimmed.starts_with("//") => scenario.comments.push(trimmed.to_owned()),
+            _ => {
+                let (key, value) = trimmed
+                    .split_once(':')
+                    .ok_or_else(|| format!("expected `key: value`, got `{trimmed}`"))?;
+                let value = value.trim();
+                match key.trim() {
+                    "ticks" => {
+                        ticks = Some(
+                            value
+                                .parse()
+                                .map_err(|err| format!("bad tick count: {err}"))?,
+                        );
+                    }
+                    "input" => scenario.input_file = Some(value.to_owned()),
+                    "hash" => {
+                        let hash = u64::from_str_radix(value, 16)
+                            .map_err(|err| format!("bad hash: {err}"))?;
+                        scenario.hash = Some(hash);
+                    }
+                    other => return Err(format!("unknown key `{other}`")),
+                }
+            }
+        }
+    }
+    scenario.ticks = ticks.ok_or("missing `ticks:`")?;
+    if !input.trim().is_empty() {
+        scenario.input_picture = Some(input);
+    }
+    if !expected.trim().is_empty() {
+        scenario.expected_picture = Some(expected);
+    }
+    if scenario.input_picture.is_some() == scenario.input_file.is_some() {
+        return Err("needs exactly one of an `input:` picture or `input: <file>`".into());
+    }
+    Ok(scenario)
+}
+
+/// Write `scenario` back out with its expectations replaced by `grid`'s.
+fn render(scenario: &Scenario, grid: &Grid) -> String {
+    let mut out = String::new();
+    for comment in &scenario.comments {
+        let _ = writeln!(out, "{comment}");
+    }
+    let _ = writeln!(out, "ticks: {}", scenario.ticks);
+    if let Some(file) = &scenario.input_file {
+        let _ = writeln!(out, "input: {file}");
+    }
+    let wants_hash = scenario.hash.is_some()
+        || scenario.input_file.is_some() && scenario.expected_picture.is_none();
+    if wants_hash {
+        let _ = writeln!(out, "hash: {:016x}", grid.state_hash());
+    }
+    if let Some(picture) = &scenario.input_picture {
+        let _ = write!(
+            out,
+            "\ninput:\n{}",
+            Grid::from_ascii(picture)
+                .map(|g| g.to_ascii())
+                .unwrap_or_default()
+        );
+    }
+    if scenario.expected_picture.is_some() || !wants_hash {
+        let _ = write!(out, "\nexpected:\n{grid}");
+    }
+    out
+}
+
+fn load_input(scenario: &Scenario, dir: &Path) -> Result<Grid, String> {
+    if let Some(picture) = &scenario.input_picture {
+        return Grid::from_ascii(picture).map_err(|err| format!("input picture: {err}"));
+    }
+    let file = scenario.input_file.as_deref().unwrap_or_default();
+    let bytes = fs::read(dir.join(file)).map_err(|err| format!("reading {file}: {err}"))?;
+    if bytes.starts_with(b"SNDC") {
+        Grid::from_compact(&bytes).map_err(|err| format!("{file}: {err}"))
+    } else {
+        Grid::from_snapshot(&bytes).map_err(|err| format!("{file}: {err}"))
+    }
+}
+
+/// Run one scenario; returns a failure message, or blesses it.
+fn check(path: &Path, bless: bool) -> Result<(), String> {
+    let text = fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let scenario = parse(&text)?;
+    let mut grid = load_input(&scenario, path.parent().unwrap_or(Path::new(".")))?;
+    for _ in 0..scenario.ticks {
+        grid.tick();
+    }
+
+    if bless {
+        let blessed = render(&scenario, &grid);
+        if blessed != text {
+            fs::write(path, blessed).map_err(|err| err.to_string())?;
+        }
+        return Ok(());
+    }
+
+    if scenario.expected_picture.is_none() && scenario.hash.is_none() {
+        return Err(format!(
+            "no expectation; run with {BLESS_VAR}=1 to record one"
+        ));
+    }
+    if let Some(picture) = &scenario.expected_picture {
+        let expected =
+            Grid::from_ascii(picture).map_err(|err| format!("expected picture: {err}"))?;
+        if let Some(diff) = ascii::diff(&grid, &expected) {
+            return Err(diff);
+        }
+    }
+    if let Some(hash) = scenario.hash {
+        let actual = grid.state_hash();
+        if actual != hash {
+            return Err(format!(
+                "state hash {actual:016x}, expected {hash:016x}\n{grid}"
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn scenario_paths() -> Vec<PathBuf> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/scenarios");
+    let mut paths: Vec<PathBuf> = fs::read_dir(&dir)
+        .expect("tests/scenarios exists")
+        .map(|entry| entry.expect("readable directory entry").path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+        .collect();
+    paths.sort();
+    paths
+}
+
+#[test]
+fn golden_scenarios() {
+    let bless = std::env::var_os(BLESS_VAR).is_some_and(|v| v != "0");
+    let paths = scenario_paths();
+    assert!(!paths.is_empty(), "no scenarios found");
+
+    let mut failures = String::new();
+    for path in &paths {
+        if let Err(message) = check(path, bless) {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            let _ = writeln!(failures, "--- {name}\n{message}");
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "scenarios failed (rerun with {BLESS_VAR}=1 if the change is intended):\n{failures}"
+    );
+}
+
+#[test]
+fn parses_and_renders_the_format() {
+    let text = "// c\nticks: 3\nhash: 00ff\n\ninput:\n  .s.\n  ###\n\nexpected:\n...\n";
+    let scenario = parse(text).unwrap();
+    assert_eq!(scenario.ticks, 3);
+    assert_eq!(scenario.hash, Some(0xff));
+    assert_eq!(scenario.comments, ["// c"]);
+    assert!(scenario.input_picture.as_deref().unwrap().contains(".s."));
+    assert!(scenario.expected_picture.is_some());
+
+    let grid = Grid::from_ascii(".s. / ###").unwrap();
+    let rendered = render(&scenario, &grid);
+    assert!(rendered.starts_with("// c\nticks: 3\nhash: "), "{rendered}");
+    assert!(
+        rendered.ends_with("\ninput:\n.s.\n###\n\nexpected:\n.s.\n###\n"),
+        "{rendered}"
+    );
+
+    assert!(parse("input:\n.\n").is_err(), "missing ticks");
+    assert!(parse("ticks: 1\n").is_err(), "missing input");
+    assert!(
+        parse("ticks: 1\nspeed: 2\ninput:\n.\n").is_err(),
+        "unknown key"
+    );
+}